@@ -0,0 +1,68 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+// The days that have been solved, as in `Config::SOLVED_DAYS`.
+const SOLVED_DAYS: std::ops::RangeInclusive<usize> = 1..=15;
+
+fn main() {
+    embed_inputs();
+
+    // What the solved days share -- parsing, tracing, checked arithmetic -- is only used when one
+    // of them is compiled in, which `cfg(any_solved_day)` says.
+    println!("cargo:rustc-check-cfg=cfg(any_solved_day)");
+    if SOLVED_DAYS
+        .clone()
+        .any(|day| env::var_os(format!("CARGO_FEATURE_DAY_{day}")).is_some())
+    {
+        println!("cargo:rustc-cfg=any_solved_day");
+    }
+
+    // With the `ffi` feature, generate the C header for the FFI layer from `src/ffi.rs`, as
+    // `aoc2022.h` in `OUT_DIR`. The copy in `include/` is checked against it by `tests/ffi.rs`.
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        cbindgen::generate_with_config(
+            &crate_dir,
+            cbindgen::Config::from_file(Path::new(&crate_dir).join("cbindgen.toml")).unwrap(),
+        )
+        .expect("the FFI layer should be expressible in C")
+        .write_to_file(Path::new(&env::var("OUT_DIR").unwrap()).join("aoc2022.h"));
+    }
+}
+
+// Embed each compiled-in day's puzzle input, as `INPUTS` in `src/input.rs`: the plaintext if it's
+// there, or else the encrypted input, to be decrypted when it's loaded.
+fn embed_inputs() {
+    println!("cargo:rerun-if-changed=src/input");
+    let inputs = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src/input");
+
+    let mut embedded = "static INPUTS: [Embedded; 25] = [\n".to_string();
+    for day in 1..=25 {
+        let plain = inputs.join(format!("day_{day}.txt"));
+        let encrypted = inputs.join(format!("day_{day}.txt.enc"));
+        if env::var_os(format!("CARGO_FEATURE_DAY_{day}")).is_none() {
+            embedded.push_str("    Embedded::Missing,\n");
+        } else if plain.exists() {
+            writeln!(embedded, "    Embedded::Plain(include_str!({plain:?})),").unwrap();
+        } else if encrypted.exists() {
+            writeln!(
+                embedded,
+                "    Embedded::Encrypted(include_bytes!({encrypted:?})),"
+            )
+            .unwrap();
+        } else {
+            embedded.push_str("    Embedded::Missing,\n");
+        }
+    }
+    embedded.push_str("];\n");
+    fs::write(
+        Path::new(&env::var("OUT_DIR").unwrap()).join("inputs.rs"),
+        embedded,
+    )
+    .unwrap();
+}
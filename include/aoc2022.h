@@ -0,0 +1,57 @@
+/* Generated from src/ffi.rs by the build script -- do not edit. */
+
+#ifndef AOC2022_H
+#define AOC2022_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// What became of a call to `aoc2022_solve`.
+typedef enum Aoc2022Status {
+  // The answer is in the output buffer.
+  AOC2022_STATUS_OK = 0,
+  // The day hasn't been solved yet, or its solver isn't compiled in.
+  AOC2022_STATUS_NO_SUCH_DAY = 1,
+  // There is no such part: every day has parts 1 and 2.
+  AOC2022_STATUS_NO_SUCH_PART = 2,
+  // The input isn't valid UTF-8.
+  AOC2022_STATUS_INVALID_UTF8 = 3,
+  // The input isn't a well-formed puzzle input for the day.
+  AOC2022_STATUS_PARSE_ERROR = 4,
+  // The solver panicked.
+  AOC2022_STATUS_PANICKED = 5,
+  // The answer doesn't fit in the output buffer, which is left alone.
+  AOC2022_STATUS_BUFFER_TOO_SMALL = 6,
+  // A pointer that can't be null was.
+  AOC2022_STATUS_NULL_POINTER = 7,
+} Aoc2022Status;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Solve `part` (1 or 2) of `day` for the `input_len` bytes of puzzle input at `input`, with the
+// puzzle's own parameters.
+//
+// On entry, `*out_len` is the size of `out_buf`. The answer is written to `out_buf` as it would be
+// printed, NUL-terminated, and `*out_len` is set to its length, not counting the NUL. If it doesn't
+// fit, the status is `AOC2022_STATUS_BUFFER_TOO_SMALL` and `*out_len` is set to the length it
+// needs, again not counting the NUL. After any other error, `out_buf` holds a message saying what
+// went wrong, truncated to fit, and `*out_len` its length.
+//
+// # Safety
+//
+// `input` must point to `input_len` readable bytes, and `out_buf` to `*out_len` writable bytes.
+// Either may be null when its length is 0.
+enum Aoc2022Status aoc2022_solve(uint32_t day,
+                                 uint32_t part,
+                                 const uint8_t *input,
+                                 size_t input_len,
+                                 char *out_buf,
+                                 size_t *out_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AOC2022_H */
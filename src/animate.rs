@@ -0,0 +1,262 @@
+use crate::simulation::Simulation;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+// Play a simulation live in the terminal, redrawing its state in place.
+//
+// While it plays: space pauses and resumes, 'n' (or the right arrow) steps once and pauses, '+' and
+// '-' double and halve the frame rate, and 'q' (or escape, or ctrl-c) stops.
+//
+// When stdout isn't a terminal there's nothing to redraw, and no one to press keys, so every state
+// is printed one after the other instead.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Pause,
+    Step,
+    Faster,
+    Slower,
+    Quit,
+}
+
+pub trait Controls {
+    // Wait up to `timeout` (or forever, if there's none) for the next control.
+    fn wait(&mut self, timeout: Option<Duration>) -> io::Result<Option<Control>>;
+}
+
+const MAX_FPS: u32 = 960;
+
+// Move the cursor to the top left, clear each line after it's drawn, then clear whatever is left of
+// the previous (possibly taller) frame below.
+const HOME: &str = "\x1b[H";
+const CLEAR_LINE: &str = "\x1b[K";
+const CLEAR_BELOW: &str = "\x1b[J";
+const CLEAR_SCREEN: &str = "\x1b[2J";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+const KEYS: &str = "space: pause, n: step, +/-: speed, q: quit";
+
+pub fn play<S: Simulation>(simulation: &mut S, fps: u32) -> io::Result<()> {
+    let output = &mut io::stdout().lock();
+    if !output.is_terminal() {
+        return print_frames(simulation, output);
+    }
+
+    let _terminal = RawTerminal::enable(output)?;
+    animate(simulation, fps, &mut Keyboard, output)
+}
+
+pub fn print_frames<S: Simulation>(simulation: &mut S, output: &mut impl Write) -> io::Result<()> {
+    let mut steps = 0;
+    loop {
+        write!(output, "{}", simulation.render())?;
+        writeln!(output, "step {steps}")?;
+        if simulation.is_done() {
+            return Ok(());
+        }
+        writeln!(output)?;
+        simulation.step();
+        steps += 1;
+    }
+}
+
+pub fn animate<S: Simulation>(
+    simulation: &mut S,
+    fps: u32,
+    controls: &mut impl Controls,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let mut fps = fps.clamp(1, MAX_FPS);
+    let mut paused = false;
+    let mut steps = 0;
+    let mut next_frame = Instant::now();
+
+    loop {
+        draw(simulation, steps, fps, paused, output)?;
+        if simulation.is_done() {
+            return Ok(());
+        }
+
+        let timeout = (!paused).then(|| next_frame.saturating_duration_since(Instant::now()));
+        match controls.wait(timeout)? {
+            None => {
+                simulation.step();
+                steps += 1;
+                next_frame = Instant::now() + Duration::from_secs(1) / fps;
+            }
+            Some(Control::Pause) => paused = !paused,
+            Some(Control::Step) => {
+                paused = true;
+                simulation.step();
+                steps += 1;
+            }
+            Some(Control::Faster) => fps = (fps * 2).min(MAX_FPS),
+            Some(Control::Slower) => fps = (fps / 2).max(1),
+            Some(Control::Quit) => return Ok(()),
+        }
+    }
+}
+
+// The terminal is in raw mode while animating, so every line needs its carriage return too.
+fn draw<S: Simulation>(
+    simulation: &S,
+    steps: usize,
+    fps: u32,
+    paused: bool,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    write!(output, "{HOME}")?;
+    for line in simulation.render().lines() {
+        write!(output, "{line}{CLEAR_LINE}\r\n")?;
+    }
+    let state = if simulation.is_done() {
+        "done"
+    } else if paused {
+        "paused"
+    } else {
+        "playing"
+    };
+    write!(
+        output,
+        "step {steps} ({state}, {fps} fps) -- {KEYS}{CLEAR_LINE}\r\n"
+    )?;
+    write!(output, "{CLEAR_BELOW}")?;
+    output.flush()
+}
+
+struct Keyboard;
+
+impl Controls for Keyboard {
+    fn wait(&mut self, timeout: Option<Duration>) -> io::Result<Option<Control>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            if let Some(deadline) = deadline {
+                if !event::poll(deadline.saturating_duration_since(Instant::now()))? {
+                    return Ok(None);
+                }
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            let control = match key.code {
+                KeyCode::Char(' ') => Control::Pause,
+                KeyCode::Char('n') | KeyCode::Right => Control::Step,
+                KeyCode::Char('+') | KeyCode::Char('=') => Control::Faster,
+                KeyCode::Char('-') => Control::Slower,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Control::Quit
+                }
+                KeyCode::Char('q') | KeyCode::Esc => Control::Quit,
+                _ => continue,
+            };
+            return Ok(Some(control));
+        }
+    }
+}
+
+// Raw mode lets single key presses through without waiting for enter; it's switched off again (and
+// the cursor shown) however the animation ends.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enable(output: &mut impl Write) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        write!(output, "{CLEAR_SCREEN}{HIDE_CURSOR}")?;
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let mut output = io::stdout();
+        let _ = write!(output, "{SHOW_CURSOR}");
+        let _ = output.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Countdown(usize);
+
+    impl Simulation for Countdown {
+        type Snapshot = usize;
+
+        fn step(&mut self) {
+            self.0 -= 1;
+        }
+
+        fn is_done(&self) -> bool {
+            self.0 == 0
+        }
+
+        fn snapshot(&self) -> usize {
+            self.0
+        }
+
+        fn render(&self) -> String {
+            format!("{}\n", self.0)
+        }
+    }
+
+    // Plays back a script of controls, in place of key presses; `None` lets a frame go by.
+    struct Script(Vec<Option<Control>>);
+
+    impl Controls for Script {
+        fn wait(&mut self, timeout: Option<Duration>) -> io::Result<Option<Control>> {
+            let control = self.0.remove(0);
+            assert!(timeout.is_some() || control.is_some(), "waited forever");
+            Ok(control)
+        }
+    }
+
+    fn animated(start: usize, script: Vec<Option<Control>>) -> (usize, String) {
+        let (countdown, mut output) = (&mut Countdown(start), Vec::new());
+        animate(countdown, MAX_FPS, &mut Script(script), &mut output).unwrap();
+        (countdown.0, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn animate_redraws_in_place() {
+        let (left, output) = animated(2, vec![None, None]);
+        assert_eq!(left, 0);
+        let frames = output.split(HOME).skip(1).collect::<Vec<_>>();
+        assert_eq!(frames.len(), 3);
+        let first = format!("2{CLEAR_LINE}\r\nstep 0 (playing, {MAX_FPS} fps)");
+        assert!(frames[0].starts_with(&first));
+        assert!(frames[2].starts_with(&format!("0{CLEAR_LINE}\r\nstep 2 (done, ")));
+        assert!(frames.iter().all(|frame| frame.ends_with(CLEAR_BELOW)));
+    }
+
+    #[test]
+    fn animate_controls() {
+        use Control::*;
+        let (left, output) = animated(5, vec![Some(Pause), Some(Step), Some(Pause), Some(Quit)]);
+        assert_eq!(left, 4);
+        assert!(output.contains("step 0 (paused, "));
+        assert!(output.contains("step 1 (paused, "));
+        assert!(output.contains("step 1 (playing, "));
+
+        let (_, output) = animated(1, vec![Some(Slower), Some(Slower), Some(Faster), None]);
+        assert!(output.contains(&format!("(playing, {} fps)", MAX_FPS / 4)));
+        assert!(output.contains(&format!("(done, {} fps)", MAX_FPS / 2)));
+    }
+
+    #[test]
+    fn animate_prints_plain_frames() {
+        let mut output = Vec::new();
+        print_frames(&mut Countdown(2), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "2\nstep 0\n\n1\nstep 1\n\n0\nstep 2\n"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::compare::{answers, normalize, write_answer};
+use crate::params::Params;
+use crate::solve_isolated;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// Run a day on every input in a directory -- inputs from several people's accounts, say -- rather
+// than just the one that's embedded, to catch a solution that only works on one of them. A day's
+// inputs are `<dir>/day_<N>/*.txt`, and each can have a sidecar file of the answers it should
+// get, `<name>.answers`, written the way they're printed: part 1's on the first line, and part 2's
+// after it. Either can be left out, and then that part isn't checked.
+
+// The answers for one input, and what they should have been.
+pub struct Run {
+    day: usize,
+    name: String,
+    answers: [Result<String, String>; 2],
+    expected: [Option<String>; 2],
+    took: Duration,
+}
+
+impl Run {
+    // Whether a part's answer is right, if there's an answer to check it against.
+    fn is_right(&self, part: usize) -> Option<bool> {
+        match (&self.answers[part], &self.expected[part]) {
+            (Ok(answer), Some(expected)) => Some(normalize(answer) == *expected),
+            (Err(_), _) => Some(false),
+            (Ok(_), None) => None,
+        }
+    }
+
+    fn went_wrong(&self) -> bool {
+        (0..2).any(|part| self.is_right(part) == Some(false))
+    }
+}
+
+#[derive(Default)]
+pub struct Results {
+    runs: Vec<Run>,
+}
+
+impl Results {
+    pub fn push(&mut self, run: Run) {
+        self.runs.push(run);
+    }
+
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    // How many inputs had an answer that was wrong, or no answer at all.
+    pub fn went_wrong(&self) -> usize {
+        self.runs.iter().filter(|run| run.went_wrong()).count()
+    }
+}
+
+// A day's inputs, in order of name. A day without a directory has none.
+pub fn inputs(dir: &Path, day: usize) -> io::Result<Vec<PathBuf>> {
+    let dir = dir.join(format!("day_{day}"));
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut inputs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "txt") {
+            inputs.push(path);
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+// Solve both parts of `day` for the input at `path`, and read the answers it should get.
+pub fn run(day: usize, path: &Path, params: &Params) -> Result<Run, String> {
+    let input =
+        fs::read_to_string(path).map_err(|err| format!("can't read {}: {err}", path.display()))?;
+    let sidecar = path.with_extension("answers");
+    let expected = match fs::read_to_string(&sidecar) {
+        Ok(expected) => answers(&expected),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => [None, None],
+        Err(err) => return Err(format!("can't read {}: {err}", sidecar.display())),
+    };
+
+    let started = Instant::now();
+    let answers = [1, 2].map(|part| solve_isolated(day, part, &input, params));
+    Ok(Run {
+        day,
+        name: path
+            .file_stem()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+        answers,
+        expected,
+        took: started.elapsed(),
+    })
+}
+
+// An answer as it fits in a cell of the table: pictures are too big to.
+fn cell(run: &Run, part: usize) -> String {
+    let answer = match &run.answers[part] {
+        Ok(answer) if answer.trim_end().contains('\n') => {
+            format!("[{} lines]", normalize(answer).lines().count())
+        }
+        Ok(answer) => answer.trim_end().to_string(),
+        Err(_) => "failed".to_string(),
+    };
+    match run.is_right(part) {
+        Some(true) => format!("{answer} ✓"),
+        Some(false) => format!("{answer} ✗"),
+        None => answer,
+    }
+}
+
+// A table with a row for each input, followed by what went wrong, in full.
+impl fmt::Display for Results {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut rows = vec![["Day", "Input", "Part 1", "Part 2", "Took"].map(String::from)];
+        rows.extend(self.runs.iter().map(|run| {
+            [
+                run.day.to_string(),
+                run.name.clone(),
+                cell(run, 0),
+                cell(run, 1),
+                format!("{:.1?}", run.took),
+            ]
+        }));
+        let mut widths = [0; 5];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for row in &rows {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        for run in self.runs.iter().filter(|run| run.went_wrong()) {
+            for (part, answer) in (1..).zip(&run.answers) {
+                let heading = format!("\nday {}, {}: part {part}", run.day, run.name);
+                match (answer, &run.expected[part - 1]) {
+                    (Err(err), _) => writeln!(f, "{heading} failed: {err}")?,
+                    (Ok(answer), Some(expected)) if normalize(answer) != *expected => {
+                        writeln!(f, "{heading} is wrong:")?;
+                        write_answer(f, "  got:", &normalize(answer))?;
+                        write_answer(f, "  wanted:", expected)?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "day-1"))]
+mod tests {
+    use super::*;
+    use crate::day_1::EXAMPLE;
+    use std::env;
+
+    #[test]
+    fn batch_runs_every_input_and_checks_its_answers() {
+        let dir = env::temp_dir().join(format!("aoc_2022_batch_{}", std::process::id()));
+        let day = dir.join("day_1");
+        fs::create_dir_all(&day).unwrap();
+        for (name, input, expected) in [
+            ("alice", EXAMPLE, Some("24000\n45000\n")),
+            ("bob", EXAMPLE, Some("24000\n45001\n")),
+            ("carol", "one\n", Some("1\n")),
+            ("dave", EXAMPLE, None),
+            ("erin", EXAMPLE, Some("24000")),
+        ] {
+            fs::write(day.join(format!("{name}.txt")), input).unwrap();
+            if let Some(expected) = expected {
+                fs::write(day.join(format!("{name}.answers")), expected).unwrap();
+            }
+        }
+        fs::write(day.join("notes.md"), "not an input").unwrap();
+
+        let paths = inputs(&dir, 1).unwrap();
+        assert_eq!(paths.len(), 5);
+        assert!(inputs(&dir, 2).unwrap().is_empty());
+
+        let params = Params::defaults(&[]);
+        let mut results = Results::default();
+        for path in &paths {
+            let mut run = run(1, path, &params).unwrap();
+            run.took = Duration::from_millis(1);
+            results.push(run);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(results.went_wrong(), 2);
+        assert_eq!(
+            results.to_string(),
+            "Day  Input  Part 1    Part 2    Took\n\
+             1    alice  24000 ✓   45000 ✓   1.0ms\n\
+             1    bob    24000 ✓   45000 ✗   1.0ms\n\
+             1    carol  failed ✗  failed ✗  1.0ms\n\
+             1    dave   24000     45000     1.0ms\n\
+             1    erin   24000 ✓   45000     1.0ms\n\
+             \nday 1, bob: part 2 is wrong:\n  got:    45000\n  wanted: 45001\n\
+             \nday 1, carol: part 1 failed: Invalid puzzle input: 'one' is not a valid number\n\
+             \nday 1, carol: part 2 failed: Invalid puzzle input: 'one' is not a valid number\n"
+        );
+    }
+}
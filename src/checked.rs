@@ -0,0 +1,191 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::cell::Cell;
+use std::fmt::Display;
+use std::ops::{Add, Mul, Sub};
+use std::panic::Location;
+
+// Arithmetic that overflows wraps around silently in release builds, and the solver carries on to
+// a wrong answer. The arithmetic the solvers do on numbers that come from their input -- worry
+// levels, directory sizes, tuning frequencies -- goes through these functions instead, so that in
+// checked mode (`--checked`) an overflow stops the day with an error naming the day and the
+// operation:
+//
+//     panicked at src/day_11.rs:70:46: day 11 overflowed: 6103515625 * 6103515625 doesn't fit in a usize
+//
+// Outside checked mode they're the plain operators, which panic in debug builds and wrap in
+// release builds, as they always have.
+
+thread_local! {
+    static CHECKED: Cell<bool> = const { Cell::new(false) };
+}
+
+// Checked mode is on for the thread that enables it, until the guard is dropped, when it goes back
+// to whatever it was before -- so a test, or a call from C, can't leave it on for everything that
+// runs after it.
+#[must_use = "checked mode is only on until the guard is dropped"]
+pub struct Guard {
+    previous: bool,
+}
+
+pub fn enable() -> Guard {
+    Guard {
+        previous: CHECKED.replace(true),
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        CHECKED.set(self.previous);
+    }
+}
+
+pub fn enabled() -> bool {
+    CHECKED.get()
+}
+
+pub trait Integer:
+    Copy + Display + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const NAME: &'static str;
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! integer {
+    ( $( $type:ty ),* ) => {
+        $(
+            impl Integer for $type {
+                const NAME: &'static str = stringify!($type);
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$type>::checked_add(self, other)
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$type>::checked_sub(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$type>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+integer!(i32, i64, u32, u64, usize);
+
+// The day is the one whose module the operation is in. Every function here tracks its caller, so
+// the panic is reported there too, rather than here.
+#[track_caller]
+fn overflow<T: Integer>(a: T, operator: char, b: T) -> ! {
+    let day = Location::caller()
+        .file()
+        .rsplit(['/', '\\'])
+        .next()
+        .and_then(|file| file.strip_prefix("day_")?.strip_suffix(".rs"))
+        .unwrap_or("?");
+    panic!(
+        "day {day} overflowed: {a} {operator} {b} doesn't fit in a {}",
+        T::NAME
+    );
+}
+
+#[track_caller]
+pub fn add<T: Integer>(a: T, b: T) -> T {
+    if !enabled() {
+        return a + b;
+    }
+    match a.checked_add(b) {
+        Some(result) => result,
+        None => overflow(a, '+', b),
+    }
+}
+
+#[track_caller]
+pub fn sub<T: Integer>(a: T, b: T) -> T {
+    if !enabled() {
+        return a - b;
+    }
+    match a.checked_sub(b) {
+        Some(result) => result,
+        None => overflow(a, '-', b),
+    }
+}
+
+#[track_caller]
+pub fn mul<T: Integer>(a: T, b: T) -> T {
+    if !enabled() {
+        return a * b;
+    }
+    match a.checked_mul(b) {
+        Some(result) => result,
+        None => overflow(a, '*', b),
+    }
+}
+
+#[track_caller]
+pub fn sum<T: Integer>(values: impl IntoIterator<Item = T>) -> T {
+    let mut total = T::ZERO;
+    for value in values {
+        total = add(total, value);
+    }
+    total
+}
+
+#[track_caller]
+pub fn product<T: Integer>(values: impl IntoIterator<Item = T>) -> T {
+    let mut total = T::ONE;
+    for value in values {
+        total = mul(total, value);
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isolation::{self, Outcome};
+
+    #[test]
+    fn checked_reports_the_day_and_operation() {
+        assert_eq!(add(2_u64, 3), 5);
+        assert_eq!(sum([1_usize, 2, 3]), 6);
+        assert_eq!(product([2_i64, -3, 4]), -24);
+
+        let checked = enable();
+        assert_eq!(sub(2_i32, 3), -1);
+        assert_eq!(mul(u64::MAX, 1), u64::MAX);
+
+        let failure = |operation: fn() -> String| match isolation::run(|| {
+            operation();
+            Ok(())
+        }) {
+            Outcome::Failed(failure) => failure,
+            outcome => panic!("{outcome:?} should have been a failure"),
+        };
+        let overflowed = failure(|| mul(usize::MAX / 2, 3).to_string());
+        assert!(overflowed.starts_with("panicked at src/checked.rs:"));
+        assert!(overflowed.ends_with(&format!(
+            ": day ? overflowed: {} * 3 doesn't fit in a usize",
+            usize::MAX / 2
+        )));
+        assert!(failure(|| sub(0_u32, 1).to_string()).ends_with(": 0 - 1 doesn't fit in a u32"));
+        assert!(failure(|| sum([u64::MAX, 1]).to_string())
+            .ends_with(": 18446744073709551615 + 1 doesn't fit in a u64"));
+
+        // Dropping the guard turns checked mode back off, even when it was enabled twice over.
+        let again = enable();
+        drop(again);
+        assert!(enabled());
+        drop(checked);
+        assert!(!enabled());
+    }
+}
@@ -0,0 +1,293 @@
+use crate::params::Params;
+use std::fmt;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Cross-check another implementation of a day -- in another language, say -- against this one.
+// The other program is run through the shell, given the puzzle input on stdin, and expected to
+// print its answers the way this one does: part 1's on the first line, and part 2's on the lines
+// after it, since some answers (day 10's) are pictures several lines high. Trailing whitespace,
+// and blank lines around an answer, don't count.
+//
+// Its runtime is the whole process's, starting it up included, so a comparison with this one's is
+// only a rough guide for days that are quick to solve.
+
+pub struct Comparison {
+    day: usize,
+    parts: [Part; 2],
+    ours_took: Duration,
+    theirs_took: Duration,
+}
+
+struct Part {
+    ours: Result<String, String>,
+    theirs: Option<String>,
+}
+
+impl Part {
+    fn agrees(&self) -> bool {
+        match (&self.ours, &self.theirs) {
+            (Ok(ours), Some(theirs)) => normalize(ours) == normalize(theirs),
+            _ => false,
+        }
+    }
+}
+
+impl Comparison {
+    pub fn agrees(&self) -> bool {
+        self.parts.iter().all(Part::agrees)
+    }
+}
+
+// Solve both parts of `day` for `input`, then have `program` do the same.
+pub fn compare(
+    day: usize,
+    program: &str,
+    input: &str,
+    params: &Params,
+) -> Result<Comparison, String> {
+    let mut ours_took = Duration::ZERO;
+    let ours = [1, 2].map(|part| {
+        let started = Instant::now();
+        let answer = crate::solve_isolated(day, part, input, params);
+        ours_took += started.elapsed();
+        answer
+    });
+
+    let (output, theirs_took) = run(program, input)?;
+    let [theirs_1, theirs_2] = answers(&output);
+    let [ours_1, ours_2] = ours;
+    Ok(Comparison {
+        day,
+        parts: [
+            Part {
+                ours: ours_1,
+                theirs: theirs_1,
+            },
+            Part {
+                ours: ours_2,
+                theirs: theirs_2,
+            },
+        ],
+        ours_took,
+        theirs_took,
+    })
+}
+
+// Run `program` through the shell with `input` on its stdin, and return what it printed, and how
+// long it took. A program that fails is an error, with what it said on stderr.
+fn run(program: &str, input: &str) -> Result<(String, Duration), String> {
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", program]);
+        command
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command.args(["-c", program]);
+        command
+    };
+
+    let started = Instant::now();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("couldn't run `{program}`: {err}"))?;
+
+    // The input is written while the output is read, so that neither can fill its pipe and stall
+    // the program. A program that doesn't read all of its input is fine.
+    let mut stdin = child.stdin.take().unwrap();
+    let (written, output) = thread::scope(|scope| {
+        let writer = scope.spawn(move || match stdin.write_all(input.as_bytes()) {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(err),
+            _ => Ok(()),
+        });
+        let output = child.wait_with_output();
+        (writer.join().unwrap(), output)
+    });
+    let output = output.map_err(|err| format!("couldn't run `{program}`: {err}"))?;
+    let took = started.elapsed();
+    written.map_err(|err| format!("couldn't give `{program}` its input: {err}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "`{program}` failed ({}): {}",
+            output.status,
+            stderr.trim_end()
+        ));
+    }
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| format!("`{program}` printed something that isn't UTF-8"))?;
+    Ok((stdout, took))
+}
+
+// Part 1's answer is the first line that isn't blank, and part 2's is everything after it.
+pub fn answers(output: &str) -> [Option<String>; 2] {
+    let output = output.trim_start();
+    let (first, rest) = output.split_once('\n').unwrap_or((output, ""));
+    [first, rest].map(|answer| {
+        let answer = normalize(answer);
+        (!answer.is_empty()).then_some(answer)
+    })
+}
+
+pub fn normalize(answer: &str) -> String {
+    let lines: Vec<_> = answer.lines().map(str::trim_end).collect();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].join("\n"),
+        _ => String::new(),
+    }
+}
+
+// An answer on the same line as its label, or on the lines below it if it's a picture.
+pub fn write_answer(f: &mut fmt::Formatter, label: &str, answer: &str) -> fmt::Result {
+    if answer.contains('\n') {
+        writeln!(f, "{label}")?;
+        answer
+            .lines()
+            .try_for_each(|line| writeln!(f, "    {line}"))
+    } else {
+        writeln!(f, "{label:<9} {answer}")
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Day {}", self.day)?;
+        for (part, answers) in (1..).zip(&self.parts) {
+            match (&answers.ours, &answers.theirs) {
+                (Ok(ours), _) if answers.agrees() => {
+                    write_answer(f, &format!("part {part} agrees:"), &normalize(ours))?
+                }
+                (ours, theirs) => {
+                    writeln!(f, "part {part} differs:")?;
+                    match ours {
+                        Ok(ours) => write_answer(f, "  ours:", &normalize(ours))?,
+                        Err(err) => writeln!(f, "  ours:   failed: {err}")?,
+                    }
+                    write_answer(f, "  theirs:", theirs.as_deref().unwrap_or("(no answer)"))?;
+                }
+            }
+        }
+
+        let ratio = self.theirs_took.as_secs_f64() / self.ours_took.as_secs_f64();
+        write!(
+            f,
+            "ours took {:.1?}, theirs {:.1?}: ",
+            self.ours_took, self.theirs_took
+        )?;
+        if ratio >= 1.0 {
+            write!(f, "{ratio:.1}x as long")
+        } else {
+            write!(f, "{:.1}x as fast", 1.0 / ratio)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_reads_answers_the_way_they_are_printed() {
+        assert_eq!(
+            answers("24000\n45000\n"),
+            [Some("24000".into()), Some("45000".into())]
+        );
+        assert_eq!(
+            answers("\n24000  \r\n\n##..\n#..#  \n\n"),
+            [Some("24000".into()), Some("##..\n#..#".into())]
+        );
+        assert_eq!(answers("24000"), [Some("24000".into()), None]);
+        assert_eq!(answers(""), [None, None]);
+    }
+
+    #[test]
+    fn compare_reports_agreement_and_mismatches() {
+        let comparison = Comparison {
+            day: 10,
+            parts: [
+                Part {
+                    ours: Ok("13140".into()),
+                    theirs: Some("13140".into()),
+                },
+                Part {
+                    ours: Ok("##..\n#..#\n".into()),
+                    theirs: Some("##..\n#...".into()),
+                },
+            ],
+            ours_took: Duration::from_millis(2),
+            theirs_took: Duration::from_millis(10),
+        };
+        assert!(!comparison.agrees());
+        assert_eq!(
+            comparison.to_string(),
+            "Day 10\n\
+             part 1 agrees: 13140\n\
+             part 2 differs:\n  \
+               ours:\n    ##..\n    #..#\n  \
+               theirs:\n    ##..\n    #...\n\
+             ours took 2.0ms, theirs 10.0ms: 5.0x as long"
+        );
+
+        let comparison = Comparison {
+            parts: [
+                Part {
+                    ours: Err("Invalid puzzle input".into()),
+                    theirs: Some("1".into()),
+                },
+                Part {
+                    ours: Ok("2".into()),
+                    theirs: None,
+                },
+            ],
+            ours_took: Duration::from_millis(4),
+            theirs_took: Duration::from_millis(1),
+            ..comparison
+        };
+        assert_eq!(
+            comparison.to_string(),
+            "Day 10\n\
+             part 1 differs:\n  ours:   failed: Invalid puzzle input\n  theirs: 1\n\
+             part 2 differs:\n  ours:   2\n  theirs: (no answer)\n\
+             ours took 4.0ms, theirs 1.0ms: 4.0x as fast"
+        );
+    }
+
+    #[cfg(all(unix, feature = "day-1"))]
+    #[test]
+    fn compare_runs_the_other_program_on_the_input() {
+        let input = crate::day_1::EXAMPLE;
+        let params = Params::defaults(&[]);
+
+        // A stand-in for another implementation, which reads its input and prints the answers.
+        let comparison = compare(1, "cat > /dev/null; echo 24000; echo 45000", input, &params);
+        assert!(comparison.unwrap().agrees());
+
+        let comparison = compare(1, "wc -l", input, &params).unwrap();
+        assert!(!comparison.agrees());
+        assert!(comparison
+            .to_string()
+            .contains("part 1 differs:\n  ours:   24000\n  theirs: 14\n"));
+
+        // One that stops reading part-way through a large input isn't a failure to give it the
+        // input.
+        let large = "1\n".repeat(1_000_000);
+        assert!(compare(1, "exec 0<&-; echo 1000000", &large, &params).is_ok());
+
+        let failed = compare(1, "echo oops >&2; exit 3", input, &params);
+        assert_eq!(
+            failed.err().unwrap(),
+            "`echo oops >&2; exit 3` failed (exit status: 3): oops"
+        );
+    }
+}
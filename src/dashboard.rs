@@ -0,0 +1,463 @@
+#![cfg_attr(not(feature = "all-days"), allow(unreachable_code, unused_variables))]
+
+use crate::params::{Overrides, Params};
+use crate::parse::ParseError;
+use crate::{is_compiled, parameters, puzzle_input, solve_isolated, Config};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+
+// A full-screen dashboard for browsing the days: every day, and how far along it is, on the left,
+// and the selected day's input, what it parses into, and its answers -- with how long each took --
+// on the right.
+//
+// Up and down (or 'k' and 'j') choose a day, 'r' runs the selected part and 'p' switches part, 'e'
+// switches between the puzzle input and the puzzle's example, page up and page down scroll the
+// input and what it parses into, and 'q' (or escape, or ctrl-c) quits.
+
+const KEYS: &str = "↑/↓: day, r: run, p: part, e: example, PgUp/PgDn: scroll, q: quit";
+const SCROLL: u16 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Source {
+    Puzzle,
+    Example,
+}
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    NotCompiled,
+    Unsolved,
+    Solved,
+}
+
+// A day's input, and what it parses into, or why there isn't one.
+struct Loaded {
+    input: Result<Cow<'static, str>, String>,
+    preview: Result<String, String>,
+}
+
+// What became of solving one part of a day, and how long it took.
+#[derive(Debug, PartialEq)]
+struct Answer {
+    outcome: Result<String, String>,
+    took: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+enum Handled {
+    Redraw,
+    Run,
+    Quit,
+}
+
+struct Dashboard<'a> {
+    overrides: &'a Overrides,
+    day: usize,
+    part: usize,
+    source: Source,
+    scroll: u16,
+    loaded: HashMap<(usize, Source), Loaded>,
+    answers: HashMap<(usize, Source, usize), Answer>,
+}
+
+impl<'a> Dashboard<'a> {
+    fn new(overrides: &'a Overrides) -> Self {
+        let mut dashboard = Dashboard {
+            overrides,
+            day: 1,
+            part: 1,
+            source: Source::Puzzle,
+            scroll: 0,
+            loaded: HashMap::new(),
+            answers: HashMap::new(),
+        };
+        dashboard.select(
+            Config::DAY_RANGE
+                .into_iter()
+                .find(|&day| is_compiled(day))
+                .unwrap_or(1),
+        );
+        dashboard
+    }
+
+    fn status(day: usize) -> Status {
+        if !is_compiled(day) {
+            Status::NotCompiled
+        } else if Config::SOLVED_DAYS.contains(&day) {
+            Status::Solved
+        } else {
+            Status::Unsolved
+        }
+    }
+
+    fn select(&mut self, day: usize) {
+        self.day = day;
+        self.scroll = 0;
+        self.load();
+    }
+
+    // Inputs are loaded, and parsed, once -- decrypting one takes a moment.
+    fn load(&mut self) {
+        let (day, source) = (self.day, self.source);
+        if Self::status(day) != Status::Solved || self.loaded.contains_key(&(day, source)) {
+            return;
+        }
+        let input: Result<Cow<str>, String> = match source {
+            Source::Puzzle => puzzle_input(day).map_err(|err| err.to_string()),
+            Source::Example => Ok(Cow::Borrowed(day!(day => EXAMPLE))),
+        };
+        let preview = match &input {
+            Ok(input) => {
+                let preview: Result<String, ParseError> = day!(day => preview(input));
+                preview.map_err(|err| err.to_string())
+            }
+            Err(_) => Err("There's no input to parse".to_string()),
+        };
+        self.loaded.insert((day, source), Loaded { input, preview });
+    }
+
+    fn params(&self) -> Params {
+        match self.source {
+            Source::Puzzle => self.overrides.params(self.day, parameters(self.day)),
+            Source::Example => Params::examples(parameters(self.day)),
+        }
+    }
+
+    fn handle(&mut self, key: KeyCode, modifiers: KeyModifiers) -> Handled {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Handled::Quit,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                return Handled::Quit
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.day > *Config::DAY_RANGE.start() => {
+                self.select(self.day - 1)
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.day < *Config::DAY_RANGE.end() => {
+                self.select(self.day + 1)
+            }
+            KeyCode::Char('r') | KeyCode::Enter if Self::status(self.day) == Status::Solved => {
+                return Handled::Run
+            }
+            KeyCode::Char('p') | KeyCode::Tab => self.part = 3 - self.part,
+            KeyCode::Char('e') => {
+                self.source = match self.source {
+                    Source::Puzzle => Source::Example,
+                    Source::Example => Source::Puzzle,
+                };
+                self.scroll = 0;
+                self.load();
+            }
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(SCROLL),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(SCROLL),
+            _ => {}
+        }
+        Handled::Redraw
+    }
+
+    // Solve the selected part, in isolation: a solver that panics is reported like any other
+    // failure, rather than taking the dashboard down with it.
+    fn run(&mut self) {
+        let (day, part, source) = (self.day, self.part, self.source);
+        let Some(Ok(input)) = self.loaded.get(&(day, source)).map(|loaded| &loaded.input) else {
+            return;
+        };
+        let params = self.params();
+
+        let started = Instant::now();
+        let outcome = solve_isolated(day, part, input, &params);
+        let took = started.elapsed();
+        self.answers
+            .insert((day, source, part), Answer { outcome, took });
+    }
+
+    // A mark for how a day's answers have gone so far, if any have been run.
+    fn mark(&self, day: usize) -> &'static str {
+        let answers = self
+            .answers
+            .iter()
+            .filter(|((answered, _, _), _)| *answered == day)
+            .map(|(_, answer)| answer);
+        let mut mark = "";
+        for answer in answers {
+            if answer.outcome.is_err() {
+                return "✗";
+            }
+            mark = "✓";
+        }
+        mark
+    }
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard, running: bool) {
+    let [main, keys] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [days, details] =
+        Layout::horizontal([Constraint::Length(26), Constraint::Min(0)]).areas(main);
+    let [input, preview, answers] = Layout::vertical([
+        Constraint::Percentage(40),
+        Constraint::Percentage(40),
+        Constraint::Min(5),
+    ])
+    .areas(details);
+
+    let items = Config::DAY_RANGE.map(|day| {
+        let (status, style) = match Dashboard::status(day) {
+            Status::Solved => ("solved", Style::default()),
+            Status::Unsolved => ("unsolved", Style::default().fg(Color::DarkGray)),
+            Status::NotCompiled => ("not compiled", Style::default().fg(Color::DarkGray)),
+        };
+        ListItem::new(format!(
+            "Day {day:>2}  {status:<12} {}",
+            dashboard.mark(day)
+        ))
+        .style(style)
+    });
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Days "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(dashboard.day - 1));
+    frame.render_stateful_widget(list, days, &mut state);
+
+    let source = match dashboard.source {
+        Source::Puzzle => "puzzle",
+        Source::Example => "example",
+    };
+    let (input_text, preview_text) = match (
+        Dashboard::status(dashboard.day),
+        dashboard.loaded.get(&(dashboard.day, dashboard.source)),
+    ) {
+        (Status::NotCompiled, _) => (
+            format!("Build with the day-{} feature to see it", dashboard.day),
+            String::new(),
+        ),
+        (Status::Unsolved, _) | (_, None) => (
+            format!("Day {} hasn't been solved yet", dashboard.day),
+            String::new(),
+        ),
+        (Status::Solved, Some(loaded)) => (
+            loaded.input.clone().unwrap_or_else(Cow::Owned).into_owned(),
+            loaded.preview.clone().unwrap_or_else(|err| err),
+        ),
+    };
+    frame.render_widget(
+        Paragraph::new(input_text)
+            .scroll((dashboard.scroll, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Day {} input: {source} ", dashboard.day)),
+            ),
+        input,
+    );
+    frame.render_widget(
+        Paragraph::new(preview_text)
+            .scroll((dashboard.scroll, 0))
+            .block(Block::default().borders(Borders::ALL).title(" Parsed ")),
+        preview,
+    );
+
+    let mut lines = Vec::new();
+    for part in 1..=2 {
+        let selected = if part == dashboard.part { "▶" } else { " " };
+        let answer = dashboard
+            .answers
+            .get(&(dashboard.day, dashboard.source, part));
+        let text = match answer {
+            _ if running && part == dashboard.part => "running...".to_string(),
+            None => "not run yet".to_string(),
+            Some(Answer {
+                outcome: Ok(answer),
+                took,
+            }) => format!("{answer}  ({took:.1?})"),
+            Some(Answer {
+                outcome: Err(err),
+                took,
+            }) => format!("failed: {err}  ({took:.1?})"),
+        };
+        let mut text = text.lines();
+        let first = text.next().unwrap_or_default();
+        lines.push(Line::from(format!("{selected} Part {part}: {first}")));
+        lines.extend(text.map(|line| Line::from(format!("           {line}"))));
+    }
+    frame.render_widget(
+        Paragraph::new(Text::from(lines))
+            .block(Block::default().borders(Borders::ALL).title(" Answers ")),
+        answers,
+    );
+
+    frame.render_widget(
+        Paragraph::new(KEYS).style(Style::default().fg(Color::DarkGray)),
+        keys,
+    );
+}
+
+// The terminal is in raw mode, on the alternate screen, for as long as the dashboard is up, and
+// put back however the dashboard stops.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+pub fn run(overrides: &Overrides) -> Result<(), Box<dyn std::error::Error>> {
+    if !io::stdout().is_terminal() {
+        return Err("The dashboard needs a terminal".into());
+    }
+    let _screen = Screen::enter()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let mut dashboard = Dashboard::new(overrides);
+
+    loop {
+        terminal.draw(|frame| draw(frame, &dashboard, false))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match dashboard.handle(key.code, key.modifiers) {
+            Handled::Redraw => {}
+            Handled::Run => {
+                terminal.draw(|frame| draw(frame, &dashboard, true))?;
+                dashboard.run();
+            }
+            Handled::Quit => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn press(dashboard: &mut Dashboard, keys: &str) {
+        for key in keys.chars() {
+            dashboard.handle(KeyCode::Char(key), KeyModifiers::NONE);
+        }
+    }
+
+    fn screen(dashboard: &Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, dashboard, false))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn dashboard_keys_choose_the_day_part_and_input() {
+        let overrides = Overrides::default();
+        let mut dashboard = Dashboard::new(&overrides);
+        assert_eq!((dashboard.day, dashboard.part), (1, 1));
+
+        dashboard.handle(KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(dashboard.day, 1);
+        press(&mut dashboard, "jjpe");
+        assert_eq!(dashboard.day, 3);
+        assert_eq!(dashboard.part, 2);
+        assert_eq!(dashboard.source, Source::Example);
+        assert!(dashboard.loaded.contains_key(&(3, Source::Example)));
+
+        assert_eq!(
+            dashboard.handle(KeyCode::Char('r'), KeyModifiers::NONE),
+            Handled::Run
+        );
+        assert_eq!(
+            dashboard.handle(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Handled::Quit
+        );
+        dashboard.select(25);
+        assert_eq!(
+            dashboard.handle(KeyCode::Char('r'), KeyModifiers::NONE),
+            Handled::Redraw
+        );
+    }
+
+    #[test]
+    fn dashboard_shows_answers_and_timings() {
+        let overrides = Overrides::default();
+        let mut dashboard = Dashboard::new(&overrides);
+        press(&mut dashboard, "e");
+        dashboard.run();
+        press(&mut dashboard, "p");
+        dashboard.run();
+        assert_eq!(
+            dashboard.answers[&(1, Source::Example, 2)].outcome,
+            Ok("45000".to_string())
+        );
+
+        let screen = screen(&dashboard);
+        assert!(screen.contains("Day  1  solved       ✓"), "{screen}");
+        assert!(screen.contains("Day 16  unsolved"), "{screen}");
+        assert!(screen.contains("Day 1 input: example"), "{screen}");
+        assert!(screen.contains("    6000,"), "{screen}");
+        assert!(screen.contains("  Part 1: 24000  ("), "{screen}");
+        assert!(screen.contains("▶ Part 2: 45000  ("), "{screen}");
+    }
+
+    #[test]
+    fn dashboard_solves_examples_with_their_parameters() {
+        let overrides = Overrides::default();
+        let mut dashboard = Dashboard::new(&overrides);
+        dashboard.select(15);
+        press(&mut dashboard, "e");
+        dashboard.run();
+        assert_eq!(
+            dashboard.answers[&(15, Source::Example, 1)].outcome,
+            Ok("26".to_string())
+        );
+        press(&mut dashboard, "p");
+        dashboard.run();
+        assert_eq!(
+            dashboard.answers[&(15, Source::Example, 2)].outcome,
+            Ok("56000011".to_string())
+        );
+
+        dashboard.select(11);
+        dashboard.loaded.insert(
+            (11, Source::Example),
+            Loaded {
+                input: Ok(Cow::Borrowed("Monkey 0:\n")),
+                preview: Ok(String::new()),
+            },
+        );
+        dashboard.run();
+        assert!(dashboard.answers[&(11, Source::Example, 2)]
+            .outcome
+            .is_err());
+        assert_eq!(dashboard.mark(11), "✗");
+        assert_eq!(dashboard.mark(15), "✓");
+        assert_eq!(dashboard.mark(2), "");
+    }
+}
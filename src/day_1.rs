@@ -1,63 +1,232 @@
-pub fn run() {
-    let input = include_str!("input/day_1.txt");
+use crate::checked;
+use crate::explain::{Explanation, Witness};
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
+use std::cmp::Reverse;
+use std::fmt;
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
     //let processed_input = &mut sum_groups(input);
     //processed_input.sort();
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // Each elf is carrying x calories worth of food
 // x is a single number on a single line, or the sum of multiple numbers on consequtive lines.
 // Each x is seperated by an empty line.
 
-fn sum_groups(input: &str) -> Vec<u64> {
+fn sum_groups(input: &str) -> Result<Vec<u64>, ParseError> {
+    if input.trim().is_empty() {
+        return Err(ParseError::new("there are no elves in the input"));
+    }
     input
         .replace("\r\n", "\n")
         .split("\n\n")
         .map(|elf| {
-            elf
-                .lines()
-                .map(|food| food.parse::<u64>().expect("Unexpected value -- either not a number, a floating point number, or a huge (>u64) number."))
-                .sum()
+            elf.lines().try_fold(0, |calories: u64, food| {
+                calories.checked_add(parse::number(food)?).ok_or_else(|| {
+                    ParseError::new("an elf is carrying more than u64::MAX calories")
+                })
+            })
         })
         .collect()
 }
 
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    sum_groups(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", sum_groups(input)?))
+}
+
 // Puzzle 1:
 // How many calories are being carried by the elf carrying the most calories ?
-fn puzzle_1(input: &str) -> u64 {
-    sum_groups(input).iter().max().copied().unwrap()
+fn puzzle_1(input: &str) -> Result<u64, ParseError> {
+    Ok(sum_groups(input)?.iter().max().copied().unwrap())
 }
 
 //Puzzle 2:
 // How many calories are carried by the top three calorie-carrying elves ?
-fn puzzle_2(input: &str) -> u64 {
-    let input = &mut sum_groups(input);
+fn puzzle_2(input: &str) -> Result<u64, ParseError> {
+    let input = &mut sum_groups(input)?;
     input.sort();
-    input.iter().rev().take(3).sum()
+    Ok(checked::sum(input.iter().rev().take(3).copied()))
+}
+
+// The elves carrying the most calories, heaviest first, numbered by where they are in the list
+// (from 1) along with how many calories they carry.
+#[derive(Debug, PartialEq)]
+struct TopElves(Vec<(usize, u64)>);
+
+fn top_elves(input: &str, count: usize) -> Result<TopElves, ParseError> {
+    let mut elves = sum_groups(input)?
+        .into_iter()
+        .enumerate()
+        .map(|(idx, calories)| (idx + 1, calories))
+        .collect::<Vec<_>>();
+    elves.sort_by_key(|&(_, calories)| Reverse(calories));
+    elves.truncate(count);
+    Ok(TopElves(elves))
+}
+
+impl fmt::Display for TopElves {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (elf, calories) in &self.0 {
+            writeln!(f, "elf {elf} carries {calories} calories")?;
+        }
+        Ok(())
+    }
+}
+
+impl Witness for TopElves {
+    type Answer = u64;
+
+    fn answer(&self) -> u64 {
+        checked::sum(self.0.iter().map(|&(_, calories)| calories))
+    }
+
+    // Tally every elf's food again, line by line, and make sure no one left out carries more than
+    // the lightest of the elves named.
+    fn check(&self, input: &str) -> Result<(), String> {
+        let mut tallies = vec![0u128];
+        for line in input.lines() {
+            if line.is_empty() {
+                tallies.push(0);
+            } else {
+                *tallies.last_mut().unwrap() +=
+                    line.parse::<u128>().map_err(|err| err.to_string())?;
+            }
+        }
+
+        for (idx, &(elf, calories)) in self.0.iter().enumerate() {
+            if self.0[..idx].iter().any(|&(other, _)| other == elf) {
+                return Err(format!("elf {elf} is named twice"));
+            }
+            match tallies.get(elf.wrapping_sub(1)) {
+                None => return Err(format!("there is no elf {elf}")),
+                Some(&tally) if tally != u128::from(calories) => {
+                    return Err(format!("elf {elf} carries {tally} calories"))
+                }
+                Some(_) => {}
+            }
+        }
+
+        let lightest = self.0.iter().map(|&(_, calories)| calories).min();
+        match (1..=tallies.len()).find(|elf| {
+            self.0.iter().all(|(named, _)| named != elf)
+                && lightest.is_some_and(|lightest| tallies[elf - 1] > u128::from(lightest))
+        }) {
+            Some(elf) => Err(format!(
+                "elf {elf} carries more, with {} calories",
+                tallies[elf - 1]
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn explain(input: &str) -> Result<Vec<Explanation>, ParseError> {
+    Ok(vec![
+        Explanation::new(1, puzzle_1(input)?, &top_elves(input, 1)?, input),
+        Explanation::new(2, puzzle_2(input)?, &top_elves(input, 3)?, input),
+    ])
+}
+
+// Generate `size` elves, each carrying a handful of food items.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            (0..rng.range(1..=15))
+                .map(|_| format!("{}\n", rng.range(1000..=70_000)))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const TEST_INPUT: &str =
-        "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+    use crate::property;
+    const TEST_INPUT: &str = EXAMPLE;
 
     #[test]
     fn day_1_parse_input() {
         assert_eq!(
             sum_groups(TEST_INPUT),
-            vec![6000, 4000, 11000, 24000, 10000]
+            Ok(vec![6000, 4000, 11000, 24000, 10000])
         );
     }
 
+    #[test]
+    fn day_1_parse_input_rejects_malformed() {
+        assert!(sum_groups("1000\nlots\n").is_err());
+        assert!(sum_groups("18446744073709551615\n1\n").is_err());
+        assert!(sum_groups("\n").is_err());
+    }
+
     #[test]
     fn day_1_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 24000);
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(24000));
     }
 
     #[test]
     fn day_1_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 45000);
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(45000));
+    }
+
+    #[test]
+    fn day_1_explain() {
+        assert_eq!(
+            top_elves(TEST_INPUT, 3),
+            Ok(TopElves(vec![(4, 24000), (3, 11000), (5, 10000)]))
+        );
+        assert!(explain(TEST_INPUT)
+            .unwrap()
+            .iter()
+            .all(Explanation::is_verified));
+
+        assert_eq!(
+            TopElves(vec![(3, 11000)]).check(TEST_INPUT),
+            Err("elf 4 carries more, with 24000 calories".to_string())
+        );
+        assert_eq!(
+            TopElves(vec![(4, 24001)]).check(TEST_INPUT),
+            Err("elf 4 carries 24000 calories".to_string())
+        );
+        assert!(TopElves(vec![(6, 0)]).check(TEST_INPUT).is_err());
+        assert!(TopElves(vec![(4, 24000), (4, 24000)])
+            .check(TEST_INPUT)
+            .is_err());
+    }
+
+    #[test]
+    fn day_1_generate() {
+        let input = generate(&mut Rng::new(1), 50);
+        assert_eq!(sum_groups(&input).unwrap().len(), 50);
+        assert!(puzzle_2(&input).unwrap() >= puzzle_1(&input).unwrap());
+    }
+
+    #[test]
+    fn day_1_property_top_three_bounds() {
+        property::assert_holds("day_1 top three bounds", generate, |_, input| {
+            let (top, top_three) = (puzzle_1(input).unwrap(), puzzle_2(input).unwrap());
+            top <= top_three && top_three <= 3 * top
+        });
     }
 }
@@ -1,193 +1,19 @@
-pub fn run() {
-    let input = include_str!("input/day_10.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
-}
-
-// We want to design a replacement for a broken video display.
-// The display is driven by a clock circuit, ticking at a constant rate.
-// Call a single tick a cycle.
-//
-// - What signal is being sent by the CPU?
-// The CPU has a single register.
-// Call it x.
-// x starts with the value 1.
-// The CPU has two instructions.
-// - addx v : takes two cycles; x is increased by value v.
-// - noop : takes one cycle; no effect.
-//
-// We will receive a program using these instructions.
-// Use the output to determine a video output.
-//
-// Determine the video output by considering the signal strength during the 20th cycle, and every
-// 40 cycles after that.
-// Signal strength is cycle number * x
-
-fn parse_input(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(|line| {
-            if line.trim() == "noop" {
-                Instruction::Noop
-            } else {
-                Instruction::AddX(line.trim_start_matches("addx ").parse::<i64>().unwrap())
-            }
-        })
-        .collect::<Vec<_>>()
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum Instruction {
-    Noop,
-    AddX(i64),
-}
-
-struct Cpu {
-    cycle: usize,
-    x: i64,
-    current_instruction: Option<Instruction>,
-    cycles_until_instruction: usize,
-}
-
-impl Cpu {
-    fn new() -> Self {
-        Cpu {
-            cycle: 0,
-            x: 1,
-            current_instruction: None,
-            cycles_until_instruction: 0,
-        }
-    }
-
-    fn apply_instruction(&mut self) {
-        if let Some(instruction) = &self.current_instruction {
-            match instruction {
-                Instruction::Noop => {}
-                Instruction::AddX(v) => {
-                    self.x += v;
-                }
-            }
-        }
-        self.current_instruction = None;
-        self.cycles_until_instruction = 0;
-    }
-
-    fn next_cycle(&mut self) {
-        self.cycles_until_instruction -= 1;
-        self.cycle += 1;
-
-        if self.cycles_until_instruction == 0 {
-            self.apply_instruction();
-        }
-    }
-
-    fn run_1(&mut self, instructions: Vec<Instruction>) -> usize {
-        let mut signal = 0;
-        let instructions = instructions.iter();
-        for instruction in instructions {
-            if self.current_instruction.is_none() {
-                self.current_instruction = Some(*instruction);
-            }
-            self.cycles_until_instruction = if let Instruction::Noop = instruction {
-                1
-            } else {
-                2
-            };
-
-            while self.cycles_until_instruction != 0 {
-                if (self.cycle + 1) == 20
-                    || (self.cycle + 1) > 20 && ((self.cycle + 1) - 20) % 40 == 0
-                {
-                    signal += self.x * (self.cycle + 1) as i64;
-                    //println!("{}:{},{}", self.cycle, self.x, signal);
-                }
-                self.next_cycle();
-
-                //if self.cycle > 180 {
-                //    println!("{}:{}", self.cycle, self.x);
-                //}
-            }
-        }
-        signal as usize
-    }
-
-    fn run_2(&mut self, instructions: Vec<Instruction>) -> String {
-        let mut display = String::new();
-        let instructions = instructions.iter();
-        for instruction in instructions {
-            if self.current_instruction.is_none() {
-                self.current_instruction = Some(*instruction);
-            }
-            self.cycles_until_instruction = if let Instruction::Noop = instruction {
-                1
-            } else {
-                2
-            };
-
-            while self.cycles_until_instruction != 0 {
-                self.cycles_until_instruction -= 1;
-                self.cycle += 1;
-
-                let sprite = [self.x - 1, self.x, self.x + 1];
-
-                if sprite.iter().any(|&s| s == (self.cycle - 1) as i64 % 40) {
-                    display.push('#')
-                } else {
-                    display.push('.')
-                }
-
-                //dbg!(&display);
-                if self.cycles_until_instruction == 0 {
-                    self.apply_instruction();
-                }
-            }
-        }
-        (0..6)
-            .map(|n| {
-                let mut line = display.chars().skip(n * 40).take(40).collect::<String>();
-                line.push('\n');
-                line
-            })
-            .collect::<String>()
-    }
-}
-
-// Calulate the sum of (cycle * x), where cycle = 20, 60, 100, 140, 180, 220
-fn puzzle_1(input: &str) -> usize {
-    let mut cpu = Cpu::new();
-    let instructions = parse_input(input);
-    cpu.run_1(instructions)
-}
-
-// x controls the horizontal position of a sprite.
-// The sprite is 3 pixels wide.
-// X sets the horizontal position of the middle sprite.
-// the output is 40 pixels wide & 6 pixels tall.
-// It draws left to right, top to bottom.
-// left-most position is 0; right-most is 39.
-//
-// A single pixel is drawn each cycle:
-// Cycle   1 -> ######################################## <- Cycle  40
-// Cycle  41 -> ######################################## <- Cycle  80
-// Cycle  81 -> ######################################## <- Cycle 120
-// Cycle 121 -> ######################################## <- Cycle 160
-// Cycle 161 -> ######################################## <- Cycle 200
-// Cycle 201 -> ######################################## <- Cycle 240
-//
-fn puzzle_2(input: &str) -> String {
-    let mut cpu = Cpu::new();
-    let instructions = parse_input(input);
-    cpu.run_2(instructions)
-}
+use crate::checked;
+use crate::generate::Rng;
+use crate::params::{Parameter, Params};
+use crate::parse::{self, either, integer, lines, literal, map, preceded, Input, ParseError};
+use crate::simulation::Simulation;
+use crate::visualize::{Frame, Rgb, Visualize};
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// The screen is at most a million pixels, so its size can't overflow, and it can still be drawn a
+// frame at a time.
+pub const PARAMETERS: &[Parameter] = &[
+    Parameter::new("width", 40, 1, "pixels in each row of the screen").at_most(1_000),
+    Parameter::new("height", 6, 1, "rows of pixels on the screen").at_most(1_000),
+];
 
-    const TEST_INPUT: &str = "noop
-addx 3
-addx -5";
-    const TEST_INPUT_1: &str = "addx 15
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "addx 15
 addx -11
 addx 6
 addx -3
@@ -334,39 +160,420 @@ noop
 noop
 noop";
 
+pub fn run(input: &str, params: &Params) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1, params)?);
+    println!("{}", solve(input, 2, params)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize, params: &Params) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => puzzle_2(input, params.get("width"), params.get("height")),
+        _ => unreachable!("there is no part {part}"),
+    }
+}
+
+// We want to design a replacement for a broken video display.
+// The display is driven by a clock circuit, ticking at a constant rate.
+// Call a single tick a cycle.
+//
+// - What signal is being sent by the CPU?
+// The CPU has a single register.
+// Call it x.
+// x starts with the value 1.
+// The CPU has two instructions.
+// - addx v : takes two cycles; x is increased by value v.
+// - noop : takes one cycle; no effect.
+//
+// We will receive a program using these instructions.
+// Use the output to determine a video output.
+//
+// Determine the video output by considering the signal strength during the 20th cycle, and every
+// 40 cycles after that.
+// Signal strength is cycle number * x
+
+// x is kept within an i32, so that the signal strength can't overflow.
+fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let mut x = 1i32;
+    let addx = move |input: &mut Input| {
+        let start = *input;
+        let v = preceded("addx ", integer::<i32>())(input)?;
+        x = x
+            .checked_add(v)
+            .ok_or_else(|| start.error("this overflows x"))?;
+        Ok(Instruction::AddX(v as i64))
+    };
+    let noop = map(literal("noop"), |_| Instruction::Noop);
+    parse::all(input, lines(either(noop, addx)))
+}
+
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Instruction {
+    Noop,
+    AddX(i64),
+}
+
+#[derive(Clone)]
+struct Cpu {
+    cycle: usize,
+    x: i64,
+    current_instruction: Option<Instruction>,
+    cycles_until_instruction: usize,
+    program: Vec<Instruction>,
+    next_instruction: usize,
+    signal: i64,
+    display: String,
+    width: usize,
+    height: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct CpuSnapshot {
+    cycle: usize,
+    x: i64,
+    signal: i64,
+    display: String,
+}
+
+impl Cpu {
+    fn new(program: Vec<Instruction>, width: usize, height: usize) -> Self {
+        Cpu {
+            cycle: 0,
+            x: 1,
+            current_instruction: None,
+            cycles_until_instruction: 0,
+            program,
+            next_instruction: 0,
+            signal: 0,
+            display: String::new(),
+            width,
+            height,
+        }
+    }
+
+    fn apply_instruction(&mut self) {
+        if let Some(instruction) = &self.current_instruction {
+            match instruction {
+                Instruction::Noop => {}
+                Instruction::AddX(v) => {
+                    self.x += v;
+                }
+            }
+        }
+        self.current_instruction = None;
+        self.cycles_until_instruction = 0;
+    }
+
+    fn next_cycle(&mut self) {
+        self.cycles_until_instruction -= 1;
+        self.cycle += 1;
+
+        if self.cycles_until_instruction == 0 {
+            self.apply_instruction();
+        }
+    }
+
+    // The first screenful of pixels drawn, e.g. 240 as six rows of 40.
+    fn screen(&self) -> String {
+        (0..self.height)
+            .map(|n| {
+                let mut line = self
+                    .display
+                    .chars()
+                    .skip(checked::mul(n, self.width))
+                    .take(self.width)
+                    .collect::<String>();
+                line.push('\n');
+                line
+            })
+            .collect::<String>()
+    }
+}
+
+// Each step is a single cycle: the signal strength is sampled and a pixel is drawn during the
+// cycle, before the instruction being executed has any effect.
+impl Simulation for Cpu {
+    type Snapshot = CpuSnapshot;
+
+    fn step(&mut self) {
+        if self.current_instruction.is_none() {
+            let instruction = self.program[self.next_instruction];
+            self.next_instruction += 1;
+            self.current_instruction = Some(instruction);
+            trace!(
+                Debug,
+                "instruction",
+                instruction = format!("{instruction:?}")
+            );
+            self.cycles_until_instruction = if let Instruction::Noop = instruction {
+                1
+            } else {
+                2
+            };
+        }
+
+        let during = self.cycle + 1;
+        if during == 20 || during > 20 && (during - 20).is_multiple_of(40) {
+            self.signal = checked::add(self.signal, checked::mul(self.x, during as i64));
+        }
+
+        let sprite = [self.x - 1, self.x, self.x + 1];
+        if sprite.contains(&((self.cycle % self.width) as i64)) {
+            self.display.push('#')
+        } else {
+            self.display.push('.')
+        }
+        trace!(
+            Info,
+            "cycle",
+            cycle = during,
+            x = self.x,
+            signal = self.signal,
+            pixel = self.display.chars().last().unwrap(),
+        );
+
+        self.next_cycle();
+    }
+
+    fn is_done(&self) -> bool {
+        self.current_instruction.is_none() && self.next_instruction == self.program.len()
+    }
+
+    fn snapshot(&self) -> CpuSnapshot {
+        CpuSnapshot {
+            cycle: self.cycle,
+            x: self.x,
+            signal: self.signal,
+            display: self.display.clone(),
+        }
+    }
+
+    fn render(&self) -> String {
+        format!(
+            "{}cycle {}: x = {}, signal strength = {}\n",
+            self.screen(),
+            self.cycle,
+            self.x,
+            self.signal
+        )
+    }
+}
+
+// The CRT, as it is drawn: lit pixels are green, and the pixel about to be drawn is white.
+impl Visualize for Cpu {
+    const PALETTE: &'static [Rgb] = &[[10, 20, 10], [60, 230, 90], [255, 255, 255]];
+
+    fn frame(&self) -> Frame {
+        let (width, height) = (self.width, self.height);
+        let mut frame = Frame::new(width, height);
+        for (idx, pixel) in self.display.chars().take(width * height).enumerate() {
+            if pixel == '#' {
+                frame.set(idx % width, idx / width, 1);
+            }
+        }
+        if !self.is_done() && self.cycle < width * height {
+            frame.set(self.cycle % width, self.cycle / width, 2);
+        }
+        frame
+    }
+}
+
+pub fn simulation(
+    input: &str,
+    _part: usize,
+    params: &Params,
+) -> Result<impl Visualize + Clone, ParseError> {
+    Ok(Cpu::new(
+        parse_input(input)?,
+        params.get("width"),
+        params.get("height"),
+    ))
+}
+
+// Calulate the sum of (cycle * x), where cycle = 20, 60, 100, 140, 180, 220
+// The signal doesn't depend on the screen, so any size will do.
+fn puzzle_1(input: &str) -> Result<usize, ParseError> {
+    let mut cpu = Cpu::new(parse_input(input)?, 40, 6);
+    cpu.run();
+    Ok(cpu.snapshot().signal as usize)
+}
+
+// x controls the horizontal position of a sprite.
+// The sprite is 3 pixels wide.
+// X sets the horizontal position of the middle sprite.
+// the output is `width` (40) pixels wide & `height` (6) pixels tall.
+// It draws left to right, top to bottom.
+// left-most position is 0; right-most is 39.
+//
+// A single pixel is drawn each cycle:
+// Cycle   1 -> ######################################## <- Cycle  40
+// Cycle  41 -> ######################################## <- Cycle  80
+// Cycle  81 -> ######################################## <- Cycle 120
+// Cycle 121 -> ######################################## <- Cycle 160
+// Cycle 161 -> ######################################## <- Cycle 200
+// Cycle 201 -> ######################################## <- Cycle 240
+//
+fn puzzle_2(input: &str, width: usize, height: usize) -> Result<String, ParseError> {
+    let mut cpu = Cpu::new(parse_input(input)?, width, height);
+    cpu.run();
+    Ok(cpu.screen())
+}
+
+// Generate a program that runs for at least `size` cycles, and always long enough to draw the
+// whole screen.
+// The x register is kept on the screen, so the sprite is always visible somewhere.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut program = String::new();
+    let mut x: i64 = 1;
+    let mut cycles = 0;
+
+    while cycles < size.max(240) {
+        let v = rng.range_i64(-20..=20);
+        if v != 0 && (0..40).contains(&(x + v)) && rng.chance(60) {
+            x += v;
+            cycles += 2;
+            program.push_str(&format!("addx {v}\n"));
+        } else {
+            cycles += 1;
+            program.push_str("noop\n");
+        }
+    }
+    program
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{property, snapshot};
+
+    const TEST_INPUT: &str = "noop
+addx 3
+addx -5";
+    const TEST_INPUT_1: &str = EXAMPLE;
+
     #[test]
     fn day_10_parse_input() {
         assert_eq!(
             parse_input(TEST_INPUT),
-            vec![
+            Ok(vec![
                 Instruction::Noop,
                 Instruction::AddX(3),
                 Instruction::AddX(-5)
-            ]
+            ])
         );
     }
 
+    #[test]
+    fn day_10_parse_input_rejects_malformed() {
+        assert!(parse_input("nop").is_err());
+        assert!(parse_input("addx").is_err());
+        assert!(parse_input("addx 2147483647").is_err());
+    }
+
     #[test]
     fn day_10_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 0);
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(0));
     }
 
     #[test]
     fn day_10_puzzle_1_1() {
-        assert_eq!(puzzle_1(TEST_INPUT_1), 13140)
+        assert_eq!(puzzle_1(TEST_INPUT_1), Ok(13140))
     }
 
     #[test]
     fn day_10_puzzle_2() {
+        snapshot::assert_matches("day_10_crt", &puzzle_2(TEST_INPUT_1, 40, 6).unwrap());
+        assert_eq!(
+            puzzle_2(TEST_INPUT_1, 20, 2).unwrap(),
+            "##..##..##..##..##..\n....................\n".to_string()
+        );
+    }
+
+    // The largest screen the parameters allow doesn't overflow, even in checked mode.
+    #[test]
+    fn day_10_puzzle_2_largest_screen() {
+        let _checked = checked::enable();
+        let (width, height) = (PARAMETERS[0].max as usize, PARAMETERS[1].max as usize);
+        let screen = puzzle_2(TEST_INPUT_1, width, height).unwrap();
+        assert_eq!(screen.lines().count(), height);
+        assert_eq!(screen.lines().next().map(str::len), Some(240));
+    }
+
+    #[test]
+    fn day_10_generate() {
+        let input = generate(&mut Rng::new(10), 500);
+        let cycles: usize = parse_input(&input)
+            .unwrap()
+            .iter()
+            .map(|instruction| match instruction {
+                Instruction::Noop => 1,
+                Instruction::AddX(_) => 2,
+            })
+            .sum();
+        assert!(cycles >= 500);
+        assert_eq!(puzzle_2(&input, 40, 6).unwrap().lines().count(), 6);
+        assert!(puzzle_2(&input, 40, 6)
+            .unwrap()
+            .lines()
+            .all(|line| line.len() == 40));
+    }
+
+    // The screen is fully drawn after 240 cycles, so nothing run after that can change it.
+    #[test]
+    fn day_10_property_screen_is_drawn_once() {
+        property::assert_holds("day_10 screen is drawn once", generate, |rng, input| {
+            let extra = format!("addx {}\nnoop\n", rng.range_i64(-20..=20));
+            let display = puzzle_2(input, 40, 6).unwrap();
+            display.lines().count() == 6
+                && display.lines().all(|line| line.len() == 40)
+                && display == puzzle_2(&(input.to_string() + &extra), 40, 6).unwrap()
+        });
+    }
+
+    #[test]
+    fn day_10_simulation() {
+        let mut cpu = Cpu::new(parse_input(TEST_INPUT).unwrap(), 40, 6);
+        cpu.step();
+        cpu.step();
+        assert_eq!(
+            cpu.snapshot(),
+            CpuSnapshot {
+                cycle: 2,
+                x: 1,
+                signal: 0,
+                display: "##".to_string()
+            }
+        );
+        assert_eq!(cpu.run(), 3);
+        assert_eq!(cpu.snapshot().x, -1);
+        assert_eq!(
+            cpu.render(),
+            "#####\n\n\n\n\n\ncycle 5: x = -1, signal strength = 0\n"
+        );
+    }
+
+    #[test]
+    fn day_10_visualize() {
+        let mut cpu = Cpu::new(parse_input(TEST_INPUT_1).unwrap(), 40, 6);
+        (0..41).for_each(|_| cpu.step());
+        let frame = cpu.frame();
+        assert_eq!((frame.width(), frame.height()), (40, 6));
         assert_eq!(
-            puzzle_2(TEST_INPUT_1),
-            "##..##..##..##..##..##..##..##..##..##..
-###...###...###...###...###...###...###.
-####....####....####....####....####....
-#####.....#####.....#####.....#####.....
-######......######......######......####
-#######.......#######.......#######.....\n"
-                .to_string()
+            (frame.get(0, 0), frame.get(2, 0), frame.get(0, 1)),
+            (1, 0, 1)
         );
+        assert_eq!(frame.get(1, 1), 2);
+        cpu.run();
+        assert_eq!(cpu.frame().get(39, 5), 0);
     }
 }
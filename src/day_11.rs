@@ -1,7 +1,64 @@
-pub fn run() {
-    let input = include_str!("input/day_11.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+use crate::checked;
+use crate::generate::Rng;
+use crate::params::{Parameter, Params};
+use crate::parse::{
+    self, blocks, either, integer, line, literal, map, optional, pair, preceded, separated,
+    terminated, Input, ParseError,
+};
+use crate::simulation::Simulation;
+
+pub const PARAMETERS: &[Parameter] = &[
+    Parameter::new("rounds_1", 20, 0, "rounds of keep away in puzzle 1"),
+    Parameter::new("rounds_2", 10_000, 0, "rounds of keep away in puzzle 2"),
+    Parameter::new(
+        "relief",
+        3,
+        1,
+        "what worry levels are divided by in puzzle 1",
+    ),
+];
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
+
+pub fn run(input: &str, params: &Params) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1, params)?);
+    println!("{}", solve(input, 2, params)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize, params: &Params) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input, params.get("rounds_1"), params.get("relief"))?.to_string()),
+        2 => Ok(puzzle_2(input, params.get("rounds_2"))?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // Monkeys are playing with our belongings.
@@ -37,20 +94,15 @@ impl std::fmt::Debug for Monkey {
         write!(f, "{:?}", self.items,)
     }
 }
-fn parse_input(input: &str) -> Vec<Monkey> {
-    fn parse_operation((sign, operand): (&str, &str)) -> Box<dyn Fn(usize) -> usize> {
-        if let Ok(operand) = operand.parse::<usize>() {
-            match sign {
-                "+" => Box::new(move |old| old + operand),
-                "*" => Box::new(move |old| old * operand),
-                _ => unreachable!(),
-            }
-        } else {
-            match sign {
-                "+" => Box::new(|old| old + old),
-                "*" => Box::new(|old| old * old),
-                _ => unreachable!(),
-            }
+// A monkey, and which monkeys it throws to when its test is true and when it's false.
+fn monkey(input: &mut Input) -> Result<(Monkey, [usize; 2]), ParseError> {
+    fn operation(sign: char, operand: Option<usize>) -> Box<dyn Fn(usize) -> usize> {
+        match (sign, operand) {
+            ('+', Some(operand)) => Box::new(move |old| checked::add(old, operand)),
+            ('*', Some(operand)) => Box::new(move |old| checked::mul(old, operand)),
+            ('+', None) => Box::new(|old| checked::add(old, old)),
+            ('*', None) => Box::new(|old| checked::mul(old, old)),
+            _ => unreachable!("'{sign}' is not an operation"),
         }
     }
 
@@ -58,105 +110,413 @@ fn parse_input(input: &str) -> Vec<Monkey> {
         Box::new(move |worry| if worry % test == 0 { t } else { f })
     }
 
-    input
-        .split("\n\n")
-        .map(|monkey| {
-            let mut monkey = monkey.split('\n').skip(1);
-            let items = monkey
-                .next()
-                .unwrap()
-                .trim_start_matches("  Starting items: ")
-                .split(", ")
-                .map(|n| n.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>();
-            let operation = parse_operation(
-                monkey
-                    .next()
-                    .unwrap()
-                    .trim_start_matches("  Operation: new = old ")
-                    .split_once(' ')
-                    .unwrap(),
-            );
-            let test = monkey
-                .next()
-                .unwrap()
-                .trim_start_matches("  Test: divisible by ")
-                .parse::<usize>()
-                .unwrap();
-            let test_no = test;
-            let t = monkey
-                .next()
-                .unwrap()
-                .trim_start_matches("    If true: throw to monkey ")
-                .parse::<usize>()
-                .unwrap();
-            let f = monkey
-                .next()
-                .unwrap()
-                .trim_start_matches("    If false: throw to monkey ")
-                .parse::<usize>()
-                .unwrap();
-            let test = parse_test(test, t, f);
-
-            Monkey {
-                items,
-                operation,
-                test,
-                test_no,
-                inspection_count: 0,
-            }
-        })
-        .collect::<Vec<Monkey>>()
+    line(preceded("Monkey ", terminated(integer::<usize>(), ":")))(input)?;
+    let (_, items) = line(preceded(
+        "  Starting items:",
+        pair(optional(literal(" ")), optional(separated(integer(), ", "))),
+    ))(input)?;
+    let sign = either(map(literal("+"), |_| '+'), map(literal("*"), |_| '*'));
+    let operand = either(map(literal("old"), |_| None), map(integer(), Some));
+    let (sign, operand) = line(preceded(
+        "  Operation: new = old ",
+        pair(sign, preceded(" ", operand)),
+    ))(input)?;
+
+    let start = *input;
+    let test = line(preceded("  Test: divisible by ", integer::<usize>()))(input)?;
+    if test == 0 {
+        return Err(start.error("a monkey can't test divisibility by 0"));
+    }
+    let t = line(preceded("    If true: throw to monkey ", integer()))(input)?;
+    let f = line(preceded("    If false: throw to monkey ", integer()))(input)?;
+
+    let monkey = Monkey {
+        items: items.unwrap_or_default(),
+        operation: operation(sign, operand),
+        test: parse_test(test, t, f),
+        test_no: test,
+        inspection_count: 0,
+    };
+    Ok((monkey, [t, f]))
 }
 
-//
-fn puzzle_1(input: &str) -> usize {
-    fn relief(item: usize) -> usize {
-        item / 3
-    }
-    let mut monkeys = parse_input(input);
-    for _ in 0..20 {
-        for monkey in 0..monkeys.len() {
-            for idx in 0..monkeys[monkey].items.len() {
-                let mut item = monkeys[monkey].items[idx];
-                item = (monkeys[monkey].operation)(item);
-                item = relief(item);
-                let new_monkey = (monkeys[monkey].test)(item);
-                monkeys[new_monkey].items.push(item);
-            }
-            monkeys[monkey].inspection_count += monkeys[monkey].items.len();
-            monkeys[monkey].items.clear();
+fn parse_input(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let monkeys = parse::all(input, blocks(monkey))?;
+    if monkeys.len() < 2 {
+        return Err(ParseError::new("there should be at least two monkeys"));
+    }
+    if let Some(target) = monkeys
+        .iter()
+        .flat_map(|(_, targets)| targets)
+        .find(|&&target| target >= monkeys.len())
+    {
+        return Err(ParseError::new(format!("there is no monkey {target}")));
+    }
+    Ok(monkeys.into_iter().map(|(monkey, _)| monkey).collect())
+}
+
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
+
+// Each monkey, in turn, inspects and throws every item it holds.
+// `relief` is applied to an item's worry level after it has been inspected.
+fn play_round(monkeys: &mut [Monkey], relief: impl Fn(usize) -> usize) {
+    for monkey in 0..monkeys.len() {
+        for idx in 0..monkeys[monkey].items.len() {
+            let mut item = monkeys[monkey].items[idx];
+            item = (monkeys[monkey].operation)(item);
+            item = relief(item);
+            let new_monkey = (monkeys[monkey].test)(item);
+            trace!(
+                Debug,
+                "throw",
+                monkey = monkey,
+                worry = item,
+                to = new_monkey
+            );
+            monkeys[new_monkey].items.push(item);
         }
+        monkeys[monkey].inspection_count += monkeys[monkey].items.len();
+        monkeys[monkey].items.clear();
     }
+}
+
+// The product of the two largest inspection counts.
+fn monkey_business(monkeys: &[Monkey]) -> usize {
     let mut inspection_count = monkeys
         .iter()
         .map(|monkey| monkey.inspection_count)
         .collect::<Vec<usize>>();
     inspection_count.sort();
-    inspection_count[inspection_count.len() - 1] * inspection_count[inspection_count.len() - 2]
+    checked::mul(
+        inspection_count[inspection_count.len() - 1],
+        inspection_count[inspection_count.len() - 2],
+    )
+}
+
+// The monkeys, playing a given number of rounds of keep away.
+struct KeepAway {
+    monkeys: Vec<Monkey>,
+    relief: Box<dyn Fn(usize) -> usize>,
+    round: usize,
+    rounds: usize,
+}
+
+// Each monkey's items, and how many items it has inspected.
+type KeepAwaySnapshot = Vec<(Vec<usize>, usize)>;
+
+impl KeepAway {
+    // Puzzle 1 divides worry levels by three after each inspection, for 20 rounds.
+    // Puzzle 2 doesn't, for 10_000 rounds -- so, to keep worry levels manageable, they are kept
+    // modulo the product of every monkey's divisor, which doesn't change the result of any test.
+    fn new(input: &str, rounds: usize, relief: Option<usize>) -> Result<Self, ParseError> {
+        let monkeys = parse_input(input)?;
+        let relief: Box<dyn Fn(usize) -> usize> = match relief {
+            Some(relief) => Box::new(move |item| item / relief),
+            None => {
+                let modulo = checked::product(monkeys.iter().map(|m| m.test_no));
+                Box::new(move |item| item % modulo)
+            }
+        };
+        Ok(KeepAway {
+            monkeys,
+            relief,
+            round: 0,
+            rounds,
+        })
+    }
+
+    // Play every round, reporting progress along the way.
+    fn play(&mut self) {
+        let mut progress = progress!("round", self.rounds as u64);
+        while !self.is_done() {
+            self.step();
+            progress.update(self.round as u64);
+        }
+    }
+}
+
+impl Simulation for KeepAway {
+    type Snapshot = KeepAwaySnapshot;
+
+    fn step(&mut self) {
+        play_round(&mut self.monkeys, &self.relief);
+        self.round += 1;
+        trace!(
+            Info,
+            "round",
+            round = self.round,
+            inspected = format!(
+                "{:?}",
+                self.monkeys
+                    .iter()
+                    .map(|monkey| monkey.inspection_count)
+                    .collect::<Vec<_>>()
+            ),
+        );
+    }
+
+    fn is_done(&self) -> bool {
+        self.round == self.rounds
+    }
+
+    fn snapshot(&self) -> KeepAwaySnapshot {
+        self.monkeys
+            .iter()
+            .map(|monkey| (monkey.items.clone(), monkey.inspection_count))
+            .collect()
+    }
+
+    fn render(&self) -> String {
+        let mut rendered = format!("After round {}:\n", self.round);
+        for (idx, monkey) in self.monkeys.iter().enumerate() {
+            rendered.push_str(&format!(
+                "Monkey {idx} (inspected {} items): {}\n",
+                monkey.inspection_count,
+                monkey
+                    .items
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        rendered
+    }
+}
+
+pub fn simulation(
+    input: &str,
+    part: usize,
+    params: &Params,
+) -> Result<impl Simulation, ParseError> {
+    if part == 1 {
+        KeepAway::new(input, params.get("rounds_1"), Some(params.get("relief")))
+    } else {
+        KeepAway::new(input, params.get("rounds_2"), None)
+    }
+}
+
+//
+fn puzzle_1(input: &str, rounds: usize, relief: usize) -> Result<usize, ParseError> {
+    let mut keep_away = KeepAway::new(input, rounds, Some(relief))?;
+    keep_away.play();
+    Ok(monkey_business(&keep_away.monkeys))
 }
 
 //
-fn puzzle_2(input: &str) -> usize {
-    let mut monkeys = parse_input(input);
-    let modulo: usize = monkeys.iter().map(|m| m.test_no).product();
-    for _ in 0..10_000 {
-        for monkey in 0..monkeys.len() {
-            for idx in 0..monkeys[monkey].items.len() {
-                let mut item = monkeys[monkey].items[idx];
-                item = (monkeys[monkey].operation)(item);
-                item %= modulo;
-                let new_monkey = (monkeys[monkey].test)(item);
-                monkeys[new_monkey].items.push(item);
+fn puzzle_2(input: &str, rounds: usize) -> Result<usize, ParseError> {
+    let mut keep_away = KeepAway::new(input, rounds, None)?;
+    keep_away.play();
+    Ok(monkey_business(&keep_away.monkeys))
+}
+
+// Generate `size` monkeys (between 3 and 9 -- each one throws to two others, and needs its own
+// prime to test with).
+// Like the real puzzle, at most one monkey squares its worry levels; inputs whose first 20 rounds
+// would overflow are thrown away and regenerated.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    struct MonkeySpec {
+        items: Vec<usize>,
+        operation: (&'static str, Option<usize>),
+        test: usize,
+        t: usize,
+        f: usize,
+    }
+
+    fn fits_in_20_rounds(monkeys: &[MonkeySpec]) -> bool {
+        let mut items = monkeys
+            .iter()
+            .map(|monkey| monkey.items.clone())
+            .collect::<Vec<_>>();
+        for _ in 0..20 {
+            for (idx, monkey) in monkeys.iter().enumerate() {
+                for item in std::mem::take(&mut items[idx]) {
+                    let item = match monkey.operation {
+                        ("+", operand) => item.checked_add(operand.unwrap_or(item)),
+                        (_, operand) => item.checked_mul(operand.unwrap_or(item)),
+                    };
+                    let Some(item) = item.map(|item| item / 3) else {
+                        return false;
+                    };
+                    items[if item % monkey.test == 0 {
+                        monkey.t
+                    } else {
+                        monkey.f
+                    }]
+                    .push(item);
+                }
             }
-            monkeys[monkey].inspection_count += monkeys[monkey].items.len();
-            monkeys[monkey].items.clear();
         }
+        true
+    }
+
+    let number_of_monkeys = size.clamp(3, 9);
+    loop {
+        let mut primes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23];
+        rng.shuffle(&mut primes);
+        let squarer = rng.range(0..=number_of_monkeys * 2);
+
+        let monkeys = (0..number_of_monkeys)
+            .map(|idx| {
+                let operation = if idx == squarer {
+                    ("*", None)
+                } else if rng.chance(50) {
+                    ("+", Some(rng.range(1..=8)))
+                } else {
+                    ("*", Some(rng.range(2..=19)))
+                };
+                let t = (idx + rng.range(1..=number_of_monkeys - 1)) % number_of_monkeys;
+                let mut f = t;
+                while f == t || f == idx {
+                    f = rng.range(0..=number_of_monkeys - 1);
+                }
+                MonkeySpec {
+                    items: (0..rng.range(1..=8)).map(|_| rng.range(50..=99)).collect(),
+                    operation,
+                    test: primes[idx],
+                    t,
+                    f,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if !fits_in_20_rounds(&monkeys) {
+            continue;
+        }
+
+        return monkeys
+            .iter()
+            .enumerate()
+            .map(|(idx, monkey)| {
+                format!(
+                    "Monkey {}:\n  Starting items: {}\n  Operation: new = old {} {}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                    idx,
+                    monkey
+                        .items
+                        .iter()
+                        .map(|item| item.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    monkey.operation.0,
+                    monkey
+                        .operation
+                        .1
+                        .map_or("old".to_string(), |operand| operand.to_string()),
+                    monkey.test,
+                    monkey.t,
+                    monkey.f
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isolation::{self, Outcome};
+    use crate::property;
+
+    const TEST_INPUT: &str = EXAMPLE;
+
+    const MONKEY: &str = "Monkey 0:\n  Starting items: 79, 98\n  Operation: new = old * 19\n  Test: divisible by 23\n    If true: throw to monkey 1\n    If false: throw to monkey 1";
+
+    #[test]
+    fn day_11_parse_input_rejects_malformed() {
+        let twice = |monkey: &str| format!("{monkey}\n\n{monkey}");
+        assert!(parse_input(&twice(MONKEY)).is_ok());
+        assert!(parse_input(MONKEY).is_err());
+        assert!(parse_input(&twice(&MONKEY.replace('*', "/"))).is_err());
+        assert!(parse_input(&twice(&MONKEY.replace("23", "0"))).is_err());
+        assert!(parse_input(&twice(&MONKEY.replace("monkey 1", "monkey 2"))).is_err());
+        assert!(parse_input(&twice(&MONKEY.replace("79, 98", "79,"))).is_err());
+        assert!(parse_input(&twice(MONKEY.split_once("\n    If false").unwrap().0)).is_err());
+    }
+
+    #[test]
+    fn day_11_parse_input() {
+        let monkeys = parse_input(TEST_INPUT).unwrap();
+        assert_eq!(monkeys.len(), 4);
+        assert_eq!(monkeys[1].items, vec![54, 65, 75, 74]);
+        assert_eq!((monkeys[2].operation)(3), 9);
+        assert_eq!(monkeys.iter().map(|m| m.test_no).sum::<usize>(), 72);
+        assert_eq!((monkeys[0].test)(46), 2);
+        assert_eq!((monkeys[0].test)(47), 3);
+    }
+
+    #[test]
+    fn day_11_puzzle_1() {
+        assert_eq!(puzzle_1(TEST_INPUT, 20, 3), Ok(10605));
+    }
+
+    #[test]
+    fn day_11_puzzle_2() {
+        assert_eq!(puzzle_2(TEST_INPUT, 20), Ok(99 * 103));
+        assert_eq!(puzzle_2(TEST_INPUT, 10_000), Ok(2713310158));
+    }
+
+    // Without any relief, worry levels soon overflow.
+    #[test]
+    fn day_11_checked_overflow() {
+        let _checked = checked::enable();
+        let Outcome::Failed(failure) = isolation::run(|| puzzle_1(TEST_INPUT, 1_000, 1).map(drop))
+        else {
+            panic!("the worry levels should overflow");
+        };
+        assert!(failure.starts_with("panicked at src/day_11.rs:"));
+        assert!(failure.contains(": day 11 overflowed: "));
+        assert!(failure.ends_with(" doesn't fit in a usize"));
+    }
+
+    #[test]
+    fn day_11_simulation() {
+        let mut keep_away = KeepAway::new(TEST_INPUT, 20, Some(3)).unwrap();
+        keep_away.step();
+        assert_eq!(
+            keep_away.render(),
+            "After round 1:
+Monkey 0 (inspected 2 items): 20, 23, 27, 26
+Monkey 1 (inspected 4 items): 2080, 25, 167, 207, 401, 1046
+Monkey 2 (inspected 3 items): \nMonkey 3 (inspected 5 items): \n"
+        );
+        assert_eq!(keep_away.run(), 19);
+        assert_eq!(
+            keep_away
+                .snapshot()
+                .iter()
+                .map(|&(_, inspected)| inspected)
+                .collect::<Vec<_>>(),
+            vec![101, 95, 7, 105]
+        );
+    }
+
+    #[test]
+    fn day_11_generate() {
+        let input = generate(&mut Rng::new(11), 6);
+        assert_eq!(parse_input(&input).unwrap().len(), 6);
+        assert!(puzzle_1(&input, 20, 3).unwrap() > 0);
+        assert!(puzzle_2(&input, 10_000).unwrap() >= puzzle_1(&input, 20, 3).unwrap());
+    }
+
+    // Monkeys never lose an item, and every item is inspected at least once a round.
+    #[test]
+    fn day_11_property_items_are_conserved() {
+        property::assert_holds("day_11 items are conserved", generate, |_, input| {
+            let mut monkeys = parse_input(input).unwrap();
+            let items = monkeys.iter().map(|m| m.items.len()).sum::<usize>();
+            (1..=20).all(|round| {
+                play_round(&mut monkeys, |item| item / 3);
+                monkeys.iter().map(|m| m.items.len()).sum::<usize>() == items
+                    && monkeys.iter().map(|m| m.inspection_count).sum::<usize>() >= items * round
+            })
+        });
     }
-    let mut inspection_count = monkeys
-        .iter()
-        .map(|monkey| monkey.inspection_count)
-        .collect::<Vec<usize>>();
-    inspection_count.sort();
-    inspection_count[inspection_count.len() - 1] * inspection_count[inspection_count.len() - 2]
 }
@@ -1,9 +1,30 @@
-use std::collections::{HashSet, VecDeque};
+use crate::generate::Rng;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::search::{self, Bfs};
+use crate::simulation::Simulation;
+use crate::visualize::{Frame, Rgb, Visualize};
 
-pub fn run() {
-    let input = include_str!("input/day_12.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // We don't have a good enough signal to contact the elves.
@@ -16,41 +37,26 @@ pub fn run() {
 // We can only move up, down, left, or right.
 // We can only move to squares of at most one level of elevation higher than our current square.
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 struct Position {
-    point: Point,
+    point: Point<usize>,
     letter: char,
 }
 
 impl Position {
-    fn start(grid: &[Vec<char>]) -> Self {
+    fn start(grid: &Grid<char>) -> Self {
         Self::letter_position(grid, 'S')[0]
     }
-    fn end(grid: &[Vec<char>]) -> Self {
+    fn end(grid: &Grid<char>) -> Self {
         Self::letter_position(grid, 'E')[0]
     }
-    fn letter_position(grid: &[Vec<char>], target_letter: char) -> Vec<Self> {
+    fn letter_position(grid: &Grid<char>, target_letter: char) -> Vec<Self> {
         grid.iter()
-            .enumerate()
-            .filter_map(|(y, line)| {
-                let row = line
-                    .iter()
-                    .enumerate()
-                    .find(|(_x, &letter)| letter == target_letter);
-                row.map(|(x, &letter)| Position {
-                    point: Point { x, y },
-                    letter,
-                })
-            })
-            .collect::<Vec<Position>>()
+            .filter(|&(_, &letter)| letter == target_letter)
+            .map(|(point, &letter)| Position { point, letter })
+            .collect()
     }
-    fn neighbours(&self, grid: &[Vec<char>]) -> Vec<Self> {
+    fn neighbours(&self, grid: &Grid<char>) -> Vec<Self> {
         fn is_valid(current: char, other: char) -> bool {
             let current = if current == 'S' { 'a' } else { current };
             let other = if other == 'E' { 'z' } else { other };
@@ -58,122 +64,289 @@ impl Position {
             other <= (current as u8 + 1) as char
         }
 
-        let mut neighbours = Vec::new();
+        grid.neighbours(self.point)
+            .map(|point| Position {
+                point,
+                letter: grid[point],
+            })
+            .filter(|position| is_valid(self.letter, position.letter))
+            .collect()
+    }
+}
 
-        if self.point.x < grid[self.point.y].len() - 1 {
-            neighbours.push(Point {
-                x: self.point.x + 1,
-                y: self.point.y,
-            });
-        }
+// A breadth-first search over the heightmap, one level at a time, so it can be watched.
+#[derive(Clone)]
+struct Exploration {
+    grid: Grid<char>,
+    search: Bfs<Position>,
+    end: Position,
+}
 
-        if self.point.y < grid.len() - 1 {
-            neighbours.push(Point {
-                x: self.point.x,
-                y: self.point.y + 1,
-            });
+impl Exploration {
+    fn new(grid: Grid<char>, starts: Vec<Position>) -> Self {
+        Exploration {
+            end: Position::end(&grid),
+            search: Bfs::new(starts),
+            grid,
         }
+    }
 
-        if self.point.y > 0 {
-            neighbours.push(Point {
-                x: self.point.x,
-                y: self.point.y - 1,
-            });
-        }
+    fn has_reached_end(&self) -> bool {
+        self.search.frontier().any(|&position| position == self.end)
+    }
+}
 
-        if self.point.x > 0 {
-            neighbours.push(Point {
-                x: self.point.x - 1,
-                y: self.point.y,
-            });
-        }
+impl Simulation for Exploration {
+    // The length of the shortest route to E, once it has been found.
+    type Snapshot = Option<usize>;
 
-        neighbours
-            .iter()
-            .map(|&point| Position {
-                letter: grid[point.y][point.x],
-                point,
-            })
-            .filter(|point| is_valid(self.letter, point.letter))
-            .collect()
+    fn step(&mut self) {
+        self.search.step(
+            |current| current.neighbours(&self.grid),
+            |current, reached| {
+                trace!(
+                    Debug,
+                    "expand",
+                    x = current.point.x,
+                    y = current.point.y,
+                    letter = current.letter,
+                    reached = reached,
+                );
+            },
+        );
+        trace!(
+            Info,
+            "level",
+            distance = self.search.depth(),
+            frontier = self.search.frontier().count(),
+            visited = self.search.reached().count(),
+        );
+    }
+
+    fn is_done(&self) -> bool {
+        self.search.is_done() || self.has_reached_end()
+    }
+
+    fn snapshot(&self) -> Option<usize> {
+        self.has_reached_end().then_some(self.search.depth())
+    }
+
+    // The frontier is marked with '*', and squares already explored with '.'.
+    fn render(&self) -> String {
+        Grid::from_fn(self.grid.width(), self.grid.height(), |point| {
+            let letter = self.grid[point];
+            let position = Position { point, letter };
+            if self.search.frontier().any(|&frontier| frontier == position) {
+                '*'
+            } else if letter == 'S' || letter == 'E' {
+                letter
+            } else if self.search.has_reached(&position) {
+                '.'
+            } else {
+                letter
+            }
+        })
+        .to_string()
+    }
+}
+
+// Elevations from dark green to pale brown, the same again tinted blue once explored, then the
+// frontier, S and E.
+const fn heightmap_palette() -> [Rgb; 55] {
+    let mut palette = [[0; 3]; 55];
+    let mut level = 0;
+    while level < 26 {
+        let shade = [
+            (30 + level * 7) as u8,
+            (70 + level * 5) as u8,
+            (30 + level * 5) as u8,
+        ];
+        palette[level] = shade;
+        palette[26 + level] = [shade[0] / 3, shade[1] / 2, 120 + shade[2] / 2];
+        level += 1;
     }
+    palette[52] = [255, 230, 60];
+    palette[53] = [230, 40, 40];
+    palette[54] = [255, 255, 255];
+    palette
 }
 
-fn bfs(grid: &[Vec<char>], start: Position, end: Position) -> Option<usize> {
-    let mut visited: HashSet<Position> = HashSet::new();
-    visited.insert(start);
-    let mut queue = VecDeque::new();
+const HEIGHTMAP_PALETTE: [Rgb; 55] = heightmap_palette();
 
-    queue.push_back((start, 0));
+impl Visualize for Exploration {
+    const PALETTE: &'static [Rgb] = &HEIGHTMAP_PALETTE;
 
-    while let Some((current, count)) = queue.pop_front() {
-        if current == end {
-            return Some(count);
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new(self.grid.width(), self.grid.height());
+        for (point, &letter) in self.grid.iter() {
+            let level = match letter {
+                'S' => 0,
+                'E' => 25,
+                letter => letter as u8 - b'a',
+            };
+            let position = Position { point, letter };
+            let explored = if self.search.has_reached(&position) {
+                26
+            } else {
+                0
+            };
+            frame.set(point.x, point.y, level + explored);
         }
-        for next in current.neighbours(grid) {
-            if visited.insert(next) {
-                queue.push_back((next, count + 1));
-            }
+        for position in self.search.frontier() {
+            frame.set(position.point.x, position.point.y, 52);
         }
+        let (start, end) = (Position::start(&self.grid), self.end);
+        frame.set(start.point.x, start.point.y, 53);
+        frame.set(end.point.x, end.point.y, 54);
+        frame
     }
-    None
 }
 
-fn parse_input(input: &str) -> Vec<Vec<char>> {
-    input
-        .lines()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>()
+// Puzzle 1 explores from S; puzzle 2 explores from every candidate 'a' at once.
+fn exploration(input: &str, part: usize) -> Result<Exploration, ParseError> {
+    let grid = parse_input(input)?;
+    let starts = if part == 1 {
+        vec![Position::start(&grid)]
+    } else {
+        Position::letter_position(&grid, 'a')
+    };
+    Ok(Exploration::new(grid, starts))
 }
 
-//
-fn puzzle_1(input: &str) -> usize {
-    let grid = parse_input(input);
-    let start = Position::start(&grid);
+pub fn simulation(input: &str, part: usize) -> Result<impl Visualize + Clone, ParseError> {
+    exploration(input, part)
+}
+
+fn parse_input(input: &str) -> Result<Grid<char>, ParseError> {
+    let grid = Grid::parse(input, |square| match square {
+        'a'..='z' | 'S' | 'E' => Ok(square),
+        _ => Err(ParseError::new(format!("'{square}' is not an elevation"))),
+    })?;
+    for letter in ['S', 'E'] {
+        if grid.iter().filter(|&(_, &square)| square == letter).count() != 1 {
+            return Err(ParseError::new(format!(
+                "the heightmap should have exactly one '{letter}'"
+            )));
+        }
+    }
+    Ok(grid)
+}
+
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
+
+// Every step costs the same and moves one square, so the Manhattan distance to E never
+// overestimates what's left of a route, and A* can head straight for it.
+fn puzzle_1(input: &str) -> Result<usize, ParseError> {
+    let grid = parse_input(input)?;
     let end = Position::end(&grid);
-    bfs(&grid, start, end).unwrap()
+    search::a_star(
+        [Position::start(&grid)],
+        |current| current.neighbours(&grid).into_iter().map(|next| (next, 1)),
+        |current| current.point.manhattan_distance(end.point),
+        |&current| current == end,
+    )
+    .map(|route| route.cost)
+    .ok_or_else(|| ParseError::new("there is no route from S to E"))
 }
 
 // Can we find a better starting point?
 // We want to start as low as possible (a), yet be the shortest route to E.
-fn puzzle_2(input: &str) -> usize {
-    let grid = parse_input(input);
+fn puzzle_2(input: &str) -> Result<usize, ParseError> {
+    let grid = parse_input(input)?;
     let end = Position::end(&grid);
-    let all_a = Position::letter_position(&grid, 'a');
-    all_a
-        .iter()
-        .filter_map(|start| bfs(&grid, *start, end))
-        .min()
-        .unwrap()
+    search::bfs(
+        Position::letter_position(&grid, 'a'),
+        |current| current.neighbours(&grid),
+        |&current| current == end,
+    )
+    .map(|route| route.steps())
+    .ok_or_else(|| ParseError::new("there is no route from any 'a' to E"))
 }
+// Generate a heightmap at least `size` squares wide, with a guaranteed route from S to E.
+//
+// The route winds from left to right, climbing at most one level per step, and every other square
+// is random. Like the real puzzle, the left-most column is all 'a', so every start considered by
+// puzzle 2 can reach S.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let width = size.max(26);
+    let height = (size / 2).max(2);
+    let mut grid = Grid::from_fn(width, height, |point| {
+        if point.x == 0 {
+            'a'
+        } else {
+            rng.letter(b'a'..=b'z')
+        }
+    });
+
+    let start = Point {
+        x: 0,
+        y: rng.range(0..=height - 1),
+    };
+    let mut route = vec![start];
+    let mut current = start;
+    for x in 1..width {
+        current.x = x;
+        route.push(current);
+        let target = rng.range(0..=height - 1);
+        while current.y != target {
+            current.y = if current.y < target {
+                current.y + 1
+            } else {
+                current.y - 1
+            };
+            route.push(current);
+        }
+    }
+
+    let steps = route.len() - 1;
+    route.iter().enumerate().for_each(|(idx, point)| {
+        grid[*point] = (b'a' + (25 * idx / steps) as u8) as char;
+    });
+    grid[start] = 'S';
+    grid[current] = 'E';
+
+    grid.to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::trace::{self, Filter};
+    use crate::{property, snapshot};
 
-    const TEST_INPUT: &str = "Sabqponm
-abcryxxl
-accszExk
-acctuvwj
-abdefghi";
+    const TEST_INPUT: &str = EXAMPLE;
 
     #[test]
     fn day_12_parse_input() {
+        let grid = parse_input(TEST_INPUT).unwrap();
+        assert_eq!((grid.width(), grid.height()), (8, 5));
         assert_eq!(
-            parse_input(TEST_INPUT),
-            vec![
-                vec!['S', 'a', 'b', 'q', 'p', 'o', 'n', 'm'],
-                vec!['a', 'b', 'c', 'r', 'y', 'x', 'x', 'l'],
-                vec!['a', 'c', 'c', 's', 'z', 'E', 'x', 'k'],
-                vec!['a', 'c', 'c', 't', 'u', 'v', 'w', 'j'],
-                vec!['a', 'b', 'd', 'e', 'f', 'g', 'h', 'i']
-            ]
+            grid.rows().nth(2),
+            Some(&['a', 'c', 'c', 's', 'z', 'E', 'x', 'k'][..])
         );
+        assert_eq!(grid.to_string(), TEST_INPUT.to_string() + "\n");
+    }
+
+    #[test]
+    fn day_12_parse_input_rejects_malformed() {
+        assert!(parse_input("SaE\nab").is_err());
+        assert!(parse_input("Sa1E").is_err());
+        assert!(parse_input("SabS\naaaE").is_err());
+        assert!(parse_input("Saaa").is_err());
+        assert!(puzzle_1("SazE").is_err());
     }
 
     #[test]
     fn day_12_start() {
         assert_eq!(
-            Position::start(&parse_input(TEST_INPUT)),
+            Position::start(&parse_input(TEST_INPUT).unwrap()),
             Position {
                 point: Point { x: 0, y: 0 },
                 letter: 'S'
@@ -184,7 +357,7 @@ abdefghi";
     #[test]
     fn day_12_end() {
         assert_eq!(
-            Position::end(&parse_input(TEST_INPUT)),
+            Position::end(&parse_input(TEST_INPUT).unwrap()),
             Position {
                 point: Point { x: 5, y: 2 },
                 letter: 'E'
@@ -194,7 +367,7 @@ abdefghi";
 
     #[test]
     fn day_12_neighbors() {
-        let grid = parse_input(TEST_INPUT);
+        let grid = parse_input(TEST_INPUT).unwrap();
         assert_eq!(
             Position::start(&grid).neighbours(&grid),
             vec![
@@ -215,6 +388,10 @@ abdefghi";
             }
             .neighbours(&grid),
             vec![
+                Position {
+                    point: Point { x: 0, y: 0 },
+                    letter: 'S'
+                },
                 Position {
                     point: Point { x: 1, y: 1 },
                     letter: 'b'
@@ -222,10 +399,6 @@ abdefghi";
                 Position {
                     point: Point { x: 0, y: 2 },
                     letter: 'a'
-                },
-                Position {
-                    point: Point { x: 0, y: 0 },
-                    letter: 'S'
                 }
             ]
         );
@@ -236,6 +409,10 @@ abdefghi";
             }
             .neighbours(&grid),
             vec![
+                Position {
+                    point: Point { x: 1, y: 0 },
+                    letter: 'a'
+                },
                 Position {
                     point: Point { x: 2, y: 1 },
                     letter: 'c'
@@ -244,10 +421,6 @@ abdefghi";
                     point: Point { x: 1, y: 2 },
                     letter: 'c'
                 },
-                Position {
-                    point: Point { x: 1, y: 0 },
-                    letter: 'a'
-                },
                 Position {
                     point: Point { x: 0, y: 1 },
                     letter: 'a'
@@ -258,11 +431,69 @@ abdefghi";
 
     #[test]
     fn day_12_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 31);
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(31));
     }
 
     #[test]
     fn day_12_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 29);
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(29));
+    }
+
+    #[test]
+    fn day_12_simulation() {
+        let mut exploration = exploration(TEST_INPUT, 1).unwrap();
+        exploration.step();
+        exploration.step();
+        assert_eq!(
+            exploration.render(),
+            "S.*qponm\n.*cryxxl\n*ccszExk\nacctuvwj\nabdefghi\n"
+        );
+        assert_eq!(exploration.snapshot(), None);
+        assert_eq!(exploration.run(), 29);
+        assert_eq!(exploration.snapshot(), Some(31));
+
+        let mut halfway = super::exploration(TEST_INPUT, 1).unwrap();
+        (0..12).for_each(|_| halfway.step());
+        snapshot::assert_matches("day_12_exploration", &halfway.render());
+    }
+
+    #[test]
+    fn day_12_trace() {
+        let events = trace::capture(Filter::new(1, "").unwrap(), || {
+            exploration(TEST_INPUT, 1).unwrap().run();
+        });
+        snapshot::assert_matches("day_12_trace", &events);
+    }
+
+    #[test]
+    fn day_12_visualize() {
+        let mut exploration = exploration(TEST_INPUT, 2).unwrap();
+        exploration.step();
+        let frame = exploration.frame();
+        assert_eq!((frame.width(), frame.height()), (8, 5));
+        assert_eq!(frame.get(0, 0), 53);
+        assert_eq!(frame.get(5, 2), 54);
+        assert_eq!(frame.get(0, 1), 26);
+        assert_eq!(frame.get(1, 1), 52);
+        assert_eq!(frame.get(7, 4), 8);
+    }
+
+    #[test]
+    fn day_12_generate() {
+        let input = generate(&mut Rng::new(12), 40);
+        let grid = parse_input(&input).unwrap();
+        assert_eq!((grid.width(), grid.height()), (40, 20));
+        assert!(puzzle_1(&input).unwrap() >= 25);
+        assert!(puzzle_2(&input).unwrap() <= puzzle_1(&input).unwrap() + 1);
+    }
+
+    // Every step moves one square, so no route can be shorter than the distance from S to E.
+    #[test]
+    fn day_12_property_route_length() {
+        property::assert_holds("day_12 route length", generate, |_, input| {
+            let grid = parse_input(input).unwrap();
+            let (start, end) = (Position::start(&grid).point, Position::end(&grid).point);
+            puzzle_1(input).unwrap() >= start.x.abs_diff(end.x) + start.y.abs_diff(end.y)
+        });
     }
 }
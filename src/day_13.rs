@@ -1,60 +1,146 @@
+use crate::explain::{Explanation, Witness};
+use crate::generate::Rng;
+use crate::parse::{
+    self, blocks, either, integer, line, lines, map, optional, pair, preceded, separated,
+    terminated, Input, ParseError,
+};
 use std::cmp::Ordering;
+use std::fmt;
 
-pub fn run() {
-    let input = include_str!("input/day_13.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
+
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 //
 
-fn parse_input(input: &str) -> Vec<Vec<(Vec<i64>, usize)>> {
-    input
-        .split("\n\n")
-        .map(|pair| {
-            pair.lines()
-                .map(|line| {
-                    (
-                        line.replace("[]", "-1")
-                            .replace([',', '[', ']'], " ")
-                            .split_whitespace()
-                            .map(|digit| digit.parse::<i64>().unwrap())
-                            .collect::<Vec<i64>>(),
-                        line.chars().filter(|&c| c == '[' || c == ']').count(),
-                    )
-                })
-                .collect::<Vec<(Vec<i64>, usize)>>()
-        })
-        .collect::<Vec<Vec<(Vec<i64>, usize)>>>()
+// A packet's flattened values, and how many brackets it took to write it.
+type Packet = (Vec<i64>, usize);
+
+// A packet as it's written: a list of integers and other lists.
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Integer(u64),
+    List(Vec<Value>),
+}
+
+fn value(input: &mut Input) -> Result<Value, ParseError> {
+    either(map(list, Value::List), map(integer(), Value::Integer))(input)
+}
+
+fn list(input: &mut Input) -> Result<Vec<Value>, ParseError> {
+    let items = optional(separated(value, ","));
+    map(
+        preceded("[", terminated(items, "]")),
+        Option::unwrap_or_default,
+    )(input)
+}
+
+// An empty list flattens to -1, so that it comes before any integer -- and before any list that
+// has an integer in it.
+fn flatten(packet: &[Value]) -> Option<Packet> {
+    fn flatten_into(list: &[Value], values: &mut Vec<i64>) -> Option<usize> {
+        if list.is_empty() {
+            values.push(-1);
+        }
+        let mut brackets = 2;
+        for value in list {
+            match value {
+                Value::Integer(integer) => values.push(i64::try_from(*integer).ok()?),
+                Value::List(list) => brackets += flatten_into(list, values)?,
+            }
+        }
+        Some(brackets)
+    }
+
+    let mut values = Vec::new();
+    let brackets = flatten_into(packet, &mut values)?;
+    Some((values, brackets))
+}
+
+fn packet(input: &mut Input) -> Result<Packet, ParseError> {
+    let start = *input;
+    let packet = list(input)?;
+    flatten(&packet).ok_or_else(|| start.error("this packet has an integer too large to compare"))
+}
+
+fn parse_input(input: &str) -> Result<Vec<Vec<Packet>>, ParseError> {
+    let pair = map(pair(line(packet), line(packet)), |(left, right)| {
+        vec![left, right]
+    });
+    parse::all(input, blocks(pair))
+}
+
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
+
+// Packets are ordered by their values; when those are equal, the packet with fewer brackets
+// comes first.
+fn compare((left, left_length): &Packet, (right, right_length): &Packet) -> Ordering {
+    left.iter()
+        .cmp(right.iter())
+        .then(left_length.cmp(right_length))
 }
 
 //
-fn puzzle_1(input: &str) -> usize {
-    parse_input(input)
+fn puzzle_1(input: &str) -> Result<usize, ParseError> {
+    Ok(parse_input(input)?
         .iter()
         .enumerate()
         .map(|(idx, pair)| {
-            let (left, left_length) = &pair[0];
-            let (right, right_length) = &pair[1];
-
-            match left.iter().cmp(right.iter()) {
-                Ordering::Equal => {
-                    if right_length < left_length {
-                        0
-                    } else {
-                        idx + 1
-                    }
-                }
-                Ordering::Less => idx + 1,
+            let order = compare(&pair[0], &pair[1]);
+            trace!(Info, "pair", pair = idx + 1, order = format!("{order:?}"));
+            match order {
                 Ordering::Greater => 0,
+                _ => idx + 1,
             }
         })
-        .sum()
+        .sum())
 }
 
 //
-fn puzzle_2(input: &str) -> usize {
-    let mut inputs = parse_input(input);
+fn puzzle_2(input: &str) -> Result<usize, ParseError> {
+    let mut inputs = parse_input(input)?;
     inputs.push(vec![(vec![2], 5), (vec![6], 5)]);
     let mut inputs = inputs
         .into_iter()
@@ -73,42 +159,183 @@ fn puzzle_2(input: &str) -> usize {
         .position(|line| line.len() == 1 && line[0] == 6)
         .unwrap()
         + 1;
-    a * b
+    Ok(a * b)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// Witnesses are checked against packets parsed into the trees they're written as, and compared by
+// the puzzle's rules directly, rather than by their flattened values.
+impl Value {
+    fn parse(line: &str) -> Result<Value, String> {
+        parse::all(line, map(list, Value::List)).map_err(|err| err.to_string())
+    }
 
-    const TEST_INPUT: &str = "[1,1,3,1,1]
-[1,1,5,1,1]
+    fn order(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Integer(left), Value::Integer(right)) => left.cmp(right),
+            (Value::List(left), Value::List(right)) => left
+                .iter()
+                .zip(right)
+                .map(|(left, right)| left.order(right))
+                .find(|&order| order != Ordering::Equal)
+                .unwrap_or_else(|| left.len().cmp(&right.len())),
+            (&Value::Integer(left), Value::List(_)) => {
+                Value::List(vec![Value::Integer(left)]).order(other)
+            }
+            (Value::List(_), &Value::Integer(right)) => {
+                self.order(&Value::List(vec![Value::Integer(right)]))
+            }
+        }
+    }
+}
 
-[[1],[2,3,4]]
-[[1],4]
+fn parse_values(input: &str) -> Result<Vec<Value>, String> {
+    parse::all(input, blocks(lines(map(list, Value::List))))
+        .map(|blocks| blocks.concat())
+        .map_err(|err| err.to_string())
+}
 
-[9]
-[[8,7,6]]
+// The pairs (numbered from 1) that are already in the right order.
+#[derive(Debug, PartialEq)]
+struct OrderedPairs(Vec<usize>);
 
-[[4,4],4,4]
-[[4,4],4,4,4]
+fn ordered_pairs(input: &str) -> Result<OrderedPairs, ParseError> {
+    Ok(OrderedPairs(
+        parse_input(input)?
+            .iter()
+            .enumerate()
+            .filter(|(_, pair)| compare(&pair[0], &pair[1]) != Ordering::Greater)
+            .map(|(idx, _)| idx + 1)
+            .collect(),
+    ))
+}
 
-[7,7,7,7]
-[7,7,7]
+impl fmt::Display for OrderedPairs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs = self.0.iter().map(usize::to_string).collect::<Vec<_>>();
+        writeln!(f, "pairs in the right order: {}", pairs.join(", "))
+    }
+}
 
-[]
-[3]
+impl Witness for OrderedPairs {
+    type Answer = usize;
 
-[[[]]]
-[[]]
+    fn answer(&self) -> usize {
+        self.0.iter().sum()
+    }
 
-[1,[2,[3,[4,[5,6,7]]]],8,9]
-[1,[2,[3,[4,[5,6,0]]]],8,9]";
+    // Identical packets never come up in the puzzle; like the solver, they count as in order.
+    fn check(&self, input: &str) -> Result<(), String> {
+        let packets = parse_values(input)?;
+        let ordered = packets
+            .chunks(2)
+            .enumerate()
+            .filter(|(_, pair)| pair.len() == 2 && pair[0].order(&pair[1]) != Ordering::Greater)
+            .map(|(idx, _)| idx + 1)
+            .collect::<Vec<_>>();
+        match ordered.iter().find(|pair| !self.0.contains(pair)) {
+            Some(pair) => Err(format!("pair {pair} is in the right order too")),
+            None => match self.0.iter().find(|pair| !ordered.contains(pair)) {
+                Some(pair) => Err(format!("pair {pair} is out of order")),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+// Where the divider packets end up (numbered from 1) once every packet is sorted.
+#[derive(Debug, PartialEq)]
+struct Dividers {
+    two: usize,
+    six: usize,
+}
+
+fn dividers(input: &str) -> Result<Dividers, ParseError> {
+    let packets = parse_input(input)?.concat();
+    let before = |divider: &Packet| {
+        packets
+            .iter()
+            .filter(|packet| compare(packet, divider) == Ordering::Less)
+            .count()
+    };
+    Ok(Dividers {
+        two: before(&(vec![2], 4)) + 1,
+        six: before(&(vec![6], 4)) + 2,
+    })
+}
+
+impl fmt::Display for Dividers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "once sorted, [[2]] is packet {} and [[6]] is packet {}",
+            self.two, self.six
+        )
+    }
+}
+
+impl Witness for Dividers {
+    type Answer = usize;
+
+    fn answer(&self) -> usize {
+        self.two * self.six
+    }
+
+    fn check(&self, input: &str) -> Result<(), String> {
+        let mut packets = parse_values(input)?;
+        let dividers = [Value::parse("[[2]]")?, Value::parse("[[6]]")?];
+        packets.extend(dividers.clone());
+        packets.sort_by(Value::order);
+
+        let [two, six] = [&dividers[0], &dividers[1]]
+            .map(|divider| packets.iter().position(|packet| packet == divider).unwrap() + 1);
+        if (two, six) == (self.two, self.six) {
+            Ok(())
+        } else {
+            Err(format!("[[2]] is packet {two} and [[6]] is packet {six}"))
+        }
+    }
+}
+
+pub fn explain(input: &str) -> Result<Vec<Explanation>, ParseError> {
+    Ok(vec![
+        Explanation::new(1, puzzle_1(input)?, &ordered_pairs(input)?, input),
+        Explanation::new(2, puzzle_2(input)?, &dividers(input)?, input),
+    ])
+}
+
+// Generate `size` pairs of randomly nested packets.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    fn packet(rng: &mut Rng, depth: usize) -> String {
+        let elements = (0..rng.range(0..=4))
+            .map(|_| {
+                if depth < 4 && rng.chance(30) {
+                    packet(rng, depth + 1)
+                } else {
+                    rng.range(0..=10).to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", elements.join(","))
+    }
+
+    (0..size.max(1))
+        .map(|_| format!("{}\n{}\n", packet(rng, 0), packet(rng, 0)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property;
+
+    const TEST_INPUT: &str = EXAMPLE;
 
     #[test]
     fn day_13_parse_input() {
         assert_eq!(
             parse_input(TEST_INPUT),
-            vec![
+            Ok(vec![
                 vec![(vec![1, 1, 3, 1, 1], 2), (vec![1, 1, 5, 1, 1], 2)],
                 vec![(vec![1, 2, 3, 4], 6), (vec![1, 4], 4)],
                 vec![(vec![9], 2), (vec![8, 7, 6], 4)],
@@ -120,18 +347,82 @@ mod tests {
                     (vec![1, 2, 3, 4, 5, 6, 7, 8, 9], 10),
                     (vec![1, 2, 3, 4, 5, 6, 0, 8, 9], 10)
                 ]
-            ]
+            ])
         );
     }
 
+    #[test]
+    fn day_13_parse_input_rejects_malformed() {
+        assert!(parse_input("[1]").is_err());
+        assert!(parse_input("[1]\n[2]\n[3]").is_err());
+        assert!(parse_input("[1]\n[2").is_err());
+        assert!(parse_input("[1]\n2]").is_err());
+        assert!(parse_input("[1]\n[a]").is_err());
+        assert!(parse_input("[1]\n[99999999999999999999]").is_err());
+    }
+
     #[test]
     fn day_13_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 13);
-        //panic!();
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(13));
     }
 
     #[test]
     fn day_13_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 140);
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(140));
+    }
+
+    #[test]
+    fn day_13_explain() {
+        assert_eq!(
+            ordered_pairs(TEST_INPUT),
+            Ok(OrderedPairs(vec![1, 2, 4, 6]))
+        );
+        assert_eq!(dividers(TEST_INPUT), Ok(Dividers { two: 10, six: 14 }));
+        assert!(explain(TEST_INPUT)
+            .unwrap()
+            .iter()
+            .all(Explanation::is_verified));
+
+        assert_eq!(
+            OrderedPairs(vec![1, 2, 4]).check(TEST_INPUT),
+            Err("pair 6 is in the right order too".to_string())
+        );
+        assert_eq!(
+            OrderedPairs(vec![1, 2, 3, 4, 6]).check(TEST_INPUT),
+            Err("pair 3 is out of order".to_string())
+        );
+        assert!(Dividers { two: 10, six: 13 }.check(TEST_INPUT).is_err());
+    }
+
+    #[test]
+    fn day_13_generate() {
+        let input = generate(&mut Rng::new(13), 50);
+        let pairs = parse_input(&input).unwrap();
+        assert_eq!(pairs.len(), 50);
+        assert!(pairs.iter().all(|pair| pair.len() == 2));
+        assert!(puzzle_1(&input).unwrap() <= (1..=50).sum());
+    }
+
+    #[test]
+    fn day_13_property_total_order() {
+        property::assert_holds("day_13 total order", generate, |_, input| {
+            let packets = parse_input(input)
+                .unwrap()
+                .concat()
+                .into_iter()
+                .take(20)
+                .collect::<Vec<Packet>>();
+            packets.iter().all(|a| {
+                compare(a, a) == Ordering::Equal
+                    && packets.iter().all(|b| {
+                        compare(a, b) == compare(b, a).reverse()
+                            && packets.iter().all(|c| {
+                                compare(a, b) == Ordering::Greater
+                                    || compare(b, c) == Ordering::Greater
+                                    || compare(a, c) != Ordering::Greater
+                            })
+                    })
+            })
+        });
     }
 }
@@ -1,9 +1,27 @@
+use crate::generate::Rng;
+use crate::geometry;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::simulation::Simulation;
+use crate::visualize::{Frame, Rgb, Visualize};
 use std::fmt;
 
-pub fn run() {
-    let input = include_str!("input/day_14.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // We've been led to a giant waterfall.
@@ -48,36 +66,11 @@ pub fn run() {
 // 8 ........#.
 // 9 #########.
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Point {
-    fn is_horizontal(&self, other: &Point) -> bool {
-        self.y == other.y
-    }
-
-    fn is_vertical(&self, other: &Point) -> bool {
-        self.x == other.x
-    }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct Line {
-    start: Point,
-    end: Point,
-}
-
-impl From<(usize, usize)> for Point {
-    fn from((x, y): (usize, usize)) -> Self {
-        Point { x, y }
-    }
-}
+type Point = geometry::Point<usize>;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 enum Element {
+    #[default]
     Air,
     Rock,
     Sand,
@@ -99,69 +92,42 @@ impl fmt::Display for Element {
     }
 }
 
-impl Default for Element {
-    fn default() -> Self {
-        Element::Air
-    }
-}
-
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 struct Cave {
-    array: Vec<Element>,
-    width: usize,
-    height: usize,
+    grid: Grid<Element>,
 }
 
 impl fmt::Display for Cave {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for row in 0..self.height {
-            let row = self.get_row(row).expect("This row did not exist: {row}");
-            let mut line = String::new();
-            for e in row {
-                line.push_str(&format!("{e}"));
-            }
-            line.push('\n');
-            write!(f, "{line}")?;
-        }
-        Ok(())
+        write!(f, "{}", self.grid)
     }
 }
 
 impl Cave {
     fn new(width: usize, height: usize) -> Self {
         Self {
-            array: [Element::default()].repeat(width * height),
-            width,
-            height,
+            grid: Grid::new(width, height, Element::default()),
         }
     }
 
     fn width(&self) -> usize {
-        self.width
+        self.grid.width()
     }
 
     fn height(&self) -> usize {
-        self.height
+        self.grid.height()
     }
 
     fn get(&self, p: &Point) -> Option<&Element> {
-        self.array.get(p.y * self.width + p.x)
-    }
-
-    fn get_row(&self, row: usize) -> Option<&[Element]> {
-        let array = &self.array;
-        let width = &self.width;
-        array
-            .get(row * width)
-            .and(Some(&array[row * width..row * width + width]))
+        self.grid.get(*p)
     }
 
     fn set(&mut self, p: Point, val: Element) {
-        self.array[p.y * self.width + p.x] = val;
+        self.grid[p] = val;
     }
 
     fn set_line(&mut self, start: Point, end: Point, val: Element) {
-        if start.is_horizontal(&end) {
+        if start.y == end.y {
             let y = start.y;
             let (start, end) = if start.x <= end.x {
                 (start, end)
@@ -172,7 +138,7 @@ impl Cave {
                 self.set((x, y).into(), val);
             }
         } else {
-            assert!(start.is_vertical(&end));
+            assert_eq!(start.x, end.x);
             let x = start.x;
             let (start, end) = if start.y <= end.y {
                 (start, end)
@@ -186,11 +152,7 @@ impl Cave {
     }
 
     fn find(&self, item: Element) -> Option<Point> {
-        let position = self.array.iter().position(|&element| element == item);
-        position.map(|pos| Point {
-            x: pos % self.width,
-            y: pos / self.width,
-        })
+        self.grid.position(|&element| element == item)
     }
 
     fn check_below(&self, current: &Point) -> Option<Option<Point>> {
@@ -249,27 +211,49 @@ impl Cave {
     }
 }
 
-fn parse_input(input: &str) -> Cave {
+// The cave is only as wide as the sand could ever spread -- a triangle below the source -- so the
+// rock paths must stay within it, and can't be deeper than the source is far from x = 0.
+fn parse_input(input: &str) -> Result<Cave, ParseError> {
     let input = input
         .lines()
         .map(|structure| {
-            structure
+            let structure = structure
                 .split(" -> ")
                 .map(|point| {
-                    let (x, y) = point.split_once(',').unwrap();
-                    (x.parse::<usize>().unwrap(), y.parse::<usize>().unwrap()).into()
+                    let (x, y) = parse::split_once(point, ",")?;
+                    Ok((parse::number::<usize>(x)?, parse::number::<usize>(y)?).into())
                 })
-                .collect::<Vec<Point>>()
+                .collect::<Result<Vec<Point>, ParseError>>()?;
+            if structure
+                .windows(2)
+                .any(|pair| pair[0].x != pair[1].x && pair[0].y != pair[1].y)
+            {
+                return Err(ParseError::new("rock paths should only be straight lines"));
+            }
+            Ok(structure)
         })
-        .collect::<Vec<Vec<Point>>>();
+        .collect::<Result<Vec<Vec<Point>>, ParseError>>()?;
 
     let largest_y = input
         .iter()
         .flatten()
-        .max_by(|a, b| a.y.cmp(&b.y))
-        .unwrap()
-        .y
-        + 2;
+        .map(|point| point.y)
+        .max()
+        .ok_or_else(|| ParseError::new("the scan should have at least one rock"))?;
+    if largest_y > 499 {
+        return Err(ParseError::new(format!("{largest_y} is too deep")));
+    }
+    if let Some(point) = input
+        .iter()
+        .flatten()
+        .find(|point| point.x.abs_diff(500) > largest_y + 1)
+    {
+        return Err(ParseError::new(format!(
+            "{},{} is out of reach of the sand",
+            point.x, point.y
+        )));
+    }
+    let largest_y = largest_y + 2;
 
     let width = (largest_y * 2) - 1;
     let height = largest_y + 1;
@@ -294,7 +278,16 @@ fn parse_input(input: &str) -> Cave {
     });
     cave.set(((500 - shift), 0).into(), Element::SandGenerator);
 
-    cave
+    Ok(cave)
+}
+
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
 }
 
 // Sand will fall into the cave at the point (500, 0) (marked with a '+' above).
@@ -312,53 +305,273 @@ fn parse_input(input: &str) -> Cave {
 // Simulate the falling sand.
 // How many pieces of sand must settle on the rock structures before all future sand would flow
 // below them?
-fn puzzle_1(input: &str) -> usize {
-    let mut cave = parse_input(input);
-    let mut counter = 0;
+fn puzzle_1(input: &str) -> Result<usize, ParseError> {
+    let mut sand_fall = SandFall::new(input, false)?;
+    sand_fall.run();
+    Ok(sand_fall.sand)
+}
 
-    let sand_generator = cave.find(Element::SandGenerator).unwrap();
+// Now there's a floor!
+fn puzzle_2(input: &str) -> Result<u64, ParseError> {
+    let mut sand_fall = SandFall::new(input, true)?;
+    sand_fall.run();
+    Ok(sand_fall.sand as u64)
+}
 
-    while let Some(_resting_point) = cave.simulate_sand(sand_generator) {
-        counter += 1;
-    }
+// Sand falling into the cave, one unit at a time.
+#[derive(Clone)]
+struct SandFall {
+    cave: Cave,
+    sand_generator: Point,
+    has_floor: bool,
+    sand: usize,
+    last_at_rest: Option<Point>,
+    done: bool,
+}
 
-    counter
+#[derive(Debug, PartialEq)]
+struct SandFallSnapshot {
+    cave: Cave,
+    sand: usize,
+    last_at_rest: Option<Point>,
 }
 
-// Now there's a floor!
-fn puzzle_2(input: &str) -> u64 {
-    let mut cave = parse_input(input);
-    cave.set_line(
-        (0, cave.height() - 1).into(),
-        (cave.width() - 1, cave.height() - 1).into(),
-        Element::Rock,
-    );
+impl SandFall {
+    fn new(input: &str, has_floor: bool) -> Result<Self, ParseError> {
+        let mut cave = parse_input(input)?;
+        if has_floor {
+            cave.set_line(
+                (0, cave.height() - 1).into(),
+                (cave.width() - 1, cave.height() - 1).into(),
+                Element::Rock,
+            );
+        }
+        Ok(SandFall {
+            sand_generator: cave.find(Element::SandGenerator).unwrap(),
+            cave,
+            has_floor,
+            sand: 0,
+            last_at_rest: None,
+            done: false,
+        })
+    }
+}
+
+// Each step is a unit of sand, which either comes to rest or -- without a floor -- falls into the
+// abyss. With a floor, the last unit of sand comes to rest on the source itself, blocking it.
+impl Simulation for SandFall {
+    type Snapshot = SandFallSnapshot;
+
+    fn step(&mut self) {
+        self.last_at_rest = self.cave.simulate_sand(self.sand_generator);
+        if self.last_at_rest.is_none() {
+            self.done = true;
+            if self.has_floor {
+                self.cave.set(self.sand_generator, Element::Sand);
+                self.last_at_rest = Some(self.sand_generator);
+            }
+        }
+        if self.last_at_rest.is_some() {
+            self.sand += 1;
+        }
+        match self.last_at_rest {
+            Some(Point { x, y }) => trace!(Info, "sand", count = self.sand, x = x, y = y),
+            None => trace!(Info, "abyss", count = self.sand),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
 
-    let mut counter = 0;
+    fn snapshot(&self) -> SandFallSnapshot {
+        SandFallSnapshot {
+            cave: self.cave.clone(),
+            sand: self.sand,
+            last_at_rest: self.last_at_rest,
+        }
+    }
 
-    let sand_generator = cave.find(Element::SandGenerator).unwrap();
+    fn render(&self) -> String {
+        self.cave.to_string()
+    }
+}
 
-    while let Some(_resting_point) = cave.simulate_sand(sand_generator) {
-        counter += 1;
+impl Visualize for SandFall {
+    const PALETTE: &'static [Rgb] = &[[20, 16, 28], [110, 105, 100], [230, 190, 90], [220, 60, 50]];
+
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new(self.cave.width(), self.cave.height());
+        for y in 0..self.cave.height() {
+            for x in 0..self.cave.width() {
+                let colour = match self.cave.get(&(x, y).into()) {
+                    Some(Element::Air) | None => 0,
+                    Some(Element::Rock) => 1,
+                    Some(Element::Sand) => 2,
+                    Some(Element::SandGenerator) => 3,
+                };
+                frame.set(x, y, colour);
+            }
+        }
+        frame
     }
+}
 
-    counter + 1
+pub fn simulation(input: &str, part: usize) -> Result<impl Visualize + Clone, ParseError> {
+    SandFall::new(input, part == 2)
+}
+
+// Generate a scan of rock paths reaching `size` (at most 400) levels below the sand source.
+// Every point stays within the part of the cave that sand can reach once the floor is in.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let depth = size.clamp(2, 400);
+    let mut paths = (0..rng.range(0..=(depth / 3).max(2)))
+        .map(|_| {
+            let mut current = (rng.range(500 - depth..=500 + depth), rng.range(1..=depth));
+            let mut path = vec![current];
+            let horizontal_first = rng.chance(50);
+            for segment in 0..rng.range(1..=4) {
+                let length = rng.range_i64(-8..=8);
+                if (segment % 2 == 0) == horizontal_first {
+                    current.0 = (current.0 as i64 + length) as usize;
+                } else {
+                    current.1 = (current.1 as i64 + length).clamp(1, depth as i64) as usize;
+                }
+                path.push(current);
+            }
+            path
+        })
+        .collect::<Vec<_>>();
+    let ledge = rng.range(500 - depth..=500 + depth);
+    paths.push(vec![(ledge, depth), (ledge + rng.range(0..=8), depth)]);
+
+    let lowest = paths.iter().flatten().map(|&(_, y)| y).max().unwrap();
+    paths
+        .iter()
+        .map(|path| {
+            let mut line = path
+                .iter()
+                .map(|&(x, y)| format!("{},{}", x.clamp(500 - lowest, 500 + lowest), y))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            line.push('\n');
+            line
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::trace::{self, Filter};
+    use crate::{property, snapshot};
 
-    const TEST_INPUT: &str = "498,4 -> 498,6 -> 496,6
-503,4 -> 502,4 -> 502,9 -> 494,9";
+    const TEST_INPUT: &str = EXAMPLE;
+
+    #[test]
+    fn day_14_parse_input_rejects_malformed() {
+        assert!(parse_input("").is_err());
+        assert!(parse_input("498,4 -> 498").is_err());
+        assert!(parse_input("498,4 -> 496,6").is_err());
+        assert!(parse_input("500,500").is_err());
+        assert!(parse_input("0,4").is_err());
+        assert!(parse_input("495,4").is_ok());
+    }
 
     #[test]
     fn day_14_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 24);
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(24));
     }
 
     #[test]
     fn day_14_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 93);
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(93));
+    }
+
+    #[test]
+    fn day_14_simulation() {
+        let mut sand_fall = SandFall::new(TEST_INPUT, false).unwrap();
+        (0..5).for_each(|_| sand_fall.step());
+        assert_eq!(sand_fall.snapshot().sand, 5);
+        assert_eq!(
+            sand_fall.snapshot().last_at_rest,
+            Some(Point { x: 8, y: 8 })
+        );
+        assert_eq!(
+            sand_fall
+                .render()
+                .lines()
+                .skip(6)
+                .take(4)
+                .collect::<Vec<_>>(),
+            vec![
+                "......###...#........",
+                "..........o.#........",
+                "........oooo#........",
+                "....#########........",
+            ]
+        );
+        assert_eq!(sand_fall.run(), 20);
+        assert_eq!(sand_fall.snapshot().sand, 24);
+        snapshot::assert_matches("day_14_sand_abyss", &sand_fall.render());
+
+        let mut sand_fall = SandFall::new(TEST_INPUT, true).unwrap();
+        assert_eq!(sand_fall.run(), 93);
+        assert_eq!(
+            sand_fall.snapshot().last_at_rest,
+            Some(Point { x: 10, y: 0 })
+        );
+        snapshot::assert_matches("day_14_sand_floor", &sand_fall.render());
+    }
+
+    #[test]
+    fn day_14_trace() {
+        let events = trace::capture(Filter::new(1, "").unwrap(), || {
+            SandFall::new(TEST_INPUT, false).unwrap().run();
+        });
+        snapshot::assert_matches("day_14_trace", &events);
+    }
+
+    #[test]
+    fn day_14_visualize() {
+        let mut sand_fall = SandFall::new(TEST_INPUT, true).unwrap();
+        sand_fall.step();
+        let frame = sand_fall.frame();
+        assert_eq!((frame.width(), frame.height()), (21, 12));
+        assert_eq!(frame.get(10, 0), 3);
+        assert_eq!(frame.get(10, 8), 2);
+        assert_eq!(frame.get(4, 9), 1);
+        assert_eq!(frame.get(0, 11), 1);
+        assert_eq!(frame.get(0, 0), 0);
+    }
+
+    #[test]
+    fn day_14_generate() {
+        let input = generate(&mut Rng::new(14), 30);
+        let cave = parse_input(&input).unwrap();
+        assert_eq!(cave.height(), 33);
+        assert!(puzzle_1(&input).unwrap() as u64 <= puzzle_2(&input).unwrap());
+    }
+
+    // With a floor, sand comes to rest on every square it can reach, so adding rock can only take
+    // squares away. (Without a floor this doesn't hold: a new rock can catch sand that would have
+    // fallen into the abyss.)
+    #[test]
+    fn day_14_property_rocks_never_add_sand() {
+        property::assert_holds("day_14 rocks never add sand", generate, |rng, input| {
+            let lowest = parse_input(input).unwrap().height() - 3;
+            let mut input = input.to_string();
+            let mut sand = puzzle_2(&input).unwrap();
+            (0..5).all(|_| {
+                let y = rng.range(1..=lowest);
+                let x = rng.range(500 - lowest..=500 + lowest);
+                let end = (x + rng.range(0..=3)).min(500 + lowest);
+                input.push_str(&format!("{x},{y} -> {end},{y}\n"));
+                let more_rock = puzzle_2(&input).unwrap();
+                let fewer_sand = more_rock <= sand;
+                sand = more_rock;
+                fewer_sand
+            })
+        });
     }
 }
@@ -1,7 +1,59 @@
-pub fn run() {
-    let input = include_str!("input/day_15.txt");
-    println!("{}", puzzle_1(input, 2_000_000));
-    println!("{}", puzzle_2(input, 4_000_000));
+use crate::checked;
+use crate::explain::{Explanation, Witness};
+use crate::generate::Rng;
+use crate::geometry::{self, BoundingBox, Direction, Vector};
+use crate::interval::IntervalSet;
+use crate::params::{Parameter, Params};
+use crate::parse::{self, integer, lines, map, pair, preceded, ParseError, Parser};
+use std::fmt;
+
+pub const PARAMETERS: &[Parameter] = &[
+    Parameter::new(
+        "row",
+        2_000_000,
+        i32::MIN as i64,
+        "the row to count positions in, for puzzle 1",
+    )
+    .at_most(i32::MAX as i64)
+    .example(10),
+    Parameter::new(
+        "region",
+        4_000_000,
+        0,
+        "the largest x and y of the distress beacon",
+    )
+    .at_most(i32::MAX as i64)
+    .example(20),
+];
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+
+pub fn run(input: &str, params: &Params) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1, params)?);
+    println!("{}", solve(input, 2, params)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize, params: &Params) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input, params.get("row"))?.to_string()),
+        2 => Ok(puzzle_2(input, params.get("region"))?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // We've been led to a network of subterranean tunnels.
@@ -21,29 +73,18 @@ pub fn run() {
 //
 // None of the detected beacons is the one that we are searching for.
 
-#[derive(Debug, Clone, Copy, PartialEq, Hash, Eq)]
-struct Point {
-    x: i64,
-    y: i64,
-}
-
-impl Point {
-    fn manhattan_distance_to(&self, rhs: &Point) -> i64 {
-        (self.x - rhs.x).abs() + (self.y - rhs.y).abs()
-    }
+type Point = geometry::Point<i64>;
 
-    fn parse_point(input: &str) -> Self {
-        // expected form: 'x=<x>, y=<y>'
-        let (x, y) = input
-            .strip_prefix("x=")
-            .unwrap()
-            .split_once(", y=")
-            .unwrap();
-        Point {
-            x: x.parse::<i64>().unwrap(),
-            y: y.parse::<i64>().unwrap(),
-        }
-    }
+// Co-ordinates are limited to an i32, so that distances between them can't overflow. So are the
+// row and the region the puzzles look at, which are parameters.
+fn point<'a>() -> impl Parser<'a, Point> {
+    map(
+        pair(
+            preceded("x=", integer::<i32>()),
+            preceded(", y=", integer::<i32>()),
+        ),
+        |(x, y)| Point::new(x as i64, y as i64),
+    )
 }
 
 #[derive(Debug)]
@@ -61,57 +102,74 @@ impl Sensor {
             distance_to_beacon,
         }
     }
+}
 
-    fn can_contain_unseen_points(&self, min: Point, max: Point) -> bool {
-        let corners = [
-            (min.x, min.y),
-            (min.x, max.y),
-            (max.x, min.y),
-            (max.x, max.y),
-        ];
-        let largetst_dist = corners
-            .iter()
-            .map(|corner| (corner.0 - self.location.x).abs() + (corner.1 - self.location.y).abs())
-            .max()
-            .unwrap();
-        largetst_dist > self.distance_to_beacon
-    }
+fn parse_input(input: &str) -> Result<Vec<Sensor>, ParseError> {
+    let sensor = pair(
+        preceded("Sensor at ", point()),
+        preceded(": closest beacon is at ", point()),
+    );
+    parse::all(
+        input,
+        lines(map(sensor, |(sensor, beacon)| {
+            Sensor::new(sensor, beacon, sensor.manhattan_distance(beacon))
+        })),
+    )
 }
 
-fn parse_input(input: &str) -> Vec<Sensor> {
-    fn parse_pairs((sensor, beacon): (&str, &str)) -> (Point, Point) {
-        (Point::parse_point(sensor), Point::parse_point(beacon))
-    }
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
 
-    input
-        .lines()
-        .map(|line| {
-            let (sensor, beacon) = parse_pairs(
-                line.strip_prefix("Sensor at ")
-                    .unwrap()
-                    .split_once(": closest beacon is at ")
-                    .unwrap(),
-            );
-            Sensor::new(sensor, beacon, sensor.manhattan_distance_to(&beacon))
-        })
-        .collect::<Vec<Sensor>>()
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
 }
 
 // Count the positions where a beacon cannot possibly be, along a single row.
 // How many positions cannot hold a beacon in the row where y=2_000_000
-fn puzzle_1(input: &str, target_row: i64) -> usize {
+//
+// Each sensor covers a single range of the row, so merge those ranges instead of visiting every
+// position in them.
+fn puzzle_1(input: &str, target_row: i64) -> Result<usize, ParseError> {
+    let sensors = parse_input(input)?;
+
+    let covered = covered(&sensors, target_row);
+    let beacons = sensors
+        .iter()
+        .filter(|sensor| sensor.nearest_beacon.y == target_row)
+        .map(|sensor| sensor.nearest_beacon.x..=sensor.nearest_beacon.x)
+        .collect();
+
+    Ok(covered.difference(&beacons).len() as usize)
+}
+
+// The positions in `row` that some sensor is at least as close to as its beacon.
+fn covered(sensors: &[Sensor], row: i64) -> IntervalSet<i64> {
+    sensors
+        .iter()
+        .filter_map(|sensor| {
+            let range = sensor.distance_to_beacon - checked::sub(sensor.location.y, row).abs();
+            (range >= 0).then_some(sensor.location.x - range..=sensor.location.x + range)
+        })
+        .collect()
+}
+
+// The original, position-by-position count -- kept as a reference for `puzzle_1`.
+#[cfg(test)]
+fn puzzle_1_reference(input: &str, target_row: i64) -> Result<usize, ParseError> {
     use std::collections::HashSet;
 
-    let sensors = parse_input(input);
+    let sensors = parse_input(input)?;
     let mut on_target = HashSet::new();
 
     sensors.iter().for_each(|sensor| {
-        if sensor.location.manhattan_distance_to(&Point {
+        if sensor.location.manhattan_distance(Point {
             x: sensor.location.x,
             y: target_row,
         }) <= sensor.distance_to_beacon
         {
-            let distance_to_target = (sensor.location.y - target_row).abs();
+            let distance_to_target = checked::sub(sensor.location.y, target_row).abs();
             let range = sensor.distance_to_beacon - distance_to_target;
 
             if range >= 0 {
@@ -131,76 +189,327 @@ fn puzzle_1(input: &str, target_row: i64) -> usize {
         }
     });
 
-    on_target.len()
+    Ok(on_target.len())
 }
 
 // Now we're finding the one point in a region that is unexplored by the surrounding sensors.
 // Use that position to find the answer!
-fn puzzle_2(input: &str, target_region_size: i64) -> i64 {
-    let min = Point { x: 0, y: 0 };
-    let max = Point {
-        x: target_region_size,
-        y: target_region_size,
-    };
+fn puzzle_2(input: &str, target_region_size: i64) -> Result<i64, ParseError> {
+    let beacon = distress_beacon(input, target_region_size)?;
+    Ok(tuning_frequency(beacon))
+}
+
+// The multiplier is the puzzle's, whatever the size of the region being searched.
+const TUNING_MULTIPLIER: i64 = 4_000_000;
+
+fn tuning_frequency(beacon: Point) -> i64 {
+    checked::add(checked::mul(beacon.x, TUNING_MULTIPLIER), beacon.y)
+}
+
+// Row by row, what the sensors cover is a set of ranges, as in `puzzle_1`. Covering everything
+// outside the region as well leaves only the gaps inside it, and the one row with a gap is the
+// distress beacon's.
+fn distress_beacon(input: &str, target_region_size: i64) -> Result<Point, ParseError> {
+    let sensors = parse_input(input)?;
+    let outside = IntervalSet::from_iter([i64::MIN..=-1, target_region_size + 1..=i64::MAX]);
+    let mut progress = progress!("row", target_region_size as u64 + 1);
 
-    let sensors = parse_input(input);
-    let mut quadrant_stack = vec![(min, max)];
-    let mut position = None;
+    for y in 0..=target_region_size {
+        if let Some(gap) = covered(&sensors, y).union(&outside).gaps().next() {
+            return Ok(Point::new(*gap.start(), y));
+        }
+        progress.update(y as u64 + 1);
+    }
+    Err(ParseError::new(
+        "every position in the region is covered by a sensor",
+    ))
+}
 
-    while let Some((min, max)) = quadrant_stack.pop() {
-        if min == max {
-            if sensors.iter().all(|sensor| {
-                sensor.location.manhattan_distance_to(&min) > sensor.distance_to_beacon
-            }) {
-                position = Some(min);
+// Where the distress beacon is, in a region where both x and y are within 0..=size.
+#[derive(Debug, PartialEq)]
+struct DistressBeacon {
+    position: Point,
+    size: i64,
+}
+
+impl fmt::Display for DistressBeacon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "the distress beacon is at x={}, y={}",
+            self.position.x, self.position.y
+        )
+    }
+}
+
+impl Witness for DistressBeacon {
+    type Answer = i64;
+
+    fn answer(&self) -> i64 {
+        tuning_frequency(self.position)
+    }
+
+    // The beacon has to be in the region, out of every sensor's range, and walled in: each of its
+    // neighbours in the region is in some sensor's range, or it wouldn't be the only place left.
+    fn check(&self, input: &str) -> Result<(), String> {
+        let sensors = parse_input(input).map_err(|err| err.to_string())?;
+        let region = BoundingBox::new(Point::default(), Point::new(self.size, self.size));
+        let in_region = |point: &Point| region.contains(*point);
+        let in_range = |point: &Point| {
+            sensors.iter().find(|sensor| {
+                sensor.location.manhattan_distance(*point) <= sensor.distance_to_beacon
+            })
+        };
+
+        let Point { x, y } = self.position;
+        if !in_region(&self.position) {
+            return Err(format!("x={x}, y={y} is outside the region"));
+        }
+        if let Some(sensor) = in_range(&self.position) {
+            let Point { x: sx, y: sy } = sensor.location;
+            return Err(format!(
+                "x={x}, y={y} is in range of the sensor at x={sx}, y={sy}"
+            ));
+        }
+        match Direction::ALL
+            .into_iter()
+            .map(|direction| self.position + direction.vector())
+            .find(|point| in_region(point) && in_range(point).is_none())
+        {
+            Some(Point { x, y }) => Err(format!("x={x}, y={y} is out of range too")),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn explain(input: &str, params: &Params) -> Result<Vec<Explanation>, ParseError> {
+    let region = params.get("region");
+    Ok(vec![Explanation::new(
+        2,
+        puzzle_2(input, region)?,
+        &DistressBeacon {
+            position: distress_beacon(input, region)?,
+            size: region,
+        },
+        input,
+    )])
+}
+
+// Generate sensor reports that leave exactly one position uncovered in the region where both x
+// and y are within 0..=size -- the distress beacon.
+//
+// One sensor diagonally beyond each side of the hidden position is enough to cover the rest of
+// the region; more sensors are then scattered around, without covering the hidden position or
+// disagreeing with any other sensor about which beacon is nearest.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    fn fits(sensors: &[Sensor], location: Point, distance: i64, beacon: Point) -> bool {
+        sensors.iter().all(|sensor| {
+            sensor.location != location
+                && (sensor.nearest_beacon == beacon
+                    || sensor.location.manhattan_distance(beacon) > sensor.distance_to_beacon)
+                && (sensor.nearest_beacon == beacon
+                    || location.manhattan_distance(sensor.nearest_beacon) > distance)
+        })
+    }
+
+    fn place(rng: &mut Rng, sensors: &mut Vec<Sensor>, location: Point, distance: i64) -> bool {
+        let vertices = [
+            Direction::Down,
+            Direction::Up,
+            Direction::Right,
+            Direction::Left,
+        ]
+        .map(|direction| direction.vector() * distance);
+        let random_edge = (0..32)
+            .map(|_| {
+                let dx = rng.range_i64(-distance..=distance);
+                Vector::new(
+                    dx,
+                    (distance - dx.abs()) * if rng.chance(50) { 1 } else { -1 },
+                )
+            })
+            .collect::<Vec<_>>();
+        let beacon = vertices
+            .into_iter()
+            .chain(random_edge)
+            .map(|offset| location + offset)
+            .find(|&beacon| fits(sensors, location, distance, beacon));
+
+        if let Some(beacon) = beacon {
+            sensors.push(Sensor::new(location, beacon, distance));
+        }
+        beacon.is_some()
+    }
+
+    let region = size.max(4) as i64;
+    'attempt: loop {
+        let hidden = Point {
+            x: rng.range_i64(0..=region),
+            y: rng.range_i64(0..=region),
+        };
+        let mut sensors = Vec::new();
+
+        for (sign_x, sign_y) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            let (dx, dy) = (
+                rng.range_i64(region + 1..=region * 2),
+                rng.range_i64(region + 1..=region * 2),
+            );
+            let location = hidden + Vector::new(sign_x * dx, sign_y * dy);
+            if !place(rng, &mut sensors, location, dx + dy - 1) {
+                continue 'attempt;
             }
-        } else {
-            let mid = Point {
-                x: (min.x + max.x) / 2,
-                y: (min.y + max.y) / 2,
+        }
+
+        // The region is already covered, so most new beacons would land inside another sensor's
+        // range; when that happens, the new sensor reports its nearest existing beacon instead.
+        for _ in 0..rng.range(8..=24) {
+            let location = Point {
+                x: rng.range_i64(-region * 2..=region * 3),
+                y: rng.range_i64(-region * 2..=region * 3),
             };
-            let quadrants = [
-                (min, mid),
-                (
-                    Point {
-                        x: mid.x + 1,
-                        y: min.y,
-                    },
-                    Point { x: max.x, y: mid.y },
-                ),
-                (
-                    Point {
-                        x: min.x,
-                        y: mid.y + 1,
-                    },
-                    Point { x: mid.x, y: max.y },
-                ),
-                (
-                    Point {
-                        x: mid.x + 1,
-                        y: mid.y + 1,
-                    },
-                    max,
-                ),
-            ];
-
-            for quad in quadrants.iter() {
-                if quad.0.x > quad.1.x || quad.0.y > quad.1.y {
-                    continue;
-                }
-                if sensors
-                    .iter()
-                    .all(|pair| pair.can_contain_unseen_points(quad.0, quad.1))
-                {
-                    quadrant_stack.push(*quad);
-                }
+            let mut beacons = sensors
+                .iter()
+                .map(|sensor| sensor.nearest_beacon)
+                .collect::<Vec<_>>();
+            beacons.sort_by_key(|beacon| location.manhattan_distance(*beacon));
+            beacons.dedup();
+            let nearest = location.manhattan_distance(beacons[0]);
+            let to_hidden = location.manhattan_distance(hidden);
+
+            let distance = nearest.min(to_hidden) - 1;
+            if distance > 0 && place(rng, &mut sensors, location, distance) {
+                continue;
+            }
+            let unambiguous = beacons
+                .get(1)
+                .is_none_or(|next| location.manhattan_distance(*next) > nearest);
+            if nearest > 0
+                && nearest < to_hidden
+                && unambiguous
+                && sensors.iter().all(|sensor| sensor.location != location)
+            {
+                sensors.push(Sensor::new(location, beacons[0], nearest));
             }
         }
+
+        rng.shuffle(&mut sensors);
+        return sensors
+            .iter()
+            .map(|sensor| {
+                format!(
+                    "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+                    sensor.location.x,
+                    sensor.location.y,
+                    sensor.nearest_beacon.x,
+                    sensor.nearest_beacon.y
+                )
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::differential::{self, Differential};
+    use crate::property;
+
+    const TEST_INPUT: &str = EXAMPLE;
+
+    const PUZZLE_1: Differential = Differential {
+        name: "day_15::puzzle_1",
+        reference: |input| format!("{:?}", puzzle_1_reference(input, 10)),
+        optimised: |input| format!("{:?}", puzzle_1(input, 10)),
+    };
+
+    #[test]
+    fn day_15_parse_input_rejects_malformed() {
+        assert!(parse_input("Sensor at x=2, y=18").is_err());
+        assert!(parse_input("Sensor at x=2, y=18: closest beacon is at -2, 15").is_err());
+        assert!(
+            parse_input("Sensor at x=2, y=18: closest beacon is at x=-2, y=9999999999").is_err()
+        );
+        assert!(puzzle_2("Sensor at x=0, y=0: closest beacon is at x=9, y=9", 4).is_err());
+    }
+
+    #[test]
+    fn day_15_puzzle_1() {
+        assert_eq!(puzzle_1(TEST_INPUT, 10), Ok(26));
+    }
+
+    // Rows as far away as the parameter allows don't overflow, even in checked mode.
+    #[test]
+    fn day_15_puzzle_1_far_rows() {
+        let _checked = checked::enable();
+        for row in [PARAMETERS[0].min, PARAMETERS[0].max] {
+            assert_eq!(puzzle_1(TEST_INPUT, row), Ok(0));
+        }
+    }
+
+    #[test]
+    fn day_15_puzzle_2() {
+        assert_eq!(puzzle_2(TEST_INPUT, 20), Ok(56_000_011));
+    }
+
+    #[test]
+    fn day_15_puzzle_1_differential() {
+        differential::assert_agree(&PUZZLE_1, &[TEST_INPUT], generate);
+    }
+
+    #[test]
+    fn day_15_explain() {
+        assert_eq!(distress_beacon(TEST_INPUT, 20), Ok(Point { x: 14, y: 11 }));
+        let beacon = |x, y| DistressBeacon {
+            position: Point { x, y },
+            size: 20,
+        };
+        assert_eq!(beacon(14, 11).check(TEST_INPUT), Ok(()));
+        assert_eq!(
+            beacon(14, 10).check(TEST_INPUT),
+            Err("x=14, y=10 is in range of the sensor at x=8, y=7".to_string())
+        );
+        assert!(beacon(21, 11).check(TEST_INPUT).is_err());
+
+        let explanation = Explanation::new(2, 56_000_011, &beacon(14, 11), TEST_INPUT);
+        assert!(explanation.is_verified());
+    }
+
+    #[test]
+    fn day_15_generate() {
+        for seed in 0..20 {
+            let input = generate(&mut Rng::new(seed), 20);
+            let sensors = parse_input(&input).unwrap();
+            assert!(sensors.iter().all(|sensor| {
+                sensors.iter().all(|other| {
+                    other.nearest_beacon == sensor.nearest_beacon
+                        || sensor.location.manhattan_distance(other.nearest_beacon)
+                            > sensor.distance_to_beacon
+                })
+            }));
+
+            let uncovered = (0..=20)
+                .flat_map(|x| (0..=20).map(move |y| Point { x, y }))
+                .filter(|point| {
+                    sensors.iter().all(|sensor| {
+                        sensor.location.manhattan_distance(*point) > sensor.distance_to_beacon
+                    })
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(uncovered.len(), 1);
+            assert_eq!(
+                puzzle_2(&input, 20),
+                Ok(uncovered[0].x * 4_000_000 + uncovered[0].y)
+            );
+        }
     }
 
-    if let Some(position) = position {
-        (position.x * target_region_size) + position.y
-    } else {
-        panic!()
+    // A sensor rules out every position on its own row within range, other than its beacon.
+    #[test]
+    fn day_15_property_sensor_rows_are_covered() {
+        property::assert_holds("day_15 sensor rows are covered", generate, |_, input| {
+            parse_input(input).unwrap().iter().all(|sensor| {
+                puzzle_1(input, sensor.location.y).unwrap() as i64 >= 2 * sensor.distance_to_beacon
+            })
+        });
     }
 }
@@ -1,7 +1,21 @@
-pub fn run() {
-    let input = include_str!("input/day_2.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "A Y\nB X\nC Z";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // A Rock, Paper, Scissors tournament.
@@ -15,13 +29,28 @@ pub fn run() {
 // Winner has the highest total score -- sum of all round scores
 // Round score = shape (Rock = 1; Paper = 2; Scissors = 3) + outcome (loss = 0; draw = 3; win = 6)
 
-fn parse_input(input: &str, puzzle_map: fn((&str, &str)) -> u64) -> Vec<u64> {
+fn parse_input(
+    input: &str,
+    puzzle_map: fn((&str, &str)) -> Option<u64>,
+) -> Result<Vec<u64>, ParseError> {
     input
         .lines()
-        .map(|round| round.split_once(' ').map(puzzle_map).unwrap())
+        .map(|round| {
+            puzzle_map(parse::split_once(round, " ")?)
+                .ok_or_else(|| ParseError::new(format!("'{round}' is not a valid round")))
+        })
         .collect()
 }
 
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input, puzzle_1_map).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input, puzzle_1_map)?))
+}
+
 const ROCK: u64 = 1;
 const PAPER: u64 = 2;
 const SCISSORS: u64 = 3;
@@ -36,8 +65,8 @@ const WIN: u64 = 6;
 // Y = Paper
 // Z = Scissors
 
-fn puzzle_1_map(input: (&str, &str)) -> u64 {
-    match input {
+fn puzzle_1_map(input: (&str, &str)) -> Option<u64> {
+    Some(match input {
         ("A", "X") => ROCK + DRAW,
         ("A", "Y") => PAPER + WIN,
         ("A", "Z") => SCISSORS + LOSE,
@@ -47,13 +76,13 @@ fn puzzle_1_map(input: (&str, &str)) -> u64 {
         ("C", "X") => ROCK + WIN,
         ("C", "Y") => PAPER + LOSE,
         ("C", "Z") => SCISSORS + DRAW,
-        _ => unreachable!(),
-    }
+        _ => return None,
+    })
 }
 
 // Calculate the score if we were to follow the strategy guide
-fn puzzle_1(input: &str) -> u64 {
-    parse_input(input, puzzle_1_map).iter().sum()
+fn puzzle_1(input: &str) -> Result<u64, ParseError> {
+    Ok(parse_input(input, puzzle_1_map)?.iter().sum())
 }
 
 // Puzzle 2:
@@ -62,8 +91,8 @@ fn puzzle_1(input: &str) -> u64 {
 // Y = Draw
 // Z = Win
 
-fn puzzle_2_map(input: (&str, &str)) -> u64 {
-    match input {
+fn puzzle_2_map(input: (&str, &str)) -> Option<u64> {
+    Some(match input {
         ("A", "X") => SCISSORS + LOSE,
         ("A", "Y") => ROCK + DRAW,
         ("A", "Z") => PAPER + WIN,
@@ -73,37 +102,70 @@ fn puzzle_2_map(input: (&str, &str)) -> u64 {
         ("C", "X") => PAPER + LOSE,
         ("C", "Y") => SCISSORS + DRAW,
         ("C", "Z") => ROCK + WIN,
-        _ => unreachable!(),
-    }
+        _ => return None,
+    })
 }
 
 // Calculate the score if we follow this guide and chose the correct corresponding shape
-fn puzzle_2(input: &str) -> u64 {
-    parse_input(input, puzzle_2_map).iter().sum()
+fn puzzle_2(input: &str) -> Result<u64, ParseError> {
+    Ok(parse_input(input, puzzle_2_map)?.iter().sum())
+}
+
+// Generate a strategy guide of `size` rounds.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{} {}\n", rng.letter(b'A'..=b'C'), rng.letter(b'X'..=b'Z')))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const TEST_INPUT: &str = "A Y\nB X\nC Z";
+    use crate::property;
+    const TEST_INPUT: &str = EXAMPLE;
 
     #[test]
     fn day_2_puzzle_1_parse_input() {
-        assert_eq!(parse_input(TEST_INPUT, puzzle_1_map), vec![8, 1, 6]);
+        assert_eq!(parse_input(TEST_INPUT, puzzle_1_map), Ok(vec![8, 1, 6]));
     }
 
     #[test]
     fn day_2_puzzle_2_parse_input() {
-        assert_eq!(parse_input(TEST_INPUT, puzzle_2_map), vec![4, 1, 7])
+        assert_eq!(parse_input(TEST_INPUT, puzzle_2_map), Ok(vec![4, 1, 7]))
+    }
+
+    #[test]
+    fn day_2_parse_input_rejects_malformed() {
+        assert!(parse_input("A", puzzle_1_map).is_err());
+        assert!(parse_input("A W", puzzle_2_map).is_err());
     }
 
     #[test]
     fn day_2_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 15);
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(15));
     }
 
     #[test]
     fn day_2_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 12);
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(12));
+    }
+
+    #[test]
+    fn day_2_generate() {
+        let input = generate(&mut Rng::new(2), 100);
+        assert_eq!(parse_input(&input, puzzle_1_map).unwrap().len(), 100);
+        assert_eq!(parse_input(&input, puzzle_2_map).unwrap().len(), 100);
+    }
+
+    #[test]
+    fn day_2_property_round_scores() {
+        property::assert_holds("day_2 round scores", generate, |_, input| {
+            [puzzle_1_map, puzzle_2_map].iter().all(|&puzzle_map| {
+                parse_input(input, puzzle_map)
+                    .unwrap()
+                    .iter()
+                    .all(|score| (1..=9).contains(score))
+            })
+        });
     }
 }
@@ -1,26 +1,35 @@
-pub fn run() {
+use crate::parse::ParseError;
+
+// There's no puzzle input for this day yet, let alone a solution.
+pub fn run() -> Result<(), ParseError> {
 	//let input = include_str!("input/day_20.txt");
-	//println!("{}", puzzle_1(input));
-	//println!("{}", puzzle_2(input));
+	let input = "";
+	println!("{}", puzzle_1(input));
+	println!("{}", puzzle_2(input));
+	Ok(())
 }
 
 // 
 
-fn parse_input(input: &str) -> Vec<u64> {
+fn parse_input(_input: &str) -> Vec<u64> {
 	unimplemented!()
 }
 
 // 
 fn puzzle_1(input: &str) -> u64 {
+	let _input = parse_input(input);
 	unimplemented!()
 }
 
 // 
 fn puzzle_2(input: &str) -> u64 {
+	let _input = parse_input(input);
 	unimplemented!()
 }
 
+// The tests to fill in once the day is solved.
 #[cfg(test)]
+#[allow(unused_imports, dead_code)]
 mod tests {
 	use super::*;
 
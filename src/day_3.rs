@@ -1,7 +1,21 @@
-pub fn run() {
-    let input = include_str!("input/day_3.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+use crate::generate::Rng;
+use crate::parse::ParseError;
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\nwMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // An elf loads rucksacks with supplies.
@@ -20,21 +34,56 @@ fn ascii_to_number(input: char) -> u64 {
     }
 }
 
+fn parse_input(input: &str) -> Result<Vec<&str>, ParseError> {
+    input
+        .lines()
+        .map(|rucksack| {
+            if !rucksack.chars().all(|item| item.is_ascii_alphabetic()) {
+                Err(ParseError::new(format!(
+                    "'{rucksack}' contains something other than a letter"
+                )))
+            } else if rucksack.len() % 2 != 0 {
+                Err(ParseError::new(format!(
+                    "'{rucksack}' can't be split into two equal compartments"
+                )))
+            } else {
+                Ok(rucksack)
+            }
+        })
+        .collect()
+}
+
+// Every rucksack needs an item in both compartments, and every group a badge, for there to be
+// answers.
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    puzzle_1(input)?;
+    puzzle_2(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
+
 // Puzzle 1:
 // Compartment 1 = first half of the letters on a line,
 // Compartment 2 = second half of the letters on a line.
 //
 // What is the sum of the priorities of the item types that appear in both compartments?
-fn puzzle_1(input: &str) -> u64 {
-    input
-        .lines()
+fn puzzle_1(input: &str) -> Result<u64, ParseError> {
+    parse_input(input)?
+        .iter()
         .map(|rucksack| rucksack.split_at(rucksack.len() / 2))
         .map(|(compartment_1, compartment_2)| {
             compartment_1
                 .chars()
                 .find(|&char_1| compartment_2.contains(char_1))
                 .map(ascii_to_number)
-                .unwrap()
+                .ok_or_else(|| {
+                    ParseError::new(format!(
+                        "no item is in both '{compartment_1}' and '{compartment_2}'"
+                    ))
+                })
         })
         .sum()
 }
@@ -45,30 +94,69 @@ fn puzzle_1(input: &str) -> u64 {
 // The letter identifying each badge can be different between groups.
 //
 // What is the sum of the priorities of each group's badge item-kind
-fn puzzle_2(input: &str) -> u64 {
-    input
-        .lines()
-        .collect::<Vec<_>>()
+fn puzzle_2(input: &str) -> Result<u64, ParseError> {
+    parse_input(input)?
         .chunks(3)
         .map(|group| {
-            let member_1 = group[0];
-            let member_2 = group[1];
-            let member_3 = group[2];
+            let [member_1, member_2, member_3] = group else {
+                return Err(ParseError::new(
+                    "the elves can't be split into groups of three",
+                ));
+            };
 
             member_1
                 .chars()
                 .find(|&char_1| member_2.contains(char_1) && member_3.contains(char_1))
                 .map(ascii_to_number)
-                .unwrap()
+                .ok_or_else(|| ParseError::new(format!("'{member_1}''s group has no badge")))
         })
         .sum()
 }
 
+// Generate `size` groups of three elves.
+// Each rucksack has exactly one item type in both compartments, and each group has exactly one
+// badge: the members' other items are drawn from disjoint pools of item types.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut item_types = ('a'..='z').chain('A'..='Z').collect::<Vec<char>>();
+    let mut rucksacks = String::new();
+
+    for _ in 0..size.max(1) {
+        rng.shuffle(&mut item_types);
+        let (badge, others) = item_types.split_first().unwrap();
+
+        for pool in others.chunks(others.len() / 3).take(3) {
+            let (shared, pool) = pool.split_first().unwrap();
+            let (left, right) = pool.split_at(rng.range(1..=pool.len() - 1));
+            let length = rng.range(4..=16);
+            let badge_in_left = rng.chance(50);
+
+            let mut compartments =
+                [(left, badge_in_left), (right, !badge_in_left)].map(|(pool, has_badge)| {
+                    let mut compartment = vec![*shared];
+                    if has_badge {
+                        compartment.push(*badge);
+                    }
+                    while compartment.len() < length {
+                        compartment.push(*rng.choose(pool));
+                    }
+                    rng.shuffle(&mut compartment);
+                    compartment
+                });
+            compartments.iter_mut().for_each(|compartment| {
+                rucksacks.extend(compartment.iter());
+            });
+            rucksacks.push('\n');
+        }
+    }
+    rucksacks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::property;
 
-    const TEST_INPUT: &str = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\nwMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw";
+    const TEST_INPUT: &str = EXAMPLE;
 
     #[test]
     fn day_3_ascii_to_numbers() {
@@ -78,13 +166,50 @@ mod tests {
         assert_eq!(ascii_to_number('Z'), 52);
     }
 
+    #[test]
+    fn day_3_parse_input_rejects_malformed() {
+        assert!(parse_input("vJrwpWtwJgW").is_err());
+        assert!(parse_input("vJrwp4").is_err());
+        assert!(puzzle_1("abcd").is_err());
+        assert!(puzzle_2("aa\naa").is_err());
+        assert!(check_input("aa\nbb\ncc\n").is_err());
+    }
+
     #[test]
     fn day_3_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 157);
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(157));
     }
 
     #[test]
     fn day_3_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 70);
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(70));
+    }
+
+    #[test]
+    fn day_3_generate() {
+        let input = generate(&mut Rng::new(3), 20);
+        assert_eq!(input.lines().count(), 60);
+        assert!(input.lines().all(|rucksack| {
+            let (compartment_1, compartment_2) = rucksack.split_at(rucksack.len() / 2);
+            let mut shared = compartment_1
+                .chars()
+                .filter(|&item| compartment_2.contains(item))
+                .collect::<Vec<_>>();
+            shared.dedup();
+            shared.len() == 1
+        }));
+        assert!(puzzle_2(&input).unwrap() > 0);
+    }
+
+    // Swapping the compartments of every rucksack doesn't change which item is in both.
+    #[test]
+    fn day_3_property_compartment_order() {
+        property::assert_holds("day_3 compartment order", generate, |_, input| {
+            let reversed = input
+                .lines()
+                .map(|rucksack| rucksack.chars().rev().collect::<String>() + "\n")
+                .collect::<String>();
+            puzzle_1(input) == puzzle_1(&reversed) && puzzle_2(input) == puzzle_2(&reversed)
+        });
     }
 }
@@ -1,9 +1,23 @@
+use crate::generate::Rng;
+use crate::interval::IntervalSet;
+use crate::parse::{self, integer, lines, map, pair, preceded, Input, ParseError};
 use std::ops::RangeInclusive;
 
-pub fn run() {
-    let input = include_str!("input/day_4.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // Elves have been assigned to clear space in the camp.
@@ -15,75 +29,137 @@ pub fn run() {
 // To do this they pair up, and make a list of assigned area ranges.
 // Input is a list of pairs of comma-seperated inclusive ranges
 
-fn parse_input(input: &str) -> Vec<Vec<RangeInclusive<u64>>> {
-    input
-        .lines()
-        .map(|pair| {
-            pair.split(',')
-                .map(|area| {
-                    let bounds = area
-                        .split('-')
-                        .map(|bound| bound.parse::<u64>().unwrap())
-                        .collect::<Vec<u64>>();
-                    RangeInclusive::new(bounds[0], bounds[1])
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>()
+// An area runs from its first section to its last, so it can't end before it starts.
+fn parse_input(input: &str) -> Result<Vec<Vec<RangeInclusive<u64>>>, ParseError> {
+    let area = |input: &mut Input| {
+        let start = *input;
+        let (first, last) = pair(integer::<u64>(), preceded("-", integer()))(input)?;
+        if first > last {
+            return Err(start.error(format!("the area {first}-{last} ends before it starts")));
+        }
+        Ok(first..=last)
+    };
+    parse::all(
+        input,
+        lines(map(pair(area, preceded(",", area)), |(first, second)| {
+            vec![first, second]
+        })),
+    )
+}
+
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
+
+fn sections(pair: &[RangeInclusive<u64>]) -> (IntervalSet<u64>, IntervalSet<u64>) {
+    (pair[0].clone().into(), pair[1].clone().into())
+}
+
+fn fully_contains(pair: &[RangeInclusive<u64>]) -> bool {
+    let (first, second) = sections(pair);
+    first.is_superset(&second) || second.is_superset(&first)
+}
+
+fn overlaps(pair: &[RangeInclusive<u64>]) -> bool {
+    let (first, second) = sections(pair);
+    first.overlaps(&second)
 }
 
 // In how many assignment pairs does one range fully contain the other?
-fn puzzle_1(input: &str) -> u64 {
-    parse_input(input)
+fn puzzle_1(input: &str) -> Result<u64, ParseError> {
+    Ok(parse_input(input)?
         .iter()
-        .filter(|pair| {
-            (pair[0].contains(pair[1].start()) && pair[0].contains(pair[1].end()))
-                || (pair[1].contains(pair[0].start()) && pair[1].contains(pair[0].end()))
-        })
-        .count() as u64
+        .filter(|pair| fully_contains(pair))
+        .count() as u64)
 }
 
 // How many assignment pairs overlap at all?
-fn puzzle_2(input: &str) -> u64 {
-    parse_input(input)
+fn puzzle_2(input: &str) -> Result<u64, ParseError> {
+    Ok(parse_input(input)?
         .iter()
-        .filter(|pair| {
-            pair[0].contains(pair[1].start())
-                || pair[0].contains(pair[1].end())
-                || pair[1].contains(pair[0].start())
-                || pair[1].contains(pair[0].end())
-        })
-        .count() as u64
+        .filter(|pair| overlaps(pair))
+        .count() as u64)
+}
+
+// Generate `size` pairs of section assignments.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut assignment = || {
+        let start = rng.range(1..=99);
+        format!("{}-{}", start, rng.range(start..=99))
+    };
+    (0..size.max(1))
+        .map(|_| format!("{},{}\n", assignment(), assignment()))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::property;
 
-    const TEST_INPUT: &str = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8";
+    const TEST_INPUT: &str = EXAMPLE;
 
     #[test]
     fn day_4_parse_input() {
         assert_eq!(
             parse_input(TEST_INPUT),
-            vec![
+            Ok(vec![
                 vec![2..=4, 6..=8],
                 vec![2..=3, 4..=5],
                 vec![5..=7, 7..=9],
                 vec![2..=8, 3..=7],
                 vec![6..=6, 4..=6],
                 vec![2..=6, 4..=8]
-            ]
+            ])
+        );
+    }
+
+    #[test]
+    fn day_4_parse_input_rejects_malformed() {
+        assert!(parse_input("2-4").is_err());
+        assert!(parse_input("2-4,6").is_err());
+        assert!(parse_input("2-4,-8").is_err());
+    }
+
+    #[test]
+    fn day_4_parse_input_rejects_backwards_areas() {
+        assert_eq!(
+            parse_input("2-4,6-8\n2-3,5-4").unwrap_err().to_string(),
+            "Invalid puzzle input: line 2, column 5: the area 5-4 ends before it starts"
         );
     }
 
     #[test]
     fn day_4_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 2);
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(2));
     }
 
     #[test]
     fn day_4_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 4);
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(4));
+    }
+
+    #[test]
+    fn day_4_generate() {
+        let input = generate(&mut Rng::new(4), 100);
+        assert_eq!(parse_input(&input).unwrap().len(), 100);
+        assert!(puzzle_1(&input).unwrap() <= puzzle_2(&input).unwrap());
+    }
+
+    #[test]
+    fn day_4_property_containment_implies_overlap() {
+        property::assert_holds("day_4 containment implies overlap", generate, |_, input| {
+            parse_input(input).unwrap().iter().all(|pair| {
+                let swapped = [pair[1].clone(), pair[0].clone()];
+                (!fully_contains(pair) || overlaps(pair))
+                    && fully_contains(pair) == fully_contains(&swapped)
+                    && overlaps(pair) == overlaps(&swapped)
+            })
+        });
     }
 }
@@ -1,7 +1,23 @@
-pub fn run() {
-    let input = include_str!("input/day_5.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+use crate::generate::Rng;
+use crate::parse::{
+    self, blank_lines, integer, lines, pair, preceded, rest_of_line, Input, ParseError,
+};
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => puzzle_1(input),
+        2 => puzzle_2(input),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // Supplies need to be unloaded from the ship.
@@ -30,21 +46,32 @@ struct Procedure {
     rearrangements: Vec<Rearrangement>,
 }
 
-fn parse_input(input: &str) -> Procedure {
-    let number_of_stacks = input
-        .lines()
-        .find_map(|line| {
+// The drawing of the stacks is read a column at a time, so it's read as whole lines first.
+fn parse_drawing(drawing: &[&str]) -> Result<Vec<Vec<char>>, ParseError> {
+    let stack_numbers = drawing
+        .iter()
+        .find(|line| {
             line.split_whitespace()
                 .last()
-                .and_then(|number| number.parse::<usize>().ok())
+                .is_some_and(|number| number.parse::<usize>().is_ok())
         })
-        .unwrap();
+        .ok_or_else(|| ParseError::new("the drawing has no row of stack numbers"))?;
+    let number_of_stacks = stack_numbers.split_whitespace().count();
+    if !stack_numbers
+        .split_whitespace()
+        .enumerate()
+        .all(|(idx, number)| number == (idx + 1).to_string())
+    {
+        return Err(ParseError::new(format!(
+            "'{stack_numbers}' doesn't number the stacks in order"
+        )));
+    }
 
     let mut stack_state = Vec::with_capacity(number_of_stacks);
     (0..number_of_stacks).for_each(|idx| {
         stack_state.push(
-            input
-                .lines()
+            drawing
+                .iter()
                 .take_while(|line| line.contains('['))
                 .filter_map(|line| {
                     line.chars()
@@ -55,73 +82,180 @@ fn parse_input(input: &str) -> Procedure {
         );
         stack_state[idx].reverse();
     });
+    Ok(stack_state)
+}
 
-    let rearrangements = input
-        .lines()
-        .skip_while(|line| !line.is_empty())
-        .skip(1)
-        .map(|rearrangements| {
-            let mut r = rearrangements
-                .split_whitespace()
-                .filter_map(|number| number.parse::<u64>().ok());
-            Rearrangement {
-                number: r.next().unwrap(),
-                origin: r.next().unwrap(),
-                destination: r.next().unwrap(),
+fn parse_input(input: &str) -> Result<Procedure, ParseError> {
+    parse::all(input, |input: &mut Input| {
+        let stack_state = parse_drawing(&lines(rest_of_line())(input)?)?;
+        blank_lines()(input)?;
+
+        let stack_range = 1..=stack_state.len() as u64;
+        let rearrangement = move |input: &mut Input| {
+            let start = *input;
+            let (number, (origin, destination)) = pair(
+                preceded("move ", integer()),
+                pair(preceded(" from ", integer()), preceded(" to ", integer())),
+            )(input)?;
+            let rearrangement = Rearrangement {
+                number,
+                origin,
+                destination,
+            };
+            if stack_range.contains(&rearrangement.origin)
+                && stack_range.contains(&rearrangement.destination)
+            {
+                Ok(rearrangement)
+            } else {
+                Err(start.error(format!(
+                    "{rearrangement:?} refers to a stack that isn't in the drawing"
+                )))
             }
+        };
+
+        Ok(Procedure {
+            stack_state,
+            rearrangements: lines(rearrangement)(input)?,
         })
-        .collect::<Vec<_>>();
+    })
+}
 
-    Procedure {
-        stack_state,
-        rearrangements,
-    }
+// Every crate moved has to be there to move, which only following the procedure shows. Both
+// cranes take the same crates from each stack, so either will do.
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    rearrange(input, false).map(drop)
 }
 
-//
-fn puzzle_1(input: &str) -> String {
-    let mut input = parse_input(input);
-    input.rearrangements.iter().for_each(|rearrangement| {
-        let len = input.stack_state[rearrangement.origin as usize - 1].len();
-        let moving_crates = &mut input.stack_state[rearrangement.origin as usize - 1]
-            .split_off(len - rearrangement.number as usize);
-        moving_crates.reverse();
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
+
+fn take_crates(
+    stack_state: &mut [Vec<char>],
+    rearrangement: &Rearrangement,
+) -> Result<Vec<char>, ParseError> {
+    let origin = &mut stack_state[rearrangement.origin as usize - 1];
+    let remaining = origin
+        .len()
+        .checked_sub(rearrangement.number as usize)
+        .ok_or_else(|| {
+            ParseError::new(format!(
+                "{rearrangement:?} moves more crates than there are on the stack"
+            ))
+        })?;
+    Ok(origin.split_off(remaining))
+}
+
+// Follow the rearrangement procedure, returning the final state of the stacks.
+// The CrateMover 9000 moves crates one at a time, so a group of crates ends up in reverse order;
+// the CrateMover 9001 moves them all at once.
+fn rearrange(input: &str, one_at_a_time: bool) -> Result<Vec<Vec<char>>, ParseError> {
+    let mut input = parse_input(input)?;
+    for rearrangement in &input.rearrangements {
+        let moving_crates = &mut take_crates(&mut input.stack_state, rearrangement)?;
+        if one_at_a_time {
+            moving_crates.reverse();
+        }
+        trace!(
+            Info,
+            "move",
+            number = rearrangement.number,
+            from = rearrangement.origin,
+            to = rearrangement.destination,
+            crates = moving_crates.iter().collect::<String>(),
+        );
         input.stack_state[rearrangement.destination as usize - 1].append(moving_crates)
-    });
-    input
-        .stack_state
+    }
+    Ok(input.stack_state)
+}
+
+fn top_crates(stack_state: Vec<Vec<char>>) -> String {
+    stack_state
         .into_iter()
         .map(|mut stack| stack.pop().unwrap_or(' '))
         .collect::<String>()
 }
 
 //
-fn puzzle_2(input: &str) -> String {
-    let mut input = parse_input(input);
-    input.rearrangements.iter().for_each(|rearrangement| {
-        let len = input.stack_state[rearrangement.origin as usize - 1].len();
-        let moving_crates = &mut input.stack_state[rearrangement.origin as usize - 1]
-            .split_off(len - rearrangement.number as usize);
-        input.stack_state[rearrangement.destination as usize - 1].append(moving_crates)
-    });
-    input
-        .stack_state
-        .into_iter()
-        .map(|mut stack| stack.pop().unwrap_or(' '))
-        .collect::<String>()
+fn puzzle_1(input: &str) -> Result<String, ParseError> {
+    Ok(top_crates(rearrange(input, true)?))
+}
+
+//
+fn puzzle_2(input: &str) -> Result<String, ParseError> {
+    Ok(top_crates(rearrange(input, false)?))
+}
+
+// Generate a drawing of up to nine stacks, followed by `size` rearrangements.
+// Every rearrangement only moves crates that are actually on the origin stack.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let number_of_stacks = rng.range(2..=9);
+    let mut stacks = (0..number_of_stacks)
+        .map(|idx| {
+            (0..rng.range(usize::from(idx == 0)..=8))
+                .map(|_| rng.letter(b'A'..=b'Z'))
+                .collect::<Vec<char>>()
+        })
+        .collect::<Vec<_>>();
+
+    let height = stacks.iter().map(Vec::len).max().unwrap();
+    let mut drawing = (0..height)
+        .rev()
+        .map(|level| {
+            let line = stacks
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(label) => format!("[{label}]"),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            line + "\n"
+        })
+        .collect::<String>();
+    drawing.push_str(
+        &(1..=number_of_stacks)
+            .map(|number| format!(" {number} "))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    drawing.push_str("\n\n");
+
+    for _ in 0..size.max(1) {
+        let non_empty = (0..number_of_stacks)
+            .filter(|&idx| !stacks[idx].is_empty())
+            .collect::<Vec<_>>();
+        let origin = *rng.choose(&non_empty);
+        let destination = (origin + rng.range(1..=number_of_stacks - 1)) % number_of_stacks;
+        let number = rng.range(1..=stacks[origin].len());
+
+        let len = stacks[origin].len();
+        let moving_crates = &mut stacks[origin].split_off(len - number);
+        stacks[destination].append(moving_crates);
+
+        drawing.push_str(&format!(
+            "move {} from {} to {}\n",
+            number,
+            origin + 1,
+            destination + 1
+        ));
+    }
+    drawing
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::property;
 
-    const TEST_INPUT: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n";
+    const TEST_INPUT: &str = EXAMPLE;
 
     #[test]
     fn day_5_parse_input() {
         assert_eq!(
             parse_input(TEST_INPUT),
-            Procedure {
+            Ok(Procedure {
                 rearrangements: vec![
                     Rearrangement {
                         number: 1,
@@ -145,17 +279,59 @@ mod tests {
                     }
                 ],
                 stack_state: vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']],
-            }
+            })
         );
     }
 
+    #[test]
+    fn day_5_parse_input_rejects_malformed() {
+        assert!(parse_input("[A]\n\nmove 1 from 1 to 1\n").is_err());
+        assert!(parse_input("[A]\n 1   3 \n\nmove 1 from 1 to 1\n").is_err());
+        assert!(parse_input("[A]\n 1 \n\nmove 1 from 1 to 2\n").is_err());
+        assert!(parse_input("[A]\n 1 \n\nmove 1 from 0 to 1\n").is_err());
+        assert!(parse_input("[A]\n 1 \n\nmove one from 1 to 1\n").is_err());
+        assert!(puzzle_1("[A]\n 1 \n\nmove 2 from 1 to 1\n").is_err());
+        assert!(check_input("[A]\n 1 \n\nmove 2 from 1 to 1\n").is_err());
+    }
+
     #[test]
     fn day_5_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), "CMZ");
+        assert_eq!(puzzle_1(TEST_INPUT), Ok("CMZ".to_string()));
     }
 
     #[test]
     fn day_5_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), "MCD");
+        assert_eq!(puzzle_2(TEST_INPUT), Ok("MCD".to_string()));
+    }
+
+    #[test]
+    fn day_5_generate() {
+        let input = generate(&mut Rng::new(5), 50);
+        let procedure = parse_input(&input).unwrap();
+        assert_eq!(procedure.rearrangements.len(), 50);
+        assert_eq!(puzzle_1(&input).unwrap().len(), procedure.stack_state.len());
+        assert_eq!(puzzle_2(&input).unwrap().len(), procedure.stack_state.len());
+    }
+
+    // Both cranes move the same number of crates between the same stacks, so neither loses a crate,
+    // and the stacks end up the same heights either way.
+    #[test]
+    fn day_5_property_crates_are_conserved() {
+        property::assert_holds("day_5 crates are conserved", generate, |_, input| {
+            let sorted = |mut crates: Vec<char>| {
+                crates.sort();
+                crates
+            };
+            let heights = |stack_state: &[Vec<char>]| {
+                stack_state.iter().map(Vec::len).collect::<Vec<usize>>()
+            };
+            let all_crates = sorted(parse_input(input).unwrap().stack_state.concat());
+            let crate_mover_9000 = rearrange(input, true).unwrap();
+            let crate_mover_9001 = rearrange(input, false).unwrap();
+
+            heights(&crate_mover_9000) == heights(&crate_mover_9001)
+                && sorted(crate_mover_9000.concat()) == all_crates
+                && sorted(crate_mover_9001.concat()) == all_crates
+        });
     }
 }
@@ -1,7 +1,40 @@
-pub fn run() {
-    let input = include_str!("input/day_6.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+use crate::explain::{Explanation, Witness};
+use crate::generate::Rng;
+use crate::params::{Parameter, Params};
+use crate::parse::ParseError;
+use std::collections::HashSet;
+use std::fmt;
+
+pub const PARAMETERS: &[Parameter] = &[
+    Parameter::new(
+        "packet_marker",
+        4,
+        1,
+        "unique characters in a start_of_packet marker",
+    ),
+    Parameter::new(
+        "message_marker",
+        14,
+        1,
+        "unique characters in a start_of_message marker",
+    ),
+];
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+
+pub fn run(input: &str, params: &Params) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1, params)?);
+    println!("{}", solve(input, 2, params)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize, params: &Params) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input, params.get("packet_marker"))?.to_string()),
+        2 => Ok(puzzle_2(input, params.get("message_marker"))?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // The elves and I leave for the star fruit grove.
@@ -13,55 +46,222 @@ pub fn run() {
 //
 // Identify the first 4 contiguous unique characters.
 
-fn parse_input(input: &str, window_size: usize) -> &str {
+fn parse_input(input: &str, window_size: usize) -> Result<&str, ParseError> {
+    if !input
+        .trim_end()
+        .bytes()
+        .all(|byte| byte.is_ascii_lowercase())
+    {
+        return Err(ParseError::new(
+            "the datastream should only contain lowercase letters",
+        ));
+    }
     input
         .as_bytes()
         .windows(window_size)
         .find(|seq| !(1..seq.len()).any(|i| seq[i..].contains(&seq[i - 1])))
-        .map(std::str::from_utf8)
-        .unwrap()
-        .unwrap()
+        .map(|seq| std::str::from_utf8(seq).unwrap())
+        .ok_or_else(|| {
+            ParseError::new(format!(
+                "there are no {window_size} unique characters in a row"
+            ))
+        })
+}
+
+// The datastream needs both markers, at the puzzle's own lengths.
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    let params = Params::defaults(PARAMETERS);
+    parse_input(input, params.get("packet_marker"))?;
+    parse_input(input, params.get("message_marker")).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input, 4)?))
 }
 
 // How many characters need to be processed before the first start_of_packet marker?
-fn puzzle_1(input: &str) -> usize {
-    let marker_length = 4;
-    input.find(parse_input(input, marker_length)).unwrap() + marker_length
+fn puzzle_1(input: &str, marker_length: usize) -> Result<usize, ParseError> {
+    Ok(input.find(parse_input(input, marker_length)?).unwrap() + marker_length)
 }
 
 // A 'start_of_message' marker is a sequence of 14 unique characters.
 // How many characters need to be processed before the first start_of_message marker is detected?
-fn puzzle_2(input: &str) -> usize {
-    let marker_length = 14;
-    input.find(parse_input(input, marker_length)).unwrap() + marker_length
+fn puzzle_2(input: &str, marker_length: usize) -> Result<usize, ParseError> {
+    Ok(input.find(parse_input(input, marker_length)?).unwrap() + marker_length)
+}
+
+// The first marker in the datastream, and how many characters have been processed once it has.
+#[derive(Debug, PartialEq)]
+struct Marker<'a> {
+    marker: &'a str,
+    end: usize,
+}
+
+fn marker(input: &str, marker_length: usize) -> Result<Marker<'_>, ParseError> {
+    let marker = parse_input(input, marker_length)?;
+    Ok(Marker {
+        marker,
+        end: input.find(marker).unwrap() + marker_length,
+    })
+}
+
+impl fmt::Display for Marker<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "the marker '{}' is complete after character {}",
+            self.marker, self.end
+        )
+    }
+}
+
+impl Witness for Marker<'_> {
+    type Answer = usize;
+
+    fn answer(&self) -> usize {
+        self.end
+    }
+
+    // The marker has to be where it's claimed to be, with no repeated characters, and every window
+    // of the same length before it must repeat at least one.
+    fn check(&self, input: &str) -> Result<(), String> {
+        let length = self.marker.len();
+        if length == 0 {
+            return Err("the marker is empty".to_string());
+        }
+        let unique = |window: &[u8]| window.iter().collect::<HashSet<_>>().len() == length;
+
+        let start = self.end.checked_sub(length);
+        if start.and_then(|start| input.get(start..self.end)) != Some(self.marker) {
+            return Err(format!(
+                "'{}' doesn't end at character {}",
+                self.marker, self.end
+            ));
+        }
+        if !unique(self.marker.as_bytes()) {
+            return Err(format!("'{}' repeats a character", self.marker));
+        }
+        match input.as_bytes()[..self.end - 1]
+            .windows(length)
+            .position(unique)
+        {
+            Some(earlier) => Err(format!(
+                "there's an earlier marker, complete after character {}",
+                earlier + length
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn explain(input: &str, params: &Params) -> Result<Vec<Explanation>, ParseError> {
+    let (packet, message) = (params.get("packet_marker"), params.get("message_marker"));
+    Ok(vec![
+        Explanation::new(1, puzzle_1(input, packet)?, &marker(input, packet)?, input),
+        Explanation::new(
+            2,
+            puzzle_2(input, message)?,
+            &marker(input, message)?,
+            input,
+        ),
+    ])
+}
+
+// Generate a datastream with around `size` characters of noise before the start_of_message
+// marker.
+// The noise only uses three letters, so no marker can be hidden inside it.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut letters = ('a'..='z').collect::<Vec<char>>();
+    rng.shuffle(&mut letters);
+    let (noise, marker) = letters.split_at(3);
+
+    let mut stream = (0..size).map(|_| *rng.choose(noise)).collect::<String>();
+    stream.extend(&marker[..14]);
+    stream.extend((0..size).map(|_| *rng.choose(&letters)));
+    stream.push('\n');
+    stream
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::property;
 
-    const TEST_INPUT_1: &str = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+    const TEST_INPUT_1: &str = EXAMPLE;
     const TEST_INPUT_2: &str = "bvwbjplbgvbhsrlpgdmjqwftvncz";
     const TEST_INPUT_3: &str = "nppdvjthqldpwncqszvftbrmjlhg";
 
     #[test]
     fn day_6_parse_input() {
-        assert_eq!(parse_input(TEST_INPUT_1, 4), "jpqm");
-        assert_eq!(parse_input(TEST_INPUT_2, 4), "vwbj");
-        assert_eq!(parse_input(TEST_INPUT_3, 4), "pdvj");
+        assert_eq!(parse_input(TEST_INPUT_1, 4), Ok("jpqm"));
+        assert_eq!(parse_input(TEST_INPUT_2, 4), Ok("vwbj"));
+        assert_eq!(parse_input(TEST_INPUT_3, 4), Ok("pdvj"));
+    }
+
+    #[test]
+    fn day_6_parse_input_rejects_malformed() {
+        assert!(parse_input("aaaaaa", 4).is_err());
+        assert!(parse_input("abcé", 4).is_err());
+        assert!(parse_input("abcd\nefgh", 4).is_err());
+        assert!(check_input("abcdabcd").is_err());
     }
 
     #[test]
     fn day_6_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT_1), 7);
-        assert_eq!(puzzle_1(TEST_INPUT_2), 5);
-        assert_eq!(puzzle_1(TEST_INPUT_3), 6);
+        assert_eq!(puzzle_1(TEST_INPUT_1, 4), Ok(7));
+        assert_eq!(puzzle_1(TEST_INPUT_2, 4), Ok(5));
+        assert_eq!(puzzle_1(TEST_INPUT_3, 4), Ok(6));
     }
 
     #[test]
     fn day_6_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT_1), 19);
-        assert_eq!(puzzle_2(TEST_INPUT_2), 23);
-        assert_eq!(puzzle_2(TEST_INPUT_3), 23);
+        assert_eq!(puzzle_2(TEST_INPUT_1, 14), Ok(19));
+        assert_eq!(puzzle_2(TEST_INPUT_2, 14), Ok(23));
+        assert_eq!(puzzle_2(TEST_INPUT_3, 14), Ok(23));
+    }
+
+    #[test]
+    fn day_6_explain() {
+        assert_eq!(
+            marker(TEST_INPUT_1, 4),
+            Ok(Marker {
+                marker: "jpqm",
+                end: 7
+            })
+        );
+        for input in [TEST_INPUT_1, TEST_INPUT_2, TEST_INPUT_3] {
+            assert!(explain(input, &Params::defaults(PARAMETERS))
+                .unwrap()
+                .iter()
+                .all(Explanation::is_verified));
+        }
+
+        let forged = |marker, end| Marker { marker, end }.check(TEST_INPUT_1);
+        assert_eq!(
+            forged("gblj", 11),
+            Err("there's an earlier marker, complete after character 7".to_string())
+        );
+        assert!(forged("jqjp", 5).is_err());
+        assert!(forged("jpqm", 8).is_err());
+        assert!(forged("jpqm", 2).is_err());
+    }
+
+    #[test]
+    fn day_6_generate() {
+        let input = generate(&mut Rng::new(6), 1000);
+        let packet = puzzle_1(&input, 4).unwrap();
+        let message = puzzle_2(&input, 14).unwrap();
+        assert!(packet <= 1004);
+        assert!(message <= 1014);
+        assert!(packet < message);
+    }
+
+    // A start-of-message marker starts with a start-of-packet marker.
+    #[test]
+    fn day_6_property_message_follows_packet() {
+        property::assert_holds("day_6 message follows packet", generate, |_, input| {
+            puzzle_1(input, 4).unwrap() + 10 <= puzzle_2(input, 14).unwrap()
+        });
     }
 }
@@ -1,9 +1,40 @@
+use crate::checked;
+use crate::explain::{Explanation, Witness};
+use crate::generate::Rng;
+use crate::params::{Parameter, Params};
+use crate::parse::{
+    self, either, integer, line, lines, literal, map, pair, preceded, rest_of_line, ParseError,
+    Parser,
+};
 use std::collections::HashMap;
+use std::fmt;
 
-pub fn run() {
-    let input = include_str!("input/day_7.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+pub const PARAMETERS: &[Parameter] = &[
+    Parameter::new(
+        "small",
+        100_000,
+        0,
+        "the largest total size of a small directory",
+    ),
+    Parameter::new("disk", 70_000_000, 0, "the total disk space"),
+    Parameter::new("update", 30_000_000, 0, "the unused space the update needs"),
+];
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n$ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst\n$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k";
+
+pub fn run(input: &str, params: &Params) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1, params)?);
+    println!("{}", solve(input, 2, params)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize, params: &Params) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input, params.get("small"))?.to_string()),
+        2 => Ok(puzzle_2(input, params.get("disk"), params.get("update"))?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // We are trying to clear storage space in a malfunctioning device to be able to install an update.
@@ -22,70 +53,443 @@ pub fn run() {
 // The file system of the device consists of a tree of plain data files and directories.
 // The outer-most directory is called '/'.
 
-fn parse_input(input: &str) -> HashMap<String, usize> {
-    let mut current_path = String::new();
-    let mut map = HashMap::new();
+// A line of the session: a command, or a line of what `ls` listed.
+enum Line<'a> {
+    Cd(&'a str),
+    Ls,
+    Dir(&'a str),
+    File(usize),
+}
+
+fn session_line<'a>() -> impl Parser<'a, Line<'a>> {
+    let cd = map(preceded("$ cd ", rest_of_line()), Line::Cd);
+    let ls = map(literal("$ ls"), |_| Line::Ls);
+    let dir = map(preceded("dir ", rest_of_line()), Line::Dir);
+    let file = map(
+        pair(integer(), preceded(" ", rest_of_line())),
+        |(size, _name)| Line::File(size),
+    );
+    either(either(cd, ls), either(dir, file))
+}
 
-    current_path.push_str("~/");
-    map.entry("~/".to_string()).or_insert(0);
-    input.split('$').skip(2).for_each(|segment| {
-        let (command, output) = segment.trim_start().split_once('\n').unwrap();
-        if command.contains("cd") {
-            if command.contains("..") {
+fn parse_input(input: &str) -> Result<HashMap<String, usize>, ParseError> {
+    let session = pair(line(literal("$ cd /")), lines(session_line()));
+    let session = parse::all(input, map(session, |(_, session)| session));
+    let mut current_path = String::from("~/");
+    let mut map: HashMap<String, usize> = HashMap::new();
+    map.insert(current_path.clone(), 0);
+
+    for line in session? {
+        match line {
+            Line::Cd("..") => {
                 if let Some((current, _)) = current_path.rsplit_once('/') {
                     current_path = current.to_string();
                     if current_path.is_empty() {
                         current_path.push('/')
                     }
                 }
-            } else {
+            }
+            Line::Cd(dir) => {
                 if current_path != "~/" {
                     current_path.push('/')
                 };
-
-                current_path.push_str(command.strip_prefix("cd ").unwrap());
+                current_path.push_str(dir);
             }
-        }
-        output.lines().for_each(|output| {
-            if output.starts_with("dir") {
+            Line::Ls => {}
+            Line::Dir(dir) => {
                 let mut dir_path = current_path.to_owned();
                 if dir_path != "~/" {
                     dir_path.push('/')
                 }
-                dir_path.push_str(output.strip_prefix("dir ").unwrap());
+                dir_path.push_str(dir);
                 map.insert(dir_path, 0);
-            } else {
+            }
+            Line::File(size) => {
                 for (dir, val) in &mut map {
-                    if current_path.starts_with(&**dir) {
-                        *val += output
-                            .split_whitespace()
-                            .next()
-                            .unwrap()
-                            .parse::<usize>()
-                            .unwrap();
+                    if is_within(&current_path, dir) {
+                        *val = val.checked_add(size).ok_or_else(|| {
+                            ParseError::new(format!("'{dir}' is too large to add up"))
+                        })?;
                     }
                 }
             }
-        });
-    });
-    map
+        }
+    }
+    Ok(map)
 }
 
-// What is the sum of the total sizes of the directories whose total sizes are at most 100,000
-fn puzzle_1(input: &str) -> usize {
-    parse_input(input)
-        .values()
-        .filter(|&&val| val <= 100_000)
-        .sum()
+// Whether `path` is the directory `dir`, or somewhere inside it. Paths are compared a whole name at
+// a time: `~/ab` isn't inside `~/a`.
+fn is_within(path: &str, dir: &str) -> bool {
+    dir == "~/"
+        || path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
 }
 
-// Total disk space = 70_000_000
-// Total needed = 30_000_000
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
+
+// What is the sum of the total sizes of the directories whose total sizes are at most `small`
+// (100,000)?
+fn puzzle_1(input: &str, small: usize) -> Result<usize, ParseError> {
+    Ok(checked::sum(
+        parse_input(input)?
+            .into_values()
+            .filter(|&val| val <= small),
+    ))
+}
+
+// Total disk space = `disk` (70_000_000)
+// Total needed = `update` (30_000_000)
 // We need to find a directory that will free enough space.
-fn puzzle_2(input: &str) -> usize {
-    let map = parse_input(input);
-    let req = 30_000_000 - (70_000_000 - map.get("~/").unwrap());
-    *map.values().filter(|&&val| val >= req).min().unwrap()
+fn puzzle_2(input: &str, disk: usize, update: usize) -> Result<usize, ParseError> {
+    let map = parse_input(input)?;
+    let req = space_required(map["~/"], disk, update)?;
+    map.values()
+        .filter(|&&val| val >= req)
+        .min()
+        .copied()
+        .ok_or_else(|| ParseError::new(format!("no directory is large enough to free {req}")))
+}
+
+// How much deleting a directory has to free for the update to fit -- which it can't do when the
+// files don't fit on the disk to begin with, and needn't when there's already room.
+fn space_required(used: usize, disk: usize, update: usize) -> Result<usize, ParseError> {
+    let free = disk
+        .checked_sub(used)
+        .ok_or_else(|| ParseError::new("the files don't fit on the disk"))?;
+    match update.checked_sub(free) {
+        Some(required) if required > 0 => Ok(required),
+        _ => Err(ParseError::new(format!(
+            "there's already room for the update, with {free} free"
+        ))),
+    }
+}
+
+// Directories are written as full paths from '/' in witnesses, e.g. '/a/e' rather than '~/a/e'.
+fn full_path(directory: &str) -> String {
+    match directory.strip_prefix('~') {
+        Some("/") | None => "/".to_string(),
+        Some(path) => path.to_string(),
+    }
+}
+
+// Walk the session again one line at a time, keeping the current path as a stack of directory
+// names, and add each file to every directory on the stack.
+fn tally_directories(input: &str) -> Result<HashMap<String, u128>, String> {
+    let full_path = |path: &[&str]| format!("/{}", path.join("/"));
+    let mut path = Vec::new();
+    let mut sizes = HashMap::from([("/".to_string(), 0)]);
+
+    for line in input.lines() {
+        if let Some(directory) = line.strip_prefix("$ cd ") {
+            match directory {
+                "/" => path.clear(),
+                ".." => drop(path.pop()),
+                directory => path.push(directory),
+            }
+            sizes.entry(full_path(&path)).or_insert(0);
+        } else if let Some(directory) = line.strip_prefix("dir ") {
+            path.push(directory);
+            sizes.entry(full_path(&path)).or_insert(0);
+            path.pop();
+        } else if line != "$ ls" {
+            let (size, _) = line
+                .split_once(' ')
+                .ok_or_else(|| format!("'{line}' isn't a file"))?;
+            let size = size.parse::<u128>().map_err(|err| err.to_string())?;
+            for depth in 0..=path.len() {
+                *sizes.get_mut(&full_path(&path[..depth])).unwrap() += size;
+            }
+        }
+    }
+    Ok(sizes)
+}
+
+// Every directory with a total size of at most `limit`, and its size.
+#[derive(Debug, PartialEq)]
+struct SmallDirectories {
+    directories: Vec<(String, usize)>,
+    limit: usize,
+}
+
+fn small_directories(input: &str, limit: usize) -> Result<SmallDirectories, ParseError> {
+    let mut directories = parse_input(input)?
+        .into_iter()
+        .filter(|&(_, size)| size <= limit)
+        .map(|(directory, size)| (full_path(&directory), size))
+        .collect::<Vec<_>>();
+    directories.sort();
+    Ok(SmallDirectories { directories, limit })
+}
+
+impl fmt::Display for SmallDirectories {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (directory, size) in &self.directories {
+            writeln!(f, "{directory} has a total size of {size}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Witness for SmallDirectories {
+    type Answer = usize;
+
+    fn answer(&self) -> usize {
+        checked::sum(self.directories.iter().map(|&(_, size)| size))
+    }
+
+    fn check(&self, input: &str) -> Result<(), String> {
+        let sizes = tally_directories(input)?;
+        for (directory, size) in &self.directories {
+            match sizes.get(directory) {
+                None => return Err(format!("there is no directory {directory}")),
+                Some(&tally) if tally != *size as u128 => {
+                    return Err(format!("{directory} has a total size of {tally}"))
+                }
+                Some(_) => {}
+            }
+        }
+        match sizes.iter().find(|&(directory, &size)| {
+            size <= self.limit as u128
+                && self.directories.iter().all(|(small, _)| small != directory)
+        }) {
+            Some((directory, size)) => Err(format!("{directory} is missing, at {size}")),
+            None => Ok(()),
+        }
+    }
+}
+
+// The smallest directory that frees up enough space for the update, given how much of the disk is
+// in use.
+#[derive(Debug, PartialEq)]
+struct Deletion {
+    directory: String,
+    size: usize,
+    used: usize,
+    required: usize,
+    disk: usize,
+    update: usize,
+}
+
+fn deletion(input: &str, disk: usize, update: usize) -> Result<Deletion, ParseError> {
+    let map = parse_input(input)?;
+    let used = map["~/"];
+    let required = space_required(used, disk, update)?;
+    let (directory, &size) = map
+        .iter()
+        .filter(|&(_, &size)| size >= required)
+        .min_by_key(|&(directory, &size)| (size, directory))
+        .ok_or_else(|| {
+            ParseError::new(format!("no directory is large enough to free {required}"))
+        })?;
+    Ok(Deletion {
+        directory: full_path(directory),
+        size,
+        used,
+        required,
+        disk,
+        update,
+    })
+}
+
+impl fmt::Display for Deletion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} is in use, so at least {} must be freed",
+            self.used, self.disk, self.required
+        )?;
+        writeln!(f, "deleting {} frees {}", self.directory, self.size)
+    }
+}
+
+impl Witness for Deletion {
+    type Answer = usize;
+
+    fn answer(&self) -> usize {
+        self.size
+    }
+
+    fn check(&self, input: &str) -> Result<(), String> {
+        let sizes = tally_directories(input)?;
+        let used = sizes["/"];
+        if used != self.used as u128 {
+            return Err(format!("{used} is in use"));
+        }
+        let required =
+            space_required(self.used, self.disk, self.update).map_err(|err| err.to_string())?;
+        if required != self.required {
+            return Err(format!("{required} must be freed"));
+        }
+        let required = required as u128;
+        match sizes.get(&self.directory) {
+            None => return Err(format!("there is no directory {}", self.directory)),
+            Some(&size) if size != self.size as u128 => {
+                return Err(format!("{} has a total size of {size}", self.directory))
+            }
+            Some(&size) if size < required => {
+                return Err(format!("deleting {} doesn't free enough", self.directory))
+            }
+            Some(_) => {}
+        }
+        match sizes
+            .iter()
+            .find(|&(_, &size)| size >= required && size < self.size as u128)
+        {
+            Some((directory, size)) => Err(format!("deleting {directory} frees only {size}")),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn explain(input: &str, params: &Params) -> Result<Vec<Explanation>, ParseError> {
+    let small = params.get("small");
+    let (disk, update) = (params.get("disk"), params.get("update"));
+    Ok(vec![
+        Explanation::new(
+            1,
+            puzzle_1(input, small)?,
+            &small_directories(input, small)?,
+            input,
+        ),
+        Explanation::new(
+            2,
+            puzzle_2(input, disk, update)?,
+            &deletion(input, disk, update)?,
+            input,
+        ),
+    ])
+}
+
+// Generate a terminal session that explores a tree of `size` directories.
+// The disk is always between 40_000_000 and 70_000_000 full, so there is always a directory that
+// can be deleted to make room for the update.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    struct Directory {
+        name: String,
+        files: Vec<(String, usize)>,
+        children: Vec<usize>,
+    }
+
+    fn name(rng: &mut Rng, length: usize) -> String {
+        (0..length).map(|_| rng.letter(b'a'..=b'z')).collect()
+    }
+
+    // Names vary in length, and some carry on from a sibling's, so that one directory's path can
+    // start with another's: `a` and `ab`.
+    fn unique_name(rng: &mut Rng, taken: &[String], extension: bool) -> String {
+        let stems = taken
+            .iter()
+            .filter(|name| !name.contains('.'))
+            .collect::<Vec<_>>();
+        loop {
+            let mut candidate = if !stems.is_empty() && rng.chance(25) {
+                format!("{}{}", rng.choose(&stems), name(rng, 1))
+            } else {
+                let length = rng.range(1..=6);
+                name(rng, length)
+            };
+            if extension {
+                candidate.push('.');
+                candidate.push_str(&name(rng, 3));
+            }
+            if !taken.contains(&candidate) {
+                return candidate;
+            }
+        }
+    }
+
+    fn explore(directories: &[Directory], idx: usize, rng: &mut Rng, session: &mut String) {
+        let directory = &directories[idx];
+        let mut listing = directory
+            .children
+            .iter()
+            .map(|&child| format!("dir {}\n", directories[child].name))
+            .chain(
+                directory
+                    .files
+                    .iter()
+                    .map(|(name, size)| format!("{size} {name}\n")),
+            )
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut listing);
+
+        session.push_str("$ ls\n");
+        listing.iter().for_each(|line| session.push_str(line));
+        for &child in &directory.children {
+            session.push_str(&format!("$ cd {}\n", directories[child].name));
+            explore(directories, child, rng, session);
+            session.push_str("$ cd ..\n");
+        }
+    }
+
+    let mut directories: Vec<Directory> = Vec::new();
+    for idx in 0..size.max(1) {
+        let mut taken = Vec::new();
+        let name = if idx == 0 {
+            "/".to_string()
+        } else {
+            let parent = rng.range(0..=idx - 1);
+            directories[parent].children.push(idx);
+            taken.extend(
+                directories[parent]
+                    .children
+                    .iter()
+                    .filter(|&&child| child != idx)
+                    .map(|&child| directories[child].name.clone()),
+            );
+            taken.extend(
+                directories[parent]
+                    .files
+                    .iter()
+                    .map(|(name, _)| name.clone()),
+            );
+            unique_name(rng, &taken, false)
+        };
+        let files = (0..rng.range(usize::from(idx == 0)..=4))
+            .map(|_| {
+                let extension = rng.chance(50);
+                let file = unique_name(rng, &taken, extension);
+                taken.push(file.clone());
+                (file, rng.range(1..=300_000))
+            })
+            .collect();
+        directories.push(Directory {
+            name,
+            files,
+            children: Vec::new(),
+        });
+    }
+
+    // Scale the file sizes so the whole disk lands in the target range.
+    let total: usize = directories
+        .iter()
+        .flat_map(|directory| directory.files.iter().map(|(_, size)| size))
+        .sum();
+    let target = rng.range(40_000_001..=69_000_000);
+    directories.iter_mut().for_each(|directory| {
+        directory
+            .files
+            .iter_mut()
+            .for_each(|(_, size)| *size = (*size * target / total).max(1));
+    });
+    let scaled: usize = directories
+        .iter()
+        .flat_map(|directory| directory.files.iter().map(|(_, size)| size))
+        .sum();
+    directories[0].files[0].1 += target.saturating_sub(scaled);
+
+    let mut session = "$ cd /\n".to_string();
+    explore(&directories, 0, rng, &mut session);
+    session
 }
 
 #[cfg(test)]
@@ -93,29 +497,191 @@ mod tests {
     use std::collections::HashMap;
 
     use super::*;
+    use crate::property;
 
-    const TEST_INPUT: &str = "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n$ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst\n$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k";
+    const TEST_INPUT: &str = EXAMPLE;
 
     #[test]
     fn day_7_parse_input() {
         assert_eq!(
             parse_input(TEST_INPUT),
-            HashMap::from([
+            Ok(HashMap::from([
                 ("~/".to_string(), 48381165),
                 ("~/a".to_string(), 94853),
                 ("~/a/e".to_string(), 584),
                 ("~/d".to_string(), 24933642)
-            ])
+            ]))
+        );
+    }
+
+    #[test]
+    fn day_7_parse_input_rejects_malformed() {
+        assert!(parse_input("$ ls\n1 a\n").is_err());
+        assert!(parse_input("$ cd /\n$ ls\nbig a\n").is_err());
+        assert!(parse_input("$ cd /\n$ ls\n1\n").is_err());
+        assert!(parse_input("$ cd /\n$ cdd\n").is_err());
+        assert!(parse_input("$ cd /\n$ ls\n18446744073709551615 a\n1 b\n").is_err());
+        assert!(parse_input("$ cd /\n$ cd a").is_ok());
+    }
+
+    // `~/ab` starts with `~/a`, but it isn't inside it.
+    #[test]
+    fn day_7_parse_input_siblings_sharing_a_prefix() {
+        let input =
+            "$ cd /\n$ ls\ndir a\ndir ab\n$ cd a\n$ ls\n1 x\n$ cd ..\n$ cd ab\n$ ls\n10 y\n";
+        assert_eq!(
+            parse_input(input),
+            Ok(HashMap::from([
+                ("~/".to_string(), 11),
+                ("~/a".to_string(), 1),
+                ("~/ab".to_string(), 10)
+            ]))
         );
     }
 
     #[test]
     fn day_7_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 95437);
+        assert_eq!(puzzle_1(TEST_INPUT, 100_000), Ok(95437));
     }
 
     #[test]
     fn day_7_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 24933642);
+        assert_eq!(puzzle_2(TEST_INPUT, 70_000_000, 30_000_000), Ok(24933642));
+    }
+
+    #[test]
+    fn day_7_puzzle_2_rejects_impossible_updates() {
+        fn error<T>(message: &str) -> Result<T, ParseError> {
+            Err(ParseError::new(message))
+        }
+        assert_eq!(
+            puzzle_2(TEST_INPUT, 40_000_000, 30_000_000),
+            error("the files don't fit on the disk")
+        );
+        assert_eq!(
+            puzzle_2(TEST_INPUT, 70_000_000, 80_000_000),
+            error("no directory is large enough to free 58381165")
+        );
+        assert_eq!(
+            puzzle_2(TEST_INPUT, 70_000_000, 20_000_000),
+            error("there's already room for the update, with 21618835 free")
+        );
+        assert_eq!(
+            deletion(TEST_INPUT, 70_000_000, 80_000_000),
+            error("no directory is large enough to free 58381165")
+        );
+    }
+
+    #[test]
+    fn day_7_explain() {
+        assert_eq!(
+            deletion(TEST_INPUT, 70_000_000, 30_000_000),
+            Ok(Deletion {
+                directory: "/d".to_string(),
+                size: 24933642,
+                used: 48381165,
+                required: 8381165,
+                disk: 70_000_000,
+                update: 30_000_000
+            })
+        );
+        assert!(explain(TEST_INPUT, &Params::defaults(PARAMETERS))
+            .unwrap()
+            .iter()
+            .all(Explanation::is_verified));
+
+        let small = |directories: &[(&str, usize)]| {
+            SmallDirectories {
+                directories: directories
+                    .iter()
+                    .map(|&(directory, size)| (directory.to_string(), size))
+                    .collect(),
+                limit: 100_000,
+            }
+            .check(TEST_INPUT)
+        };
+        assert_eq!(
+            small(&[("/a", 94853)]),
+            Err("/a/e is missing, at 584".to_string())
+        );
+        assert!(small(&[("/a", 94853), ("/a/e", 585)]).is_err());
+
+        let delete = |directory: &str, size| {
+            Deletion {
+                directory: directory.to_string(),
+                size,
+                used: 48381165,
+                required: 8381165,
+                disk: 70_000_000,
+                update: 30_000_000,
+            }
+            .check(TEST_INPUT)
+        };
+        assert_eq!(
+            delete("/", 48381165),
+            Err("deleting /d frees only 24933642".to_string())
+        );
+        assert!(delete("/a", 94853).is_err());
+        assert!(delete("/b", 24933642).is_err());
+    }
+
+    #[test]
+    fn day_7_generate() {
+        let input = generate(&mut Rng::new(7), 40);
+        let map = parse_input(&input).unwrap();
+        assert_eq!(map.len(), 40);
+        assert!((40_000_001..70_000_000).contains(map.get("~/").unwrap()));
+        assert!(puzzle_2(&input, 70_000_000, 30_000_000).unwrap() <= *map.get("~/").unwrap());
+    }
+
+    #[test]
+    fn day_7_property_directories_contain_their_subdirectories() {
+        property::assert_holds("day_7 directory sizes", generate, |_, input| {
+            let map = parse_input(input).unwrap();
+            let inside = |directory: &str, parent: &str| {
+                directory != parent
+                    && (parent == "~/" || directory.starts_with(&format!("{parent}/")))
+            };
+            map.iter().all(|(directory, size)| {
+                map.iter()
+                    .all(|(parent, total)| !inside(directory, parent) || size <= total)
+            }) && 70_000_000 - map["~/"] + puzzle_2(input, 70_000_000, 30_000_000).unwrap()
+                >= 30_000_000
+        });
+    }
+
+    // A directory's total is the files listed in it, and the totals of the directories in it --
+    // and nothing else, however alike their names are.
+    #[test]
+    fn day_7_property_totals_add_up() {
+        property::assert_holds("day_7 totals add up", generate, |_, input| {
+            let totals = parse_input(input).unwrap();
+            let key = |path: &[&str]| format!("~/{}", path.join("/"));
+            let parent = |dir: &str| match dir.rsplit_once('/') {
+                Some(("~", _)) => "~/".to_string(),
+                Some((parent, _)) => parent.to_string(),
+                None => unreachable!("every path starts at ~/"),
+            };
+
+            let mut own = HashMap::new();
+            let mut path = Vec::new();
+            for line in input.lines().skip(1) {
+                if line == "$ cd .." {
+                    path.pop();
+                } else if let Some(dir) = line.strip_prefix("$ cd ") {
+                    path.push(dir);
+                } else if let Ok(size) = line.split(' ').next().unwrap().parse::<usize>() {
+                    *own.entry(key(&path)).or_default() += size;
+                }
+            }
+            totals.iter().all(|(dir, &total)| {
+                let children = totals
+                    .iter()
+                    .filter(|&(child, _)| child != "~/" && parent(child) == *dir)
+                    .map(|(_, size)| size)
+                    .sum::<usize>();
+                total == own.get(dir).copied().unwrap_or(0) + children
+            })
+        });
     }
 }
@@ -1,7 +1,23 @@
-pub fn run() {
-    let input = include_str!("input/day_8.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+use crate::generate::Rng;
+use crate::geometry::{Direction, Point};
+use crate::grid::{Grid, Position};
+use crate::parse::ParseError;
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "30373\n25512\n65332\n33549\n35390";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
+}
+
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
 // We come across a patch of trees planted in a grid, as part of a reforestation effort.
@@ -14,24 +30,68 @@ pub fn run() {
 // A tree is VISIBLE if all other trees between it and an edge of the grid are shorter than it.
 // All trees on the edge of the grid are visible.
 
-fn parse_input(input: &str) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
-    let rows = input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).unwrap() as usize)
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<Vec<usize>>>();
-    let size = rows.len();
-    let cols = (0..size)
-        .map(|i| rows.iter().map(|col| col[i]).collect::<Vec<usize>>())
-        .collect::<Vec<Vec<usize>>>();
+// The heights of the trees.
+type Forest = Grid<usize>;
+
+fn parse_input(input: &str) -> Result<Forest, ParseError> {
+    let forest = Grid::parse(input, |c| {
+        c.to_digit(10)
+            .map(|height| height as usize)
+            .ok_or_else(|| ParseError::new(format!("'{c}' is not a tree height")))
+    })?;
+    if forest.width() != forest.height() {
+        return Err(ParseError::new(
+            "the trees should be planted in a square grid",
+        ));
+    }
+    Ok(forest)
+}
 
-    (rows, cols)
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
+
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
 }
 
 // How many trees are visible?
+// Walk along each row and column from both ends, keeping track of the tallest tree so far: only
+// a tree taller than all of those can be seen from that edge.
+fn visible_from_edges(line: &[usize]) -> Vec<bool> {
+    let mut visible = vec![false; line.len()];
+    let forwards = (0..line.len()).collect::<Vec<_>>();
+    let backwards = forwards.iter().rev().copied().collect::<Vec<_>>();
+
+    for walk in [forwards, backwards] {
+        let mut tallest = None;
+        for idx in walk {
+            if tallest.is_none_or(|tallest| line[idx] > tallest) {
+                visible[idx] = true;
+                tallest = Some(line[idx]);
+            }
+        }
+    }
+    visible
+}
+
+fn puzzle_1(input: &str) -> Result<usize, ParseError> {
+    let forest = parse_input(input)?;
+    let from_rows = forest.rows().map(visible_from_edges).collect::<Vec<_>>();
+    let from_cols = forest
+        .columns()
+        .map(|col| visible_from_edges(&col.copied().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+
+    Ok(forest
+        .positions()
+        .filter(|&Point { x, y }| from_rows[y][x] || from_cols[x][y])
+        .count())
+}
+
+// The original, tree-by-tree check -- kept as a reference for `puzzle_1`.
+#[cfg(test)]
 fn is_visible_in_line(x: usize, row: &[usize]) -> bool {
     if row.iter().take(x).any(|r| r >= &row[x])
         && row
@@ -46,6 +106,7 @@ fn is_visible_in_line(x: usize, row: &[usize]) -> bool {
     true
 }
 
+#[cfg(test)]
 fn is_visible((x, y): (usize, usize), row: Vec<usize>, col: Vec<usize>) -> bool {
     if x == 0 || y == 0 || x == row.len() - 1 || y == col.len() - 1 {
         true
@@ -54,24 +115,20 @@ fn is_visible((x, y): (usize, usize), row: Vec<usize>, col: Vec<usize>) -> bool
     }
 }
 
-fn puzzle_1(input: &str) -> usize {
-    let (rows, cols) = parse_input(input);
-    let size = rows.len();
-
-    (0..size)
-        .map(|x| {
-            (0..size)
-                .map(|y| {
-                    is_visible(
-                        (x, y),
-                        rows.to_owned()[x].to_owned(),
-                        cols.to_owned()[y].to_owned(),
-                    )
-                })
-                .filter(|&visible| visible)
-                .count()
+#[cfg(test)]
+fn puzzle_1_reference(input: &str) -> Result<usize, ParseError> {
+    let forest = parse_input(input)?;
+
+    Ok(forest
+        .positions()
+        .filter(|&Point { x: col, y: row }| {
+            is_visible(
+                (row, col),
+                forest.rows().nth(row).unwrap().to_vec(),
+                forest.column(col).copied().collect(),
+            )
         })
-        .sum()
+        .count())
 }
 
 // Now we need to find the best avaliable spot to place the tree house.
@@ -82,89 +139,117 @@ fn puzzle_1(input: &str) -> usize {
 // A tree's scenic score is each of its viewing distances multiplied together
 //
 // What is the highest scenic score of all trees?
-fn distance(idx: usize, direction: &[usize]) -> usize {
-    direction
-        .iter()
-        .skip(idx + 1)
-        .position(|r| r >= &direction[idx])
-        .map(|r| r + 1)
-        .unwrap_or_else(|| direction[idx + 1..].len())
-        * direction
-            .iter()
-            .rev()
-            .skip(direction.len() - idx)
-            .position(|r| r >= &direction[idx])
-            .map(|r| r + 1)
-            .unwrap_or_else(|| direction[..idx].len())
+fn viewing_distance(forest: &Forest, tree: Position, direction: Direction) -> usize {
+    let mut distance = 0;
+    for position in forest.ray(tree, direction.vector()) {
+        distance += 1;
+        if forest[position] >= forest[tree] {
+            break;
+        }
+    }
+    distance
 }
 
-fn scenic_score((x, y): (usize, usize), row: Vec<usize>, col: Vec<usize>) -> usize {
-    if x == 0 || y == 0 || x == row.len() - 1 || y == col.len() - 1 {
-        0
-    } else {
-        distance(x, &col) * distance(y, &row)
-    }
+// A tree on the edge sees nothing in one direction, so its score is always 0.
+fn scenic_score(forest: &Forest, tree: Position) -> usize {
+    Direction::ALL
+        .into_iter()
+        .map(|direction| viewing_distance(forest, tree, direction))
+        .product()
 }
 
-fn puzzle_2(input: &str) -> usize {
-    let (rows, cols) = parse_input(input);
-    let size = rows.len();
-
-    *(0..size)
-        .map(|x| {
-            (0..size)
-                .map(|y| {
-                    scenic_score(
-                        (x, y),
-                        rows.to_owned()[x].to_owned(),
-                        cols.to_owned()[y].to_owned(),
-                    )
-                })
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<Vec<usize>>>()
-        .iter()
-        .flatten()
+fn puzzle_2(input: &str) -> Result<usize, ParseError> {
+    let forest = parse_input(input)?;
+    Ok(forest
+        .positions()
+        .map(|tree| scenic_score(&forest, tree))
         .max()
-        .unwrap()
+        .unwrap())
+}
+
+// Generate a `size` x `size` grid of tree heights.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let mut row = (0..size.max(1))
+                .map(|_| rng.letter(b'0'..=b'9'))
+                .collect::<String>();
+            row.push('\n');
+            row
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::differential::{self, Differential};
+    use crate::property;
+
+    const TEST_INPUT: &str = EXAMPLE;
 
-    const TEST_INPUT: &str = "30373\n25512\n65332\n33549\n35390";
+    const PUZZLE_1: Differential = Differential {
+        name: "day_8::puzzle_1",
+        reference: |input| format!("{:?}", puzzle_1_reference(input)),
+        optimised: |input| format!("{:?}", puzzle_1(input)),
+    };
 
     #[test]
     fn day_8_parse_input() {
+        let forest = parse_input(TEST_INPUT).unwrap();
+        assert_eq!(forest.rows().nth(2), Some(&[6, 5, 3, 3, 2][..]));
         assert_eq!(
-            parse_input(TEST_INPUT),
-            (
-                vec![
-                    vec![3, 0, 3, 7, 3],
-                    vec![2, 5, 5, 1, 2],
-                    vec![6, 5, 3, 3, 2],
-                    vec![3, 3, 5, 4, 9],
-                    vec![3, 5, 3, 9, 0]
-                ],
-                vec![
-                    vec![3, 2, 6, 3, 3],
-                    vec![0, 5, 5, 3, 5],
-                    vec![3, 5, 3, 5, 3],
-                    vec![7, 1, 3, 4, 9],
-                    vec![3, 2, 2, 9, 0]
-                ]
-            )
+            forest.column(3).copied().collect::<Vec<_>>(),
+            [7, 1, 3, 4, 9]
         );
+        assert_eq!(forest.to_string(), TEST_INPUT.to_string() + "\n");
+    }
+
+    #[test]
+    fn day_8_parse_input_rejects_malformed() {
+        assert!(parse_input("").is_err());
+        assert!(parse_input("303\n25").is_err());
+        assert!(parse_input("3a\n25").is_err());
+        assert!(parse_input("303\n255").is_err());
     }
 
     #[test]
     fn day_8_puzzle_1() {
-        assert_eq!(puzzle_1(TEST_INPUT), 21);
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(21));
+    }
+
+    #[test]
+    fn day_8_puzzle_1_differential() {
+        differential::assert_agree(&PUZZLE_1, &[TEST_INPUT], generate);
     }
 
     #[test]
     fn day_8_puzzle_2() {
-        assert_eq!(puzzle_2(TEST_INPUT), 8);
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(8));
+        let forest = parse_input(TEST_INPUT).unwrap();
+        assert_eq!(scenic_score(&forest, Point::new(2, 1)), 4);
+        assert_eq!(scenic_score(&forest, Point::new(0, 2)), 0);
+    }
+
+    #[test]
+    fn day_8_generate() {
+        let input = generate(&mut Rng::new(8), 30);
+        assert_eq!(parse_input(&input).unwrap().height(), 30);
+        assert!(puzzle_1(&input).unwrap() >= 4 * 29);
+    }
+
+    // Looking at the forest from the side, rather than from above, changes nothing.
+    #[test]
+    fn day_8_property_transposition() {
+        property::assert_holds("day_8 transposition", generate, |_, input| {
+            let forest = parse_input(input).unwrap();
+            let transposed = forest.transpose().to_string();
+            let size = forest.width();
+            let visible = puzzle_1(input).unwrap();
+            visible == puzzle_1(&transposed).unwrap()
+                && puzzle_2(input) == puzzle_2(&transposed)
+                && (4 * (size - 1)).max(1) <= visible
+                && visible <= size * size
+        });
     }
 }
@@ -1,93 +1,344 @@
+use crate::generate::Rng;
+use crate::geometry::{self, BoundingBox, Direction};
+use crate::parse::{self, ParseError};
+use crate::simulation::Simulation;
+use crate::visualize::{Frame, Rgb, Visualize};
 use std::collections::HashSet;
 
-pub fn run() {
-    let input = include_str!("input/day_9.txt");
-    println!("{}", puzzle_1(input));
-    println!("{}", puzzle_2(input));
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub const EXAMPLE: &str = "R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2";
+
+pub fn run(input: &str) -> Result<(), ParseError> {
+    println!("{}", solve(input, 1)?);
+    println!("{}", solve(input, 2)?);
+    Ok(())
 }
 
-#[derive(Clone, Copy, Hash, Eq, PartialEq)]
-struct Point {
-    x: i32,
-    y: i32,
+pub fn solve(input: &str, part: usize) -> Result<String, ParseError> {
+    match part {
+        1 => Ok(puzzle_1(input)?.to_string()),
+        2 => Ok(puzzle_2(input)?.to_string()),
+        _ => unreachable!("there is no part {part}"),
+    }
 }
 
-fn parse_input(input: &str) -> Vec<(&str, usize)> {
+type Point = geometry::Point<i32>;
+
+// The head can never travel further than the sum of its motions, so keeping that sum within an
+// i32 keeps every knot's coordinates within one too.
+fn parse_input(input: &str) -> Result<Vec<(Direction, usize)>, ParseError> {
+    let mut total_distance = 0i32;
     input
         .lines()
         .map(|line| {
-            let (direction, distance) = line.split_once(' ').unwrap();
-            (direction, distance.parse::<usize>().unwrap())
+            let (direction, distance) = parse::split_once(line, " ")?;
+            let direction = direction.parse::<Direction>()?;
+            let distance = parse::number::<i32>(distance)?;
+            total_distance = total_distance
+                .checked_add(distance)
+                .filter(|_| distance >= 0)
+                .ok_or_else(|| ParseError::new(format!("'{line}' moves the head too far")))?;
+            Ok((direction, distance as usize))
         })
-        .collect::<Vec<(&str, usize)>>()
+        .collect()
 }
 
-fn puzzle_1(input: &str) -> usize {
-    let mut rope = vec![Point { x: 0, y: 0 }; 2];
+pub fn check_input(input: &str) -> Result<(), ParseError> {
+    parse_input(input).map(drop)
+}
 
-    let mut visited_set = HashSet::new();
-    visited_set.insert(rope[1]);
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub fn preview(input: &str) -> Result<String, ParseError> {
+    Ok(format!("{:#?}", parse_input(input)?))
+}
 
-    for (direction, distance) in parse_input(input) {
-        for _ in 0..distance {
-            let mut head = rope[0];
-            let mut tail = rope[1];
+// Move the head of the rope one step, and let every other knot follow the one in front of it.
+// A knot only moves once it is no longer touching the knot in front, and then moves one step
+// (diagonally, if need be) towards it.
+fn pull(rope: &mut [Point], direction: Direction) {
+    rope[0] += direction.vector();
 
-            match direction {
-                "R" => head.x += 1,
-                "L" => head.x -= 1,
-                "U" => head.y += 1,
-                "D" => head.y -= 1,
-                _ => unreachable!(),
-            };
+    for idx in 0..(rope.len() - 1) {
+        let head = rope[idx];
+        let tail = &mut rope[idx + 1];
 
-            if (head.y - tail.y).abs() > 1 || (head.x - tail.x).abs() > 1 {
-                tail.x += (head.x - tail.x).signum();
-                tail.y += (head.y - tail.y).signum();
-            }
+        if head.chebyshev_distance(*tail) > 1 {
+            *tail += (head - *tail).signum();
+        }
+    }
+}
 
-            rope[0] = head;
-            rope[1] = tail;
+// The rope, as it follows the motions of its head one step at a time.
+#[derive(Clone)]
+struct Rope {
+    knots: Vec<Point>,
+    motions: Vec<(Direction, usize)>,
+    motion: usize,
+    moved: usize,
+    visited: HashSet<Point>,
+    // The corners of the area the head will cover -- every other knot stays within it too.
+    bounds: BoundingBox<i32>,
+}
+
+#[derive(Debug, PartialEq)]
+struct RopeSnapshot {
+    knots: Vec<Point>,
+    visited: HashSet<Point>,
+}
+
+impl Rope {
+    fn new(input: &str, knots: usize) -> Result<Self, ParseError> {
+        let knots = vec![Point::default(); knots];
+        let motions = parse_input(input)?
+            .into_iter()
+            .filter(|&(_, distance)| distance > 0)
+            .collect::<Vec<_>>();
 
-            visited_set.insert(rope[1]);
+        let mut head = knots[0];
+        let mut bounds = BoundingBox::around(head);
+        for &(direction, distance) in &motions {
+            head += direction.vector() * distance as i32;
+            bounds.include(head);
         }
+
+        Ok(Rope {
+            visited: HashSet::from([knots[knots.len() - 1]]),
+            knots,
+            motions,
+            motion: 0,
+            moved: 0,
+            bounds,
+        })
     }
-    visited_set.len()
 }
 
-fn puzzle_2(input: &str) -> usize {
-    let mut rope = vec![Point { x: 0, y: 0 }; 10];
+impl Simulation for Rope {
+    type Snapshot = RopeSnapshot;
+
+    fn step(&mut self) {
+        let (direction, distance) = self.motions[self.motion];
+        if self.moved == 0 {
+            trace!(Info, "motion", direction = direction, distance = distance);
+        }
+        pull(&mut self.knots, direction);
+        let tail = self.knots[self.knots.len() - 1];
+        if self.visited.insert(tail) {
+            trace!(Debug, "visit", x = tail.x, y = tail.y);
+        }
 
-    let mut visited_set = HashSet::new();
-    visited_set.insert(rope[9]);
+        self.moved += 1;
+        if self.moved == distance {
+            self.motion += 1;
+            self.moved = 0;
+        }
+    }
 
-    for (direction, distance) in parse_input(input) {
-        for _ in 0..distance {
-            for idx in 0..(rope.len() - 1) {
-                let mut head = rope[idx];
-                let mut tail = rope[idx + 1];
+    fn is_done(&self) -> bool {
+        self.motion == self.motions.len()
+    }
 
-                if idx == 0 {
-                    match direction {
-                        "R" => head.x += 1,
-                        "L" => head.x -= 1,
-                        "U" => head.y += 1,
-                        "D" => head.y -= 1,
-                        _ => unreachable!(),
-                    }
-                };
+    fn snapshot(&self) -> RopeSnapshot {
+        RopeSnapshot {
+            knots: self.knots.clone(),
+            visited: self.visited.clone(),
+        }
+    }
 
-                if (head.y - tail.y).abs() > 1 || (head.x - tail.x).abs() > 1 {
-                    tail.x += (head.x - tail.x).signum();
-                    tail.y += (head.y - tail.y).signum();
-                }
+    // Like the puzzle's diagrams: the head is 'H', the other knots are numbered (or 'T', for a rope
+    // with only a head and a tail), 's' is the start, and '#' marks where the tail has been.
+    fn render(&self) -> String {
+        let bounds =
+            BoundingBox::from_points(self.knots.iter().chain(&self.visited).copied()).unwrap();
 
-                rope[idx] = head;
-                rope[idx + 1] = tail;
-            }
+        (bounds.min.y..=bounds.max.y)
+            .map(|y| {
+                let mut row = (bounds.min.x..=bounds.max.x)
+                    .map(|x| {
+                        let point = Point::new(x, y);
+                        match self.knots.iter().position(|&knot| knot == point) {
+                            Some(0) => 'H',
+                            Some(_) if self.knots.len() == 2 => 'T',
+                            Some(idx) => char::from_digit(idx as u32, 36).unwrap_or('*'),
+                            None if point == Point::default() => 's',
+                            None if self.visited.contains(&point) => '#',
+                            None => '.',
+                        }
+                    })
+                    .collect::<String>();
+                row.push('\n');
+                row
+            })
+            .collect()
+    }
+}
+
+// The whole area the head covers, with the start in green, the tail's trail in grey, and the
+// knots getting brighter towards the head.
+impl Visualize for Rope {
+    const PALETTE: &'static [Rgb] = &[
+        [16, 16, 32],
+        [90, 90, 110],
+        [60, 200, 90],
+        [150, 60, 40],
+        [170, 75, 45],
+        [190, 90, 50],
+        [205, 105, 55],
+        [215, 120, 60],
+        [225, 140, 65],
+        [235, 160, 70],
+        [240, 185, 75],
+        [245, 210, 80],
+        [255, 240, 120],
+    ];
 
-            visited_set.insert(rope[9]);
+    fn frame(&self) -> Frame {
+        let bounds = self.bounds;
+        let mut frame = Frame::new(bounds.width() as usize, bounds.height() as usize);
+        let mut draw = |point: &Point, colour: u8| {
+            let offset = *point - bounds.min;
+            frame.set(offset.x as usize, offset.y as usize, colour)
+        };
+
+        self.visited.iter().for_each(|point| draw(point, 1));
+        draw(&Point::default(), 2);
+        for (idx, knot) in self.knots.iter().enumerate().rev() {
+            let brightness = 12 - (idx * 9 / (self.knots.len() - 1)) as u8;
+            draw(knot, brightness);
         }
+        frame
+    }
+}
+
+// Puzzle 1 follows a rope with a head and a tail; puzzle 2, a rope of ten knots.
+pub fn simulation(input: &str, part: usize) -> Result<impl Visualize + Clone, ParseError> {
+    Rope::new(input, if part == 1 { 2 } else { 10 })
+}
+
+// How many positions does the tail of the rope visit at least once?
+fn tail_visits(input: &str, knots: usize) -> Result<usize, ParseError> {
+    let mut rope = Rope::new(input, knots)?;
+    rope.run();
+    Ok(rope.snapshot().visited.len())
+}
+
+fn puzzle_1(input: &str) -> Result<usize, ParseError> {
+    tail_visits(input, 2)
+}
+
+fn puzzle_2(input: &str) -> Result<usize, ParseError> {
+    tail_visits(input, 10)
+}
+
+// Generate `size` motions of the head of the rope.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            format!(
+                "{} {}\n",
+                rng.choose(&["U", "D", "L", "R"]),
+                rng.range(1..=20)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::{self, Filter};
+    use crate::{property, snapshot};
+
+    const TEST_INPUT: &str = EXAMPLE;
+    const TEST_INPUT_2: &str = "R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20";
+
+    #[test]
+    fn day_9_parse_input() {
+        assert_eq!(
+            parse_input("R 4\nU 4"),
+            Ok(vec![(Direction::Right, 4), (Direction::Up, 4)])
+        );
+    }
+
+    #[test]
+    fn day_9_parse_input_rejects_malformed() {
+        assert!(parse_input("R").is_err());
+        assert!(parse_input("X 4").is_err());
+        assert!(parse_input("R -4").is_err());
+        assert!(parse_input("R 2147483647\nL 1").is_err());
+    }
+
+    #[test]
+    fn day_9_generate() {
+        let input = generate(&mut Rng::new(9), 200);
+        assert_eq!(parse_input(&input).unwrap().len(), 200);
+        assert!(puzzle_1(&input).unwrap() > 1);
+        assert!(puzzle_2(&input).unwrap() >= 1);
+    }
+
+    #[test]
+    fn day_9_puzzle_1() {
+        assert_eq!(puzzle_1(TEST_INPUT), Ok(13));
+    }
+
+    #[test]
+    fn day_9_puzzle_2() {
+        assert_eq!(puzzle_2(TEST_INPUT), Ok(1));
+        assert_eq!(puzzle_2(TEST_INPUT_2), Ok(36));
+    }
+
+    #[test]
+    fn day_9_property_knots_stay_adjacent() {
+        property::assert_holds("day_9 knots stay adjacent", generate, |_, input| {
+            let rope = &mut [Point::default(); 10];
+            parse_input(input)
+                .unwrap()
+                .iter()
+                .all(|&(direction, distance)| {
+                    (0..distance).all(|_| {
+                        pull(rope, direction);
+                        rope.windows(2)
+                            .all(|knots| knots[0].chebyshev_distance(knots[1]) <= 1)
+                    })
+                })
+        });
+    }
+
+    #[test]
+    fn day_9_simulation() {
+        let mut rope = Rope::new(TEST_INPUT, 2).unwrap();
+        (0..4).for_each(|_| rope.step());
+        assert_eq!(rope.render(), "s##TH\n");
+        assert_eq!(rope.run(), 20);
+        assert_eq!(rope.render(), "..##.\n...##\n.TH##\n....#\ns###.\n");
+        assert_eq!(rope.snapshot().visited.len(), 13);
+
+        let mut rope = Rope::new(TEST_INPUT_2, 10).unwrap();
+        rope.run();
+        snapshot::assert_matches("day_9_rope", &rope.render());
+    }
+
+    #[test]
+    fn day_9_trace() {
+        let events = trace::capture(Filter::new(2, "").unwrap(), || {
+            Rope::new(TEST_INPUT, 2).unwrap().run();
+        });
+        snapshot::assert_matches("day_9_trace", &events);
+    }
+
+    #[test]
+    fn day_9_visualize() {
+        let mut rope = Rope::new(TEST_INPUT, 10).unwrap();
+        assert_eq!(
+            rope.bounds,
+            BoundingBox::new(Point::new(0, -4), Point::new(5, 0))
+        );
+        (0..4).for_each(|_| rope.step());
+        let frame = rope.frame();
+        assert_eq!((frame.width(), frame.height()), (6, 5));
+        assert_eq!(frame.get(4, 4), 12);
+        assert_eq!(frame.get(3, 4), 11);
+        assert_eq!(frame.get(1, 4), 9);
+        assert_eq!(frame.get(0, 4), 8);
+        assert_eq!(frame.get(0, 0), 0);
     }
-    visited_set.len()
 }
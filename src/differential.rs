@@ -0,0 +1,167 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use crate::generate::{first_failure, Rng};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+// When a solver is optimised, the original implementation is kept as a reference, and the two are
+// run side by side on the example input and on generated inputs.
+//
+// Any divergence is shrunk before it is reported: first by generating smaller inputs from the same
+// seed, then by throwing away lines while the two implementations still disagree.
+
+pub struct Differential {
+    pub name: &'static str,
+    pub reference: fn(&str) -> String,
+    pub optimised: fn(&str) -> String,
+}
+
+#[derive(Debug)]
+pub struct Divergence {
+    name: &'static str,
+    origin: String,
+    input: String,
+    reference: String,
+    optimised: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} diverged on {}", self.name, self.origin)?;
+        writeln!(f, "reference: {}", self.reference)?;
+        writeln!(f, "optimised: {}", self.optimised)?;
+        writeln!(f, "minimised input ({} lines):", self.input.lines().count())?;
+        write!(f, "{}", self.input)
+    }
+}
+
+impl Differential {
+    // Both answers, or None if either implementation panicked -- an input that makes a solver
+    // panic is not a useful reproducer for a wrong answer.
+    fn answers(&self, input: &str) -> Option<(String, String)> {
+        let reference = panic::catch_unwind(|| (self.reference)(input)).ok()?;
+        let optimised = panic::catch_unwind(AssertUnwindSafe(|| (self.optimised)(input))).ok()?;
+        Some((reference, optimised))
+    }
+
+    fn diverges(&self, input: &str) -> bool {
+        self.answers(input)
+            .is_some_and(|(reference, optimised)| reference != optimised)
+    }
+
+    pub fn check(
+        &self,
+        examples: &[&str],
+        generate: fn(&mut Rng, usize) -> String,
+    ) -> Result<(), Divergence> {
+        for (idx, example) in examples.iter().enumerate() {
+            if self.diverges(example) {
+                return Err(self.divergence(format!("example {}", idx + 1), example));
+            }
+        }
+
+        let diverges = |seed, size| self.diverges(&generate(&mut Rng::new(seed), size));
+        match first_failure(diverges) {
+            Some((seed, size)) => Err(self.divergence(
+                format!("seed {seed}, size {size}"),
+                &generate(&mut Rng::new(seed), size),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn divergence(&self, origin: String, input: &str) -> Divergence {
+        let input = self.minimise(input);
+        let (reference, optimised) = self.answers(&input).unwrap();
+        Divergence {
+            name: self.name,
+            origin,
+            input,
+            reference,
+            optimised,
+        }
+    }
+
+    // Remove ever-smaller chunks of lines, for as long as the answers still differ.
+    fn minimise(&self, input: &str) -> String {
+        let join = |lines: &[&str]| {
+            let mut input = lines.join("\n");
+            input.push('\n');
+            input
+        };
+
+        let mut lines = input.lines().collect::<Vec<_>>();
+        let mut chunk = lines.len().div_ceil(2);
+        while chunk > 0 {
+            let mut removed = false;
+            let mut start = 0;
+            while start < lines.len() {
+                let mut candidate = lines.clone();
+                candidate.drain(start..(start + chunk).min(lines.len()));
+                if !candidate.is_empty() && self.diverges(&join(&candidate)) {
+                    lines = candidate;
+                    removed = true;
+                } else {
+                    start += chunk;
+                }
+            }
+            if !removed {
+                chunk /= 2;
+            }
+        }
+        join(&lines)
+    }
+}
+
+pub fn assert_agree(
+    differential: &Differential,
+    examples: &[&str],
+    generate: fn(&mut Rng, usize) -> String,
+) {
+    if let Err(divergence) = differential.check(examples, generate) {
+        panic!("{divergence}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::digits;
+
+    const SKIPS_SEVENS: Differential = Differential {
+        name: "skips_sevens",
+        reference: |input| input.lines().count().to_string(),
+        optimised: |input| {
+            input
+                .lines()
+                .filter(|&line| line != "7")
+                .count()
+                .to_string()
+        },
+    };
+
+    #[test]
+    fn differential_agreement() {
+        let counts_bytes = Differential {
+            name: "counts_bytes",
+            reference: |input| input.lines().count().to_string(),
+            optimised: |input| input.len().to_string(),
+        };
+        assert!(counts_bytes.check(&["1\n"], digits).is_err());
+        assert!(SKIPS_SEVENS.check(&["1\n2\n"], digits).is_err());
+        assert!(SKIPS_SEVENS
+            .check(&[], |_, size| "1\n".repeat(size))
+            .is_ok());
+    }
+
+    #[test]
+    fn differential_minimises_reproducer() {
+        let divergence = SKIPS_SEVENS
+            .check(&["1\n2\n3\n7\n4\n7\n"], digits)
+            .unwrap_err();
+        assert_eq!(divergence.origin, "example 1");
+        assert_eq!(divergence.input, "7\n");
+        assert_eq!(divergence.reference, "1");
+        assert_eq!(divergence.optimised, "0");
+    }
+}
@@ -0,0 +1,124 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::fmt;
+
+// An answer on its own doesn't say much; a witness is the evidence for it -- which elves carry the
+// most, which directory to delete, where the distress beacon is -- that a solver can return
+// alongside its answer.
+//
+// Each witness comes with its own checker, which verifies it against the puzzle input from scratch.
+// The checker shouldn't share the solver's shortcuts: it's there to catch the solver being wrong.
+
+pub trait Witness: fmt::Display {
+    type Answer: fmt::Display + PartialEq;
+
+    // The answer the witness accounts for.
+    fn answer(&self) -> Self::Answer;
+
+    fn check(&self, input: &str) -> Result<(), String>;
+}
+
+#[derive(Debug)]
+pub struct Explanation {
+    puzzle: usize,
+    answer: String,
+    witness: String,
+    verdict: Result<(), String>,
+}
+
+impl Explanation {
+    // The solver's answer is only verified if its witness checks out, and accounts for that answer.
+    pub fn new<W: Witness>(puzzle: usize, answer: W::Answer, witness: &W, input: &str) -> Self {
+        let verdict = witness.check(input).and_then(|()| {
+            let accounted_for = witness.answer();
+            if accounted_for == answer {
+                Ok(())
+            } else {
+                Err(format!(
+                    "the witness accounts for {accounted_for}, not {answer}"
+                ))
+            }
+        });
+        Explanation {
+            puzzle,
+            answer: answer.to_string(),
+            witness: witness.to_string(),
+            verdict,
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verdict.is_ok()
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "puzzle {}: {}", self.puzzle, self.answer)?;
+        for line in self.witness.lines() {
+            writeln!(f, "  {line}")?;
+        }
+        match &self.verdict {
+            Ok(()) => write!(f, "  verified"),
+            Err(reason) => write!(f, "  REJECTED: {reason}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The largest number in the input.
+    struct Largest(u32);
+
+    impl fmt::Display for Largest {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} is the largest", self.0)
+        }
+    }
+
+    impl Witness for Largest {
+        type Answer = u32;
+
+        fn answer(&self) -> u32 {
+            self.0
+        }
+
+        fn check(&self, input: &str) -> Result<(), String> {
+            let numbers = input
+                .split(' ')
+                .map(|n| n.parse::<u32>().unwrap())
+                .collect::<Vec<_>>();
+            if !numbers.contains(&self.0) {
+                return Err(format!("{} isn't there", self.0));
+            }
+            match numbers.iter().find(|&&n| n > self.0) {
+                Some(larger) => Err(format!("{larger} is larger")),
+                None => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn explain_verifies_witnesses() {
+        let explanation = Explanation::new(1, 5, &Largest(5), "3 5 1");
+        assert!(explanation.is_verified());
+        assert_eq!(
+            explanation.to_string(),
+            "puzzle 1: 5\n  5 is the largest\n  verified"
+        );
+    }
+
+    #[test]
+    fn explain_rejects_witnesses() {
+        let explanation = Explanation::new(1, 3, &Largest(3), "3 5 1");
+        assert!(!explanation.is_verified());
+        assert!(explanation.to_string().ends_with("REJECTED: 5 is larger"));
+
+        let explanation = Explanation::new(2, 4, &Largest(5), "3 5 1");
+        assert!(explanation
+            .to_string()
+            .ends_with("REJECTED: the witness accounts for 5, not 4"));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::checked;
+use crate::isolation::{self, Outcome};
+use std::ffi::c_char;
+use std::panic::AssertUnwindSafe;
+use std::{slice, str};
+
+// Programs written in C call the solvers in-process, through `aoc2022_solve`. The library is
+// built as a static and a dynamic library as well as a Rust one, so the one they link against is
+// in `target/release` after
+//
+//     cargo build --release --lib --features ffi
+//
+// as `libaoc_2022.a` or `libaoc_2022.so` (or the platform's names for them). Their header, `include/aoc2022.h`, is generated from this module by the build
+// script -- so the doc comments here are its documentation -- and `tests/ffi.rs` fails when the
+// copy in `include/` is out of date, until it's rewritten with `AOC_UPDATE_SNAPSHOTS=1`.
+//
+// Nothing may unwind across the boundary, so each call runs the solver in isolation, and a panic
+// comes back as a status code, along with the message it panicked with. The solvers' arithmetic is
+// checked for the length of each call, so an answer that overflowed is reported as such, rather
+// than returned wrong, without leaving checked mode on for the rest of the program.
+
+/// What became of a call to `aoc2022_solve`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aoc2022Status {
+    /// The answer is in the output buffer.
+    Ok = 0,
+    /// The day hasn't been solved yet, or its solver isn't compiled in.
+    NoSuchDay = 1,
+    /// There is no such part: every day has parts 1 and 2.
+    NoSuchPart = 2,
+    /// The input isn't valid UTF-8.
+    InvalidUtf8 = 3,
+    /// The input isn't a well-formed puzzle input for the day.
+    ParseError = 4,
+    /// The solver panicked.
+    Panicked = 5,
+    /// The answer doesn't fit in the output buffer, which is left alone.
+    BufferTooSmall = 6,
+    /// A pointer that can't be null was.
+    NullPointer = 7,
+}
+
+/// Solve `part` (1 or 2) of `day` for the `input_len` bytes of puzzle input at `input`, with the
+/// puzzle's own parameters.
+///
+/// On entry, `*out_len` is the size of `out_buf`. The answer is written to `out_buf` as it would be
+/// printed, NUL-terminated, and `*out_len` is set to its length, not counting the NUL. If it doesn't
+/// fit, the status is `AOC2022_STATUS_BUFFER_TOO_SMALL` and `*out_len` is set to the length it
+/// needs, again not counting the NUL. After any other error, `out_buf` holds a message saying what
+/// went wrong, truncated to fit, and `*out_len` its length.
+///
+/// # Safety
+///
+/// `input` must point to `input_len` readable bytes, and `out_buf` to `*out_len` writable bytes.
+/// Either may be null when its length is 0.
+#[no_mangle]
+pub unsafe extern "C" fn aoc2022_solve(
+    day: u32,
+    part: u32,
+    input: *const u8,
+    input_len: usize,
+    out_buf: *mut c_char,
+    out_len: *mut usize,
+) -> Aoc2022Status {
+    if out_len.is_null() || (input.is_null() && input_len > 0) {
+        return Aoc2022Status::NullPointer;
+    }
+    let capacity = *out_len;
+    if out_buf.is_null() && capacity > 0 {
+        return Aoc2022Status::NullPointer;
+    }
+    let input = if input_len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(input, input_len)
+    };
+    let buffer = if capacity == 0 {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(out_buf.cast::<u8>(), capacity)
+    };
+
+    let (status, output) = solve(day as usize, part as usize, input);
+    if status == Aoc2022Status::Ok && output.len() >= capacity {
+        *out_len = output.len();
+        return Aoc2022Status::BufferTooSmall;
+    }
+    *out_len = copy_truncated(&output, buffer);
+    status
+}
+
+// The answer, or a message saying why there isn't one.
+fn solve(day: usize, part: usize, input: &[u8]) -> (Aoc2022Status, String) {
+    if let Err(err) = crate::solvable(day) {
+        return (Aoc2022Status::NoSuchDay, err.to_string());
+    }
+    if !(1..=2).contains(&part) {
+        return (
+            Aoc2022Status::NoSuchPart,
+            format!("day {day} has no part {part}"),
+        );
+    }
+    let input = match str::from_utf8(input) {
+        Ok(input) => input,
+        Err(err) => return (Aoc2022Status::InvalidUtf8, err.to_string()),
+    };
+
+    let _checked = checked::enable();
+    let mut answer = None;
+    let outcome = isolation::run(AssertUnwindSafe(|| {
+        answer = Some(crate::solve(day, part, input));
+        Ok(())
+    }));
+    match (outcome, answer) {
+        (Outcome::Succeeded, Some(Ok(answer))) => (Aoc2022Status::Ok, answer),
+        (Outcome::Succeeded, Some(Err(err))) => (Aoc2022Status::ParseError, err.to_string()),
+        (Outcome::Failed(failure), _) => (Aoc2022Status::Panicked, failure),
+        _ => (Aoc2022Status::Panicked, "not implemented".to_string()),
+    }
+}
+
+// Copy as much of `text` as fits into `buffer` with a NUL after it, without splitting a character,
+// and return how much that was.
+fn copy_truncated(text: &str, buffer: &mut [u8]) -> usize {
+    let Some(room) = buffer.len().checked_sub(1) else {
+        return 0;
+    };
+    let mut len = text.len().min(room);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    buffer[..len].copy_from_slice(&text.as_bytes()[..len]);
+    buffer[len] = 0;
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(day: u32, part: u32, input: &[u8], capacity: usize) -> (Aoc2022Status, String, usize) {
+        let mut buffer = vec![0xff_u8; capacity];
+        let mut len = capacity;
+        let status = unsafe {
+            aoc2022_solve(
+                day,
+                part,
+                input.as_ptr(),
+                input.len(),
+                buffer.as_mut_ptr().cast(),
+                &mut len,
+            )
+        };
+        let written = buffer.iter().position(|&byte| byte == 0).unwrap_or(0);
+        let output = String::from_utf8(buffer[..written].to_vec()).unwrap();
+        (status, output, len)
+    }
+
+    #[test]
+    fn ffi_solves_and_reports_errors() {
+        let input = b"1000\n2000\n\n4000\n";
+        assert_eq!(call(1, 1, input, 16), (Aoc2022Status::Ok, "4000".into(), 4));
+        assert_eq!(call(1, 2, input, 16), (Aoc2022Status::Ok, "7000".into(), 4));
+        assert_eq!(
+            call(1, 2, input, 4),
+            (Aoc2022Status::BufferTooSmall, "".into(), 4)
+        );
+
+        assert_eq!(call(26, 1, input, 16).0, Aoc2022Status::NoSuchDay);
+        assert_eq!(call(1, 3, input, 64).1, "day 1 has no part 3");
+        assert_eq!(call(1, 1, b"\xff", 16).0, Aoc2022Status::InvalidUtf8);
+
+        let (status, message, len) = call(1, 1, b"one\n", 24);
+        assert_eq!(status, Aoc2022Status::ParseError);
+        assert_eq!(message, "Invalid puzzle input: '");
+        assert_eq!(len, 23);
+        assert_eq!(
+            call(1, 1, b"one\n", 0),
+            (Aoc2022Status::ParseError, "".into(), 0)
+        );
+
+        let overflows = b"Monkey 0:\n  Starting items: 4294967296\n  Operation: new = old * old\n  \
+            Test: divisible by 2\n    If true: throw to monkey 1\n    If false: throw to monkey 1\n\n\
+            Monkey 1:\n  Starting items: 1\n  Operation: new = old + 1\n  \
+            Test: divisible by 2\n    If true: throw to monkey 0\n    If false: throw to monkey 0\n";
+        let (status, message, _) = call(11, 1, overflows, 256);
+        assert_eq!(status, Aoc2022Status::Panicked);
+        assert!(message.starts_with("panicked at src/day_11.rs:"));
+        assert!(message
+            .ends_with(": day 11 overflowed: 4294967296 * 4294967296 doesn't fit in a usize"));
+        assert!(!checked::enabled(), "checked mode should end with the call");
+
+        assert_eq!(copy_truncated("día", &mut [0; 3]), 1);
+        assert_eq!(copy_truncated("día", &mut [0; 4]), 3);
+    }
+
+    #[test]
+    fn ffi_rejects_null_pointers() {
+        let mut len = 16;
+        let status =
+            unsafe { aoc2022_solve(1, 1, std::ptr::null(), 4, std::ptr::null_mut(), &mut len) };
+        assert_eq!(status, Aoc2022Status::NullPointer);
+        let status = unsafe {
+            aoc2022_solve(
+                1,
+                1,
+                b"1\n".as_ptr(),
+                2,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(status, Aoc2022Status::NullPointer);
+    }
+}
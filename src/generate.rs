@@ -0,0 +1,111 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::ops::RangeInclusive;
+
+// Puzzle inputs are generated from a seed, so that any interesting input can be reproduced by
+// passing the same seed and size back in.
+//
+// This is a SplitMix64 generator: it is tiny, fast, and -- unlike an external crate -- its output
+// for a given seed will never change underneath us.
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<usize>) -> usize {
+        let (start, end) = range.into_inner();
+        assert!(
+            start <= end,
+            "Cannot pick from an empty range: {start}..={end}"
+        );
+        start + (self.next_u64() % ((end - start) as u64 + 1)) as usize
+    }
+
+    pub fn range_i64(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (start, end) = range.into_inner();
+        assert!(
+            start <= end,
+            "Cannot pick from an empty range: {start}..={end}"
+        );
+        start + (self.next_u64() % ((end - start) as u64 + 1)) as i64
+    }
+
+    // True roughly `percent` times out of 100.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..=items.len() - 1)]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.range(0..=idx));
+        }
+    }
+
+    pub fn letter(&mut self, letters: RangeInclusive<u8>) -> char {
+        let (start, end) = letters.into_inner();
+        (start + self.range(0..=(end - start) as usize) as u8) as char
+    }
+}
+
+// The property and differential tests try an input generated from each of these seeds, of a size
+// that goes up with the seed, back to 1 after MAX_SIZE.
+#[cfg(test)]
+const SEEDS: u64 = 100;
+#[cfg(test)]
+const MAX_SIZE: usize = 30;
+
+// The first seed whose input `fails`, and the smallest size it still fails at for that seed, so the
+// smallest input can be reported, and reproduced with `generate --seed <seed> --size <size>`.
+#[cfg(test)]
+pub fn first_failure(fails: impl Fn(u64, usize) -> bool) -> Option<(u64, usize)> {
+    (0..SEEDS).find_map(|seed| {
+        let size = 1 + seed as usize % MAX_SIZE;
+        fails(seed, size).then(|| (seed, (1..=size).find(|&size| fails(seed, size)).unwrap()))
+    })
+}
+
+// A line for each of `size` random digits: an input for the tests of the tests.
+#[cfg(test)]
+pub fn digits(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| format!("{}\n", rng.range(0..=9)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut a = Rng::new(2022);
+        let mut b = Rng::new(2022);
+        assert!((0..100).all(|_| a.next_u64() == b.next_u64()));
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn rng_stays_in_range() {
+        let mut rng = Rng::new(0);
+        assert!((0..1000).all(|_| (3..=7).contains(&rng.range(3..=7))));
+        assert!((0..1000).all(|_| (-2..=2).contains(&rng.range_i64(-2..=2))));
+        assert!((0..1000).all(|_| rng.letter(b'a'..=b'c') <= 'c'));
+    }
+}
@@ -0,0 +1,445 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use crate::parse::ParseError;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+// Points, and the vectors between them, in two dimensions and three, for the puzzles set out in
+// space: a rope being dragged about, a heightmap, a cave slice, sensors and their beacons.
+//
+// A point is a place, and a vector is a step from one place to another: adding a vector to a point
+// moves it, and subtracting one point from another gives the vector between them. In two
+// dimensions, y increases downwards, as it does down the rows of a `Grid`, so `Direction::Up` is
+// towards smaller y.
+//
+// No day works in three dimensions yet, turns as it goes, or asks where a bounding box's corners
+// are: until one does, only the tests use those.
+
+// The numbers a point's coordinates can be.
+pub trait Coordinate:
+    Copy + Ord + fmt::Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    // How far apart two coordinates are, which can't be negative.
+    fn distance(self, other: Self) -> Self {
+        if self < other {
+            other - self
+        } else {
+            self - other
+        }
+    }
+}
+
+// The coordinates that can be negative, which are what vectors -- and so directions -- are made of.
+pub trait Signed: Coordinate + Neg<Output = Self> {
+    fn signum(self) -> Self;
+}
+
+macro_rules! coordinate {
+    ($($number:ty),*) => {
+        $(impl Coordinate for $number {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+        })*
+    };
+}
+
+macro_rules! signed {
+    ($($number:ty),*) => {
+        $(impl Signed for $number {
+            fn signum(self) -> Self {
+                <$number>::signum(self)
+            }
+        })*
+    };
+}
+
+coordinate!(i32, i64, isize, u64, usize);
+signed!(i32, i64, isize);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+// Everything a point and its vectors have in common, whatever the number of dimensions. Any
+// attributes given go on the methods, which a dimension no day uses yet can't otherwise reach.
+macro_rules! space {
+    ($(#[$attr:meta])* $point:ident, $vector:ident, $($axis:ident),+) => {
+        $(#[$attr])*
+        impl<T> $point<T> {
+            pub const fn new($($axis: T),+) -> Self {
+                $point { $($axis),+ }
+            }
+        }
+
+        $(#[$attr])*
+        impl<T> $vector<T> {
+            pub const fn new($($axis: T),+) -> Self {
+                $vector { $($axis),+ }
+            }
+        }
+
+        $(#[$attr])*
+        impl<T: Coordinate> $point<T> {
+            pub fn manhattan_distance(self, other: Self) -> T {
+                T::ZERO $(+ self.$axis.distance(other.$axis))+
+            }
+
+            pub fn chebyshev_distance(self, other: Self) -> T {
+                T::ZERO $(.max(self.$axis.distance(other.$axis)))+
+            }
+        }
+
+        $(#[$attr])*
+        impl<T: Signed> $vector<T> {
+            // The vector of at most one step along each axis, in the same direction as this one:
+            // a step towards wherever it points.
+            pub fn signum(self) -> Self {
+                $vector { $($axis: self.$axis.signum()),+ }
+            }
+        }
+
+        impl<T: Add<Output = T>> Add<$vector<T>> for $point<T> {
+            type Output = Self;
+
+            fn add(self, rhs: $vector<T>) -> Self {
+                $point { $($axis: self.$axis + rhs.$axis),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub<$vector<T>> for $point<T> {
+            type Output = Self;
+
+            fn sub(self, rhs: $vector<T>) -> Self {
+                $point { $($axis: self.$axis - rhs.$axis),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = $vector<T>;
+
+            fn sub(self, rhs: Self) -> $vector<T> {
+                $vector { $($axis: self.$axis - rhs.$axis),+ }
+            }
+        }
+
+        impl<T: Copy + Add<Output = T>> AddAssign<$vector<T>> for $point<T> {
+            fn add_assign(&mut self, rhs: $vector<T>) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<T: Copy + Sub<Output = T>> SubAssign<$vector<T>> for $point<T> {
+            fn sub_assign(&mut self, rhs: $vector<T>) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<T: Add<Output = T>> Add for $vector<T> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                $vector { $($axis: self.$axis + rhs.$axis),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $vector<T> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                $vector { $($axis: self.$axis - rhs.$axis),+ }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $vector<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $vector { $($axis: -self.$axis),+ }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $vector<T> {
+            type Output = Self;
+
+            fn mul(self, rhs: T) -> Self {
+                $vector { $($axis: self.$axis * rhs),+ }
+            }
+        }
+    };
+}
+
+space!(Point, Vector, x, y);
+space!(
+    #[cfg_attr(not(test), allow(dead_code))]
+    Point3,
+    Vector3,
+    x,
+    y,
+    z
+);
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+// As a puzzle writes a point down.
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    // Clockwise, from up.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn turn_left(self) -> Self {
+        self.reverse().turn_right()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn reverse(self) -> Self {
+        self.turn_right().turn_right()
+    }
+
+    // A single step in this direction.
+    pub fn vector<T: Signed>(self) -> Vector<T> {
+        match self {
+            Direction::Up => Vector::new(T::ZERO, -T::ONE),
+            Direction::Right => Vector::new(T::ONE, T::ZERO),
+            Direction::Down => Vector::new(T::ZERO, T::ONE),
+            Direction::Left => Vector::new(-T::ONE, T::ZERO),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseError;
+
+    fn from_str(direction: &str) -> Result<Self, ParseError> {
+        match direction {
+            "U" => Ok(Direction::Up),
+            "R" => Ok(Direction::Right),
+            "D" => Ok(Direction::Down),
+            "L" => Ok(Direction::Left),
+            _ => Err(ParseError::new(format!("'{direction}' is not a direction"))),
+        }
+    }
+}
+
+// The way `FromStr` reads it.
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letter = match self {
+            Direction::Up => "U",
+            Direction::Right => "R",
+            Direction::Down => "D",
+            Direction::Left => "L",
+        };
+        f.write_str(letter)
+    }
+}
+
+// The smallest rectangle holding some points, corners included. One whose `min` is beyond its
+// `max` along either axis holds none.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoundingBox<T> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: Coordinate> BoundingBox<T> {
+    pub fn new(min: Point<T>, max: Point<T>) -> Self {
+        BoundingBox { min, max }
+    }
+
+    // The box holding just `point`.
+    pub fn around(point: Point<T>) -> Self {
+        BoundingBox {
+            min: point,
+            max: point,
+        }
+    }
+
+    // The box holding every one of `points`, if there are any.
+    pub fn from_points(points: impl IntoIterator<Item = Point<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let mut bounds = BoundingBox::around(points.next()?);
+        points.for_each(|point| bounds.include(point));
+        Some(bounds)
+    }
+
+    // Grow the box to hold `point`, if it doesn't already.
+    pub fn include(&mut self, point: Point<T>) {
+        self.min = Point::new(self.min.x.min(point.x), self.min.y.min(point.y));
+        self.max = Point::new(self.max.x.max(point.x), self.max.y.max(point.y));
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y
+    }
+
+    pub fn contains(&self, point: Point<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    // How many points wide the box is, both of its edges included.
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::ONE
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::ONE
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn corners(&self) -> [Point<T>; 4] {
+        [
+            self.min,
+            Point::new(self.max.x, self.min.y),
+            Point::new(self.min.x, self.max.y),
+            self.max,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geometry_points_move_by_vectors() {
+        let point = Point::new(3, -2);
+        assert_eq!(point + Vector::new(-1, 4), Point::new(2, 2));
+        assert_eq!(point - Point::new(1, 1), Vector::new(2, -3));
+        assert_eq!(point - Vector::new(3, -2), Point::default());
+        assert_eq!((Point::new(5, 1) - point).signum(), Vector::new(1, 1));
+        assert_eq!(-Vector::new(2, -3) * 2, Vector::new(-4, 6));
+
+        let mut point = Point3::new(1_usize, 2, 3);
+        point += Vector3::new(1, 1, 1);
+        point -= Vector3::new(2, 0, 0);
+        assert_eq!(point, Point3::from((0, 3, 4)));
+        assert_eq!(Vector3::new(-3, 0, 7).signum(), Vector3::new(-1, 0, 1));
+        assert_eq!(Point3::new(1, -2, 3).to_string(), "1,-2,3");
+    }
+
+    #[test]
+    fn geometry_distances() {
+        let (a, b) = (Point::new(2_i64, 18), Point::new(-2, 15));
+        assert_eq!(a.manhattan_distance(b), 7);
+        assert_eq!(a.chebyshev_distance(b), 4);
+        assert_eq!(b.manhattan_distance(a), 7);
+
+        let (a, b) = (Point3::new(1_usize, 5, 2), Point3::new(4, 2, 2));
+        assert_eq!(a.manhattan_distance(b), 6);
+        assert_eq!(a.chebyshev_distance(b), 3);
+    }
+
+    #[test]
+    fn geometry_directions_turn_and_parse() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.to_string().parse(), Ok(direction));
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.reverse().vector::<i32>(), -direction.vector());
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Up.vector(), Vector::new(0, -1));
+        assert!("X".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn geometry_turns_come_back_round() {
+        for direction in Direction::ALL {
+            let turned = (0..4).fold(direction, |turned, _| turned.turn_right());
+            assert_eq!(turned, direction);
+            assert_eq!(direction.reverse().reverse(), direction);
+            assert_ne!(direction.reverse(), direction);
+        }
+    }
+
+    #[test]
+    fn geometry_bounding_boxes() {
+        let points = [Point::new(1, 4), Point::new(-2, 0), Point::new(3, 2)];
+        let bounds = BoundingBox::from_points(points).unwrap();
+        assert_eq!(
+            bounds,
+            BoundingBox::new(Point::new(-2, 0), Point::new(3, 4))
+        );
+        assert_eq!((bounds.width(), bounds.height()), (6, 5));
+        assert!(points.into_iter().all(|point| bounds.contains(point)));
+        assert!(!bounds.contains(Point::new(4, 0)));
+        assert_eq!(bounds.corners()[1], Point::new(3, 0));
+        assert!(!bounds.is_empty());
+        assert!(BoundingBox::new(Point::new(1, 0), Point::new(0, 0)).is_empty());
+        assert_eq!(BoundingBox::<i32>::from_points([]), None);
+    }
+}
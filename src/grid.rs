@@ -0,0 +1,342 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use crate::geometry::{Direction, Point, Vector};
+use crate::parse::ParseError;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+// A rectangular grid of cells, stored row by row, for the puzzles set out on one: a forest, a
+// heightmap, a slice of a cave.
+//
+// Positions are points, with x increasing to the right and y increasing downwards, from `(0, 0)`
+// in the top-left corner. Looking a position up is bounds-checked -- `get` returns `None` outside
+// the grid -- and indexing outside it panics, as a slice does.
+//
+// Some ways of getting about a grid, or redrawing one, no day needs yet: until one does, only the
+// tests use them.
+
+pub type Position = Point<usize>;
+
+// A step from one position to the next.
+pub type Step = Vector<isize>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cell: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![cell; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Position) -> T) -> Self {
+        Grid {
+            cells: (0..height)
+                .flat_map(|y| (0..width).map(move |x| Point::new(x, y)))
+                .map(&mut cell)
+                .collect(),
+            width,
+            height,
+        }
+    }
+
+    // A grid with a row for each line of `input`, and a cell for each character, as `cell` reads
+    // it. Every line has to be the same length, and there has to be at least one.
+    pub fn parse(
+        input: &str,
+        mut cell: impl FnMut(char) -> Result<T, ParseError>,
+    ) -> Result<Self, ParseError> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for line in input.lines() {
+            let before = cells.len();
+            for c in line.chars() {
+                cells.push(cell(c)?);
+            }
+            let length = cells.len() - before;
+            if *width.get_or_insert(length) != length {
+                return Err(ParseError::new("the grid should be rectangular"));
+            }
+            height += 1;
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Grid {
+                cells,
+                width,
+                height,
+            }),
+            _ => Err(ParseError::new("the grid should not be empty")),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.x < self.width && position.y < self.height
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.contains(position)
+            .then(|| &self.cells[position.y * self.width + position.x])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.contains(position)
+            .then(|| &mut self.cells[position.y * self.width + position.x])
+    }
+
+    // Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    // Every position, with its cell, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    // The first position, row by row, whose cell satisfies `predicate`.
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Position> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(position, _)| position)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "there is no column {x}");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    // The position one `step` from `position`, if it's in the grid.
+    pub fn step(&self, position: Position, step: Step) -> Option<Position> {
+        let next = Point::new(
+            position.x.checked_add_signed(step.x)?,
+            position.y.checked_add_signed(step.y)?,
+        );
+        self.contains(next).then_some(next)
+    }
+
+    // The positions in the grid next to `position`, clockwise from the one above it.
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(position, direction.vector()))
+    }
+
+    // The positions in the grid next to `position`, diagonally too, clockwise from the one above
+    // it: each direction, then the diagonal between it and the next.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn neighbours_8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::ALL
+            .into_iter()
+            .flat_map(|direction| {
+                [
+                    direction.vector(),
+                    direction.vector() + direction.turn_right().vector(),
+                ]
+            })
+            .filter_map(move |step| self.step(position, step))
+    }
+
+    // The positions from `position` to the edge of the grid, a `step` at a time, not counting
+    // `position` itself.
+    pub fn ray(&self, position: Position, step: Step) -> impl Iterator<Item = Position> + '_ {
+        std::iter::successors(self.step(position, step), move |&position| {
+            self.step(position, step)
+        })
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.height, self.width, |position| {
+            self[Point::new(position.y, position.x)].clone()
+        })
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn map<U>(&self, cell: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(cell).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    // The grid drawn a row to a line, with each cell drawn by `cell`.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn display<'a, F, D>(&'a self, cell: F) -> impl fmt::Display + 'a
+    where
+        F: Fn(&T) -> D + 'a,
+        D: fmt::Display,
+    {
+        Drawn { grid: self, cell }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position)
+            .unwrap_or_else(|| panic!("{position:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        self.get_mut(position)
+            .unwrap_or_else(|| panic!("{position:?} is outside the grid"))
+    }
+}
+
+struct Drawn<'a, T, F> {
+    grid: &'a Grid<T>,
+    cell: F,
+}
+
+impl<T, F, D> fmt::Display for Drawn<'_, T, F>
+where
+    F: Fn(&T) -> D,
+    D: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.grid.rows() {
+            for cell in row {
+                write!(f, "{}", (self.cell)(cell))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Each cell as it displays itself.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            row.iter().try_for_each(|cell| write!(f, "{cell}"))?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(input, |c| {
+            c.to_digit(10)
+                .ok_or_else(|| ParseError::new(format!("'{c}' is not a digit")))
+        })
+    }
+
+    #[test]
+    fn grid_parses_rectangles() {
+        let grid = digits("123\n456\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point::new(2, 1)], 6);
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(0, 2)), None);
+        assert_eq!(grid.position(|&cell| cell > 4), Some(Point::new(1, 1)));
+
+        assert!(digits("123\n45\n").is_err());
+        assert!(digits("12a\n").is_err());
+        assert!(digits("").is_err());
+        assert!(digits("\n\n").is_err());
+    }
+
+    #[test]
+    fn grid_iterates_rows_columns_and_rays() {
+        let grid = digits("123\n456\n789").unwrap();
+        assert_eq!(grid.row(1), [4, 5, 6]);
+        assert_eq!(grid.rows().count(), 3);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), [3, 6, 9]);
+        assert_eq!(
+            grid.columns()
+                .map(|column| column.sum::<u32>())
+                .collect::<Vec<_>>(),
+            [12, 15, 18]
+        );
+        assert_eq!(
+            grid.ray(Point::new(0, 1), Vector::new(1, 0))
+                .collect::<Vec<_>>(),
+            [Point::new(1, 1), Point::new(2, 1)]
+        );
+        assert_eq!(
+            grid.ray(Point::new(2, 2), Vector::new(-1, -1))
+                .collect::<Vec<_>>(),
+            [Point::new(1, 1), Point::new(0, 0)]
+        );
+        assert_eq!(grid.ray(Point::new(0, 0), Vector::new(0, -1)).count(), 0);
+        assert_eq!(grid.to_string(), "123\n456\n789\n");
+
+        assert_eq!(grid.transpose().to_string(), "147\n258\n369\n");
+        assert_eq!(grid.transpose().transpose(), grid);
+        assert!(grid.map(|&cell| cell % 2 == 0)[Point::new(1, 0)]);
+        assert_eq!(
+            grid.display(|&cell| if cell > 5 { '#' } else { '.' })
+                .to_string(),
+            "...\n..#\n###\n"
+        );
+    }
+
+    #[test]
+    fn grid_neighbours_stay_in_bounds() {
+        let grid = Grid::new(3, 2, '.');
+        assert_eq!(
+            grid.neighbours(Point::new(0, 0)).collect::<Vec<_>>(),
+            [Point::new(1, 0), Point::new(0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours(Point::new(1, 1)).collect::<Vec<_>>(),
+            [Point::new(1, 0), Point::new(2, 1), Point::new(0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours_8(Point::new(1, 0)).collect::<Vec<_>>(),
+            [
+                Point::new(2, 0),
+                Point::new(2, 1),
+                Point::new(1, 1),
+                Point::new(0, 1),
+                Point::new(0, 0)
+            ]
+        );
+        assert_eq!(grid.neighbours_8(Point::new(2, 1)).count(), 3);
+    }
+}
@@ -0,0 +1,233 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::borrow::Cow;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// Each day's personal puzzle input is embedded in the binary. The puzzle's author asks that inputs
+// aren't published, so they can be committed encrypted instead, as `src/input/day_<N>.txt.enc`,
+// with the plaintext `day_<N>.txt` kept out of the repository. The build script embeds whichever
+// of the two is there -- the plaintext, if both are -- and an encrypted input is decrypted when
+// it's loaded, with the passphrase in `AOC_INPUT_KEY`.
+//
+// The key is derived from the passphrase with Argon2id, salted afresh for every file, and the
+// input is encrypted with ChaCha20-Poly1305, which also authenticates it: a wrong passphrase, or a
+// file that's been tampered with, is an error rather than a garbled input. An encrypted file is
+//
+//     MAGIC | salt (16 bytes) | nonce (12 bytes) | ciphertext and tag
+//
+// and the day is authenticated along with it, so one day's input can't stand in for another's.
+
+pub const KEY_VARIABLE: &str = "AOC_INPUT_KEY";
+
+const MAGIC: &[u8] = b"AOC2022\x01";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+// Which of these are embedded depends on which inputs are in the tree, and which days are compiled.
+#[allow(dead_code)]
+enum Embedded {
+    Plain(&'static str),
+    Encrypted(&'static [u8]),
+    Missing,
+}
+
+// `INPUTS`, a day's embedded input for each day, generated by the build script.
+include!(concat!(env!("OUT_DIR"), "/inputs.rs"));
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    message: String,
+}
+
+impl InputError {
+    fn new(message: impl Into<String>) -> Self {
+        InputError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+// A day's puzzle input, decrypted if need be.
+pub fn load(day: usize) -> Result<Cow<'static, str>, InputError> {
+    match INPUTS
+        .get(day.wrapping_sub(1))
+        .unwrap_or(&Embedded::Missing)
+    {
+        Embedded::Plain(input) => Ok(Cow::Borrowed(input)),
+        Embedded::Encrypted(encrypted) => {
+            let passphrase = passphrase().ok_or_else(|| {
+                InputError::new(format!(
+                    "day {day}'s input is encrypted: set {KEY_VARIABLE} to its passphrase"
+                ))
+            })?;
+            let plaintext = decrypt(day, encrypted, &passphrase)?;
+            String::from_utf8(plaintext)
+                .map(Cow::Owned)
+                .map_err(|_| InputError::new(format!("day {day}'s input isn't valid UTF-8")))
+        }
+        Embedded::Missing => Err(InputError::new(format!(
+            "there's no input for day {day}: add src/input/day_{day}.txt, or day_{day}.txt.enc"
+        ))),
+    }
+}
+
+pub fn passphrase() -> Option<String> {
+    env::var(KEY_VARIABLE)
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<ChaCha20Poly1305, InputError> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| InputError::new(format!("couldn't derive a key: {err}")))?;
+    Ok(ChaCha20Poly1305::new(&key))
+}
+
+fn associated_data(day: usize) -> String {
+    format!("day_{day}")
+}
+
+pub fn encrypt(day: usize, plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, InputError> {
+    let mut salt = [0; SALT_LEN];
+    let mut nonce = [0; NONCE_LEN];
+    getrandom::getrandom(&mut salt)
+        .and_then(|()| getrandom::getrandom(&mut nonce))
+        .map_err(|err| InputError::new(format!("couldn't generate a salt and nonce: {err}")))?;
+
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: associated_data(day).as_bytes(),
+            },
+        )
+        .map_err(|_| InputError::new(format!("couldn't encrypt day {day}'s input")))?;
+    Ok([MAGIC, &salt, &nonce, &ciphertext].concat())
+}
+
+pub fn decrypt(day: usize, encrypted: &[u8], passphrase: &str) -> Result<Vec<u8>, InputError> {
+    let header = encrypted
+        .strip_prefix(MAGIC)
+        .filter(|rest| rest.len() >= SALT_LEN + NONCE_LEN)
+        .ok_or_else(|| InputError::new(format!("day {day}'s input isn't an encrypted input")))?;
+    let (salt, rest) = header.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    cipher(passphrase, salt)?
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: associated_data(day).as_bytes(),
+            },
+        )
+        .map_err(|_| {
+            InputError::new(format!(
+                "day {day}'s input couldn't be decrypted: {KEY_VARIABLE} is wrong, or the file is \
+                 corrupt"
+            ))
+        })
+}
+
+// Encrypt `day_<N>.txt` in `dir` to `day_<N>.txt.enc`, returning whether it was written. An input
+// that's already encrypted, unchanged, is left alone, so that encrypting every input again doesn't
+// rewrite every file.
+pub fn encrypt_file(
+    dir: &Path,
+    day: usize,
+    passphrase: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let plaintext = fs::read(dir.join(format!("day_{day}.txt")))?;
+    let encrypted = dir.join(format!("day_{day}.txt.enc"));
+    if let Ok(existing) = fs::read(&encrypted) {
+        if decrypt(day, &existing, passphrase).is_ok_and(|existing| existing == plaintext) {
+            return Ok(false);
+        }
+    }
+    fs::write(encrypted, encrypt(day, &plaintext, passphrase)?)?;
+    Ok(true)
+}
+
+// Decrypt `day_<N>.txt.enc` in `dir` to `day_<N>.txt`.
+pub fn decrypt_file(
+    dir: &Path,
+    day: usize,
+    passphrase: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let encrypted = fs::read(dir.join(format!("day_{day}.txt.enc")))?;
+    fs::write(
+        dir.join(format!("day_{day}.txt")),
+        decrypt(day, &encrypted, passphrase)?,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_round_trips_through_encryption() {
+        let input = b"1000\n2000\n\n4000\n";
+        let encrypted = encrypt(1, input, "hunter2").unwrap();
+        assert!(encrypted.starts_with(MAGIC));
+        assert!(!encrypted.windows(4).any(|window| window == b"1000"));
+        assert_eq!(decrypt(1, &encrypted, "hunter2").as_deref(), Ok(&input[..]));
+
+        // Salted and encrypted afresh every time.
+        assert_ne!(encrypt(1, input, "hunter2").unwrap(), encrypted);
+    }
+
+    #[test]
+    fn input_decryption_rejects_the_wrong_key_day_or_file() {
+        let encrypted = encrypt(1, b"1000\n", "hunter2").unwrap();
+        assert!(decrypt(1, &encrypted, "hunter3").is_err());
+        assert!(decrypt(2, &encrypted, "hunter2").is_err());
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(1, &tampered, "hunter2").is_err());
+        assert!(decrypt(1, &encrypted[..MAGIC.len() + SALT_LEN], "hunter2").is_err());
+        assert_eq!(
+            decrypt(1, b"1000\n", "hunter2"),
+            Err(InputError::new("day 1's input isn't an encrypted input"))
+        );
+    }
+
+    #[test]
+    fn input_files_are_only_encrypted_when_they_change() {
+        let dir = env::temp_dir().join(format!("aoc_2022_input_files_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let _ = fs::remove_file(dir.join("day_3.txt.enc"));
+        fs::write(dir.join("day_3.txt"), "vJrwpWtwJgWrhcsFMMfFFhFp\n").unwrap();
+
+        assert!(encrypt_file(&dir, 3, "hunter2").unwrap());
+        assert!(!encrypt_file(&dir, 3, "hunter2").unwrap());
+        fs::write(dir.join("day_3.txt"), "PmmdzqPrVvPwwTWBwg\n").unwrap();
+        assert!(encrypt_file(&dir, 3, "hunter2").unwrap());
+
+        fs::remove_file(dir.join("day_3.txt")).unwrap();
+        decrypt_file(&dir, 3, "hunter2").unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("day_3.txt")).unwrap(),
+            "PmmdzqPrVvPwwTWBwg\n"
+        );
+        assert!(decrypt_file(&dir, 3, "hunter3").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,284 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use crate::geometry::Coordinate;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+// Sets of integers made of inclusive ranges -- the sections an elf has been assigned, or the
+// positions along a row that a sensor can see.
+//
+// The ranges are kept sorted, and merged whenever they overlap or touch, so 1..=3 and 4..=6 make
+// 1..=6. Two sets with the same members are then made of the same ranges, and every question about
+// a set only has to look at each of its ranges once.
+//
+// No day asks whether a single integer is in a set yet: until one does, only the tests use
+// `contains`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Coordinate> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    // Add every integer in `range` to the set, merging it with the ranges it overlaps or touches.
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        let first = self
+            .ranges
+            .partition_point(|&(_, before)| before < start && start - before > T::ONE);
+        let mut last = first;
+        while let Some(&(next_start, next_end)) = self.ranges.get(last) {
+            if next_start > end && next_start - end > T::ONE {
+                break;
+            }
+            start = start.min(next_start);
+            end = end.max(next_end);
+            last += 1;
+        }
+        self.ranges.splice(first..last, [(start, end)]);
+    }
+
+    // Everything in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        union.extend(other.ranges());
+        union
+    }
+
+    // Everything in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut ours, mut theirs) = (self.ranges.iter(), other.ranges.iter());
+        let (mut ours_next, mut theirs_next) = (ours.next(), theirs.next());
+        while let (Some(&(a_start, a_end)), Some(&(b_start, b_end))) = (ours_next, theirs_next) {
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start <= end {
+                ranges.push((start, end));
+            }
+            // Whichever range ends first can't overlap anything else in the other set.
+            if a_end < b_end {
+                ours_next = ours.next();
+            } else {
+                theirs_next = theirs.next();
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    // Everything in this set that isn't in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut theirs = other.ranges.iter().peekable();
+        for &(start, end) in &self.ranges {
+            // What's left of the range after the ranges of `other` before it are cut out.
+            let mut rest = Some(start);
+            while let (Some(start), Some(&&(b_start, b_end))) = (rest, theirs.peek()) {
+                if b_start > end {
+                    break;
+                }
+                if b_end >= start {
+                    if b_start > start {
+                        ranges.push((start, b_start - T::ONE));
+                    }
+                    if b_end >= end {
+                        // It might cut into the next range too, so it stays.
+                        rest = None;
+                        continue;
+                    }
+                    rest = Some(b_end + T::ONE);
+                }
+                theirs.next();
+            }
+            if let Some(start) = rest {
+                ranges.push((start, end));
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    // How many integers are in the set.
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::ZERO, |len, &(start, end)| len + (end - start) + T::ONE)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn contains(&self, value: T) -> bool {
+        self.range_containing(value).is_some()
+    }
+
+    // Whether every integer in `range` is in the set.
+    pub fn contains_range(&self, range: RangeInclusive<T>) -> bool {
+        range.is_empty()
+            || self
+                .range_containing(*range.start())
+                .is_some_and(|(_, end)| end >= *range.end())
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.ranges().all(|range| self.contains_range(range))
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    // The ranges the set is made of, in order.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|&(start, end)| start..=end)
+    }
+
+    // The ranges between the set's ranges, in order: what's missing between its smallest member
+    // and its largest.
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges
+            .windows(2)
+            .map(|pair| pair[0].1 + T::ONE..=pair[1].0 - T::ONE)
+    }
+
+    fn range_containing(&self, value: T) -> Option<(T, T)> {
+        let idx = self.ranges.partition_point(|&(_, end)| end < value);
+        self.ranges
+            .get(idx)
+            .copied()
+            .filter(|&(start, _)| start <= value)
+    }
+}
+
+impl<T: Coordinate> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = IntervalSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Coordinate> Extend<RangeInclusive<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, ranges: I) {
+        for range in ranges {
+            self.insert(range);
+        }
+    }
+}
+
+impl<T: Coordinate> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(ranges: I) -> Self {
+        let mut set = IntervalSet::new();
+        set.extend(ranges);
+        set
+    }
+}
+
+// {1..=3, 7..=7}
+impl<T: Coordinate> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.ranges()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[RangeInclusive<i64>]) -> IntervalSet<i64> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn interval_insert_merges() {
+        let mut ranges = set(&[10..=12, 1..=3, 20..=25]);
+        assert_eq!(
+            ranges.ranges().collect::<Vec<_>>(),
+            [1..=3, 10..=12, 20..=25]
+        );
+
+        ranges.insert(4..=5);
+        ranges.insert(7..=7);
+        ranges.insert(13..=19);
+        ranges.insert(RangeInclusive::new(8, 6));
+        assert_eq!(ranges.ranges().collect::<Vec<_>>(), [1..=5, 7..=7, 10..=25]);
+        assert_eq!(format!("{ranges:?}"), "{1..=5, 7..=7, 10..=25}");
+
+        ranges.insert(-5..=30);
+        assert_eq!(ranges, set(&[-5..=30]));
+        assert_eq!(ranges.len(), 36);
+        assert!(IntervalSet::<i64>::new().is_empty());
+    }
+
+    #[test]
+    fn interval_union_merges_overlapping_and_adjacent_ranges() {
+        let a = set(&[1..=3, 10..=12]);
+
+        assert_eq!(a.union(&set(&[2..=5])), set(&[1..=5, 10..=12]));
+        assert_eq!(a.union(&set(&[4..=9])), set(&[1..=12]));
+        assert_eq!(
+            a.union(&set(&[13..=13, 15..=15])),
+            set(&[1..=3, 10..=13, 15..=15])
+        );
+        assert_eq!(a.union(&set(&[5..=8])).ranges().count(), 3);
+        assert_eq!(a.union(&IntervalSet::new()), a);
+    }
+
+    #[test]
+    fn interval_algebra() {
+        let a = set(&[1..=5, 10..=15, 20..=20]);
+        let b = set(&[4..=11, 14..=22]);
+
+        assert_eq!(a.union(&b), set(&[1..=22]));
+        assert_eq!(a.intersection(&b), set(&[4..=5, 10..=11, 14..=15, 20..=20]));
+        assert_eq!(a.difference(&b), set(&[1..=3, 12..=13]));
+        assert_eq!(b.difference(&a), set(&[6..=9, 16..=19, 21..=22]));
+        assert_eq!(a.difference(&a), IntervalSet::new());
+        assert_eq!(a.difference(&IntervalSet::new()), a);
+
+        // Every integer from 0 to 24 is in a, in b, or in neither, with none counted twice.
+        let everything = set(&[0..=24]);
+        assert_eq!(
+            a.len() + b.len() - a.intersection(&b).len()
+                + everything.difference(&a.union(&b)).len(),
+            everything.len()
+        );
+    }
+
+    #[test]
+    fn interval_queries() {
+        let a = set(&[1..=5, 10..=15, 20..=20]);
+
+        assert!(a.contains(1) && a.contains(12) && a.contains(20));
+        assert!(!a.contains(0) && !a.contains(7) && !a.contains(21));
+        assert!(a.contains_range(11..=15));
+        assert!(!a.contains_range(4..=10));
+        assert!(a.is_superset(&set(&[2..=3, 12..=14])));
+        assert!(!a.is_superset(&set(&[2..=3, 16..=16])));
+        assert!(a.overlaps(&set(&[15..=19])));
+        assert!(!a.overlaps(&set(&[6..=9, 16..=19])));
+        assert_eq!(a.gaps().collect::<Vec<_>>(), [6..=9, 16..=19]);
+        assert_eq!(set(&[2..=8]).gaps().count(), 0);
+    }
+
+    // Covering everything outside a bounding range leaves only the gaps inside it, its ends
+    // included.
+    #[test]
+    fn interval_gaps_inside_a_bounding_range() {
+        let outside = set(&[i64::MIN..=-1, 21..=i64::MAX]);
+        let gaps = |covered: &[RangeInclusive<i64>]| {
+            set(covered).union(&outside).gaps().collect::<Vec<_>>()
+        };
+
+        assert_eq!(gaps(&[-5..=3, 6..=14, 18..=30]), [4..=5, 15..=17]);
+        assert_eq!(gaps(&[1..=19]), [0..=0, 20..=20]);
+        assert_eq!(gaps(&[]), [0..=20]);
+        assert_eq!(gaps(&[-10..=30]), []);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::parse::ParseError;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::panic::{self, UnwindSafe};
+use std::sync::Once;
+
+// Each day runs in isolation, so that one day panicking -- an unsolved day's `unimplemented!()`,
+// or an `unwrap` that didn't hold -- doesn't take every other day down with it.
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Succeeded,
+    Failed(String),
+    Unimplemented,
+}
+
+thread_local! {
+    // Whether this thread is running a day in isolation, and where its last panic happened -- which
+    // the panic's payload doesn't say.
+    static ISOLATED: Cell<bool> = const { Cell::new(false) };
+    static LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static QUIET_HOOK: Once = Once::new();
+
+// Panics in isolated days are reported as failures along with their message, so the panic hook
+// keeps quiet about them, rather than printing them twice. Panics anywhere else are left to the
+// default hook.
+fn install_quiet_hook() {
+    QUIET_HOOK.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if ISOLATED.get() {
+                LOCATION.set(info.location().map(ToString::to_string));
+            } else {
+                default(info);
+            }
+        }));
+    });
+}
+
+pub fn run(day: impl FnOnce() -> Result<(), ParseError> + UnwindSafe) -> Outcome {
+    install_quiet_hook();
+    ISOLATED.set(true);
+    let result = panic::catch_unwind(day);
+    ISOLATED.set(false);
+
+    match result {
+        Ok(Ok(())) => Outcome::Succeeded,
+        Ok(Err(err)) => Outcome::Failed(err.to_string()),
+        Err(payload) => {
+            let location = LOCATION.take();
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "(no message)".to_string());
+            if message.starts_with("not implemented") {
+                return Outcome::Unimplemented;
+            }
+            match location {
+                Some(location) => Outcome::Failed(format!("panicked at {location}: {message}")),
+                None => Outcome::Failed(format!("panicked: {message}")),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    succeeded: Vec<usize>,
+    failed: Vec<usize>,
+    unimplemented: Vec<usize>,
+}
+
+impl Summary {
+    pub fn record(&mut self, day: usize, outcome: &Outcome) {
+        match outcome {
+            Outcome::Succeeded => self.succeeded.push(day),
+            Outcome::Failed(_) => self.failed.push(day),
+            Outcome::Unimplemented => self.unimplemented.push(day),
+        }
+    }
+
+    pub fn has_failures(&self) -> bool {
+        !self.failed.is_empty()
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let days = |days: &[usize]| {
+            let list = days.iter().map(usize::to_string).collect::<Vec<_>>();
+            match days.len() {
+                0 => String::new(),
+                1 => format!(" (day {})", list[0]),
+                _ => format!(" (days {})", list.join(", ")),
+            }
+        };
+        write!(
+            f,
+            "{} succeeded, {} failed{}, {} unimplemented{}",
+            self.succeeded.len(),
+            self.failed.len(),
+            days(&self.failed),
+            self.unimplemented.len(),
+            days(&self.unimplemented)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isolation_catches_panics() {
+        assert_eq!(run(|| Ok(())), Outcome::Succeeded);
+        assert_eq!(
+            run(|| Err(ParseError::new("no elves"))),
+            Outcome::Failed("Invalid puzzle input: no elves".to_string())
+        );
+        assert_eq!(run(|| unimplemented!()), Outcome::Unimplemented);
+
+        let Outcome::Failed(message) = run(|| panic!("the elves {}", "escaped")) else {
+            panic!("a panic should be a failure");
+        };
+        assert!(message.starts_with("panicked at src/isolation.rs:"));
+        assert!(message.ends_with(": the elves escaped"));
+    }
+
+    #[test]
+    fn isolation_summarises_days() {
+        let mut summary = Summary::default();
+        summary.record(1, &Outcome::Succeeded);
+        summary.record(2, &Outcome::Unimplemented);
+        summary.record(3, &Outcome::Unimplemented);
+        assert!(!summary.has_failures());
+        summary.record(4, &Outcome::Failed("oops".to_string()));
+        assert!(summary.has_failures());
+        assert_eq!(
+            summary.to_string(),
+            "1 succeeded, 1 failed (day 4), 2 unimplemented (days 2, 3)"
+        );
+    }
+}
@@ -1,53 +1,259 @@
-macro_rules! run {
-    ( $day:expr ) => {
+// Call a function in a solved day's module, or read one of its constants. Only days that are
+// compiled in can be called.
+macro_rules! day {
+    ( $day:expr => $function:ident ( $( $arg:expr ),* ) ) => {
+        day!(@ $day => $function ( $( $arg ),* ))
+    };
+    ( $day:expr => $constant:ident ) => {
+        day!(@ $day => $constant)
+    };
+    ( @ $day:expr => $( $item:tt )* ) => {
         match $day {
-            1 => day_1::run(),
-            2 => day_2::run(),
-            3 => day_3::run(),
-            4 => day_4::run(),
-            5 => day_5::run(),
-            6 => day_6::run(),
-            7 => day_7::run(),
-            8 => day_8::run(),
-            9 => day_9::run(),
-            10 => day_10::run(),
-            11 => day_11::run(),
-            12 => day_12::run(),
-            13 => day_13::run(),
-            14 => day_14::run(),
-            15 => day_15::run(),
+            #[cfg(feature = "day-1")]
+            1 => crate::day_1::$( $item )*,
+            #[cfg(feature = "day-2")]
+            2 => crate::day_2::$( $item )*,
+            #[cfg(feature = "day-3")]
+            3 => crate::day_3::$( $item )*,
+            #[cfg(feature = "day-4")]
+            4 => crate::day_4::$( $item )*,
+            #[cfg(feature = "day-5")]
+            5 => crate::day_5::$( $item )*,
+            #[cfg(feature = "day-6")]
+            6 => crate::day_6::$( $item )*,
+            #[cfg(feature = "day-7")]
+            7 => crate::day_7::$( $item )*,
+            #[cfg(feature = "day-8")]
+            8 => crate::day_8::$( $item )*,
+            #[cfg(feature = "day-9")]
+            9 => crate::day_9::$( $item )*,
+            #[cfg(feature = "day-10")]
+            10 => crate::day_10::$( $item )*,
+            #[cfg(feature = "day-11")]
+            11 => crate::day_11::$( $item )*,
+            #[cfg(feature = "day-12")]
+            12 => crate::day_12::$( $item )*,
+            #[cfg(feature = "day-13")]
+            13 => crate::day_13::$( $item )*,
+            #[cfg(feature = "day-14")]
+            14 => crate::day_14::$( $item )*,
+            #[cfg(feature = "day-15")]
+            15 => crate::day_15::$( $item )*,
             _ => unreachable!(),
         }
     };
 }
 
-use clap::{Arg, ArgAction, Command};
+// Emit a trace event of the given level and kind, with some named fields, if `--verbose` and
+// `--trace` let it through. The fields are only evaluated when they will be.
+#[cfg_attr(not(feature = "all-days"), allow(unused_macros))]
+macro_rules! trace {
+    ( $level:ident, $kind:literal $( , $field:ident = $value:expr )* $(,)? ) => {
+        if crate::trace::enabled(crate::trace::Level::$level) {
+            crate::trace::emit(
+                crate::trace::Level::$level,
+                module_path!(),
+                $kind,
+                &[ $( (stringify!($field), &$value as &dyn std::fmt::Display) ),* ],
+            );
+        }
+    };
+}
+
+// Report how far along a slow solver is, of some total, to be drawn as a progress bar if it's
+// being shown.
+#[cfg_attr(not(feature = "all-days"), allow(unused_macros))]
+macro_rules! progress {
+    ( $kind:literal, $total:expr ) => {
+        crate::progress::Progress::new(module_path!(), $kind, $total)
+    };
+}
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use explain::Explanation;
+use generate::Rng;
+use input::InputError;
+use isolation::{Outcome, Summary};
+use params::{Overrides, Parameter, Params};
+#[cfg(feature = "simulation")]
+use simulation::Playback;
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, IsTerminal};
 use std::ops::RangeInclusive;
+use std::panic::AssertUnwindSafe;
+#[cfg(feature = "visualization")]
+use std::path::Path;
+use std::path::PathBuf;
 
+// What the days share only has to be used in full when every day is compiled in: a build with
+// fewer days leaves some of it unused, so the shared modules, and the items here that depend on
+// the days, only warn about that with `all-days`.
+#[cfg(feature = "simulation")]
+#[cfg_attr(not(feature = "all-days"), allow(dead_code))]
+mod animate;
+mod batch;
+mod checked;
+mod compare;
+#[cfg(feature = "dashboard")]
+mod dashboard;
+#[cfg(feature = "day-1")]
 mod day_1;
+#[cfg(feature = "day-10")]
 mod day_10;
+#[cfg(feature = "day-11")]
 mod day_11;
+#[cfg(feature = "day-12")]
 mod day_12;
+#[cfg(feature = "day-13")]
 mod day_13;
+#[cfg(feature = "day-14")]
 mod day_14;
+#[cfg(feature = "day-15")]
 mod day_15;
+// The days not solved yet are left as they were first written, tabs and all.
+#[cfg(feature = "day-16")]
+#[rustfmt::skip]
+mod day_16;
+#[cfg(feature = "day-17")]
+#[rustfmt::skip]
+mod day_17;
+#[cfg(feature = "day-18")]
+#[rustfmt::skip]
+mod day_18;
+#[cfg(feature = "day-19")]
+#[rustfmt::skip]
+mod day_19;
+#[cfg(feature = "day-2")]
 mod day_2;
+#[cfg(feature = "day-20")]
+#[rustfmt::skip]
+mod day_20;
+#[cfg(feature = "day-21")]
+#[rustfmt::skip]
+mod day_21;
+#[cfg(feature = "day-22")]
+#[rustfmt::skip]
+mod day_22;
+#[cfg(feature = "day-23")]
+#[rustfmt::skip]
+mod day_23;
+#[cfg(feature = "day-24")]
+#[rustfmt::skip]
+mod day_24;
+#[cfg(feature = "day-25")]
+#[rustfmt::skip]
+mod day_25;
+#[cfg(feature = "day-3")]
 mod day_3;
+#[cfg(feature = "day-4")]
 mod day_4;
+#[cfg(feature = "day-5")]
 mod day_5;
+#[cfg(feature = "day-6")]
 mod day_6;
+#[cfg(feature = "day-7")]
 mod day_7;
+#[cfg(feature = "day-8")]
 mod day_8;
+#[cfg(feature = "day-9")]
 mod day_9;
+#[cfg(test)]
+mod differential;
+mod explain;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod generate;
+#[cfg(any(
+    feature = "day-4",
+    feature = "day-8",
+    feature = "day-9",
+    feature = "day-12",
+    feature = "day-14",
+    feature = "day-15"
+))]
+mod geometry;
+#[cfg(any(feature = "day-8", feature = "day-12", feature = "day-14"))]
+mod grid;
+mod input;
+#[cfg(any(feature = "day-4", feature = "day-15"))]
+mod interval;
+mod isolation;
+mod params;
+mod parse;
+mod progress;
+#[cfg(test)]
+mod property;
+#[cfg(feature = "day-12")]
+mod search;
+#[cfg(feature = "simulation")]
+mod simulation;
+#[cfg(test)]
+mod snapshot;
+mod trace;
+#[cfg(feature = "visualization")]
+mod visualize;
+
+pub use parse::ParseError;
 
 pub struct Config {
     all_days: bool,
     specific_day: usize,
+    explain: bool,
+    checked: bool,
+    overrides: Overrides,
+    generate: Option<Generate>,
+    compare: Option<Compare>,
+    batch: Option<Batch>,
+    encryption: Option<Encryption>,
+    #[cfg(feature = "dashboard")]
+    dashboard: bool,
+    #[cfg(feature = "simulation")]
+    simulate: Option<Simulate>,
+}
+
+struct Generate {
+    day: usize,
+    seed: u64,
+    #[cfg_attr(not(feature = "all-days"), allow(dead_code))]
+    size: usize,
+}
+
+// Compare a day's answers with another program's.
+struct Compare {
+    day: usize,
+    program: String,
+}
+
+// Run days on every input in a directory, checking them against the answers they should get.
+struct Batch {
+    day: Option<usize>,
+    dir: PathBuf,
+}
+
+// Encrypt the plaintext puzzle inputs in a directory, or decrypt the encrypted ones.
+struct Encryption {
+    encrypt: bool,
+    day: Option<usize>,
+    dir: PathBuf,
+}
+
+#[cfg(feature = "simulation")]
+struct Simulate {
+    day: usize,
+    #[cfg_attr(not(feature = "all-days"), allow(dead_code))]
+    part: usize,
+    #[cfg_attr(not(feature = "all-days"), allow(dead_code))]
+    playback: Playback,
+    #[cfg(feature = "visualization")]
+    visualize: Option<PathBuf>,
+    #[cfg_attr(not(feature = "all-days"), allow(dead_code))]
+    animate: Option<u32>,
 }
 
 impl Config {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let matches = Command::new("AoC_2022")
+        let command = Command::new("AoC_2022")
             .author("BeniiAlCo")
             .about("Advent of Code 2022 in Rust!")
             .arg(
@@ -55,7 +261,7 @@ impl Config {
                     .long("all_days")
                     .short('a')
                     .num_args(0)
-                    .exclusive(true)
+                    .conflicts_with("SpecificDay")
                     .action(ArgAction::SetTrue),
             )
             .arg(
@@ -63,28 +269,249 @@ impl Config {
                     .long("day")
                     .short('d')
                     .num_args(1)
-                    .exclusive(true)
                     .value_parser(Self::days_in_range),
             )
-            .get_matches();
+            .arg(
+                Arg::new("Explain")
+                    .long("explain")
+                    .short('e')
+                    .num_args(0)
+                    .help("Show the evidence for each answer, and check it independently")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("Verbose")
+                    .long("verbose")
+                    .short('v')
+                    .global(true)
+                    .help("Trace what the solvers are doing on stderr; -vv for more detail")
+                    .action(ArgAction::Count),
+            )
+            .arg(
+                Arg::new("Trace")
+                    .long("trace")
+                    .num_args(1)
+                    .global(true)
+                    .value_name("FILTER")
+                    .help("Only trace these days and kinds of event, e.g. 11:round,cycle,12")
+                    .value_parser(|rules: &str| {
+                        trace::Filter::new(1, rules).map(|_| rules.to_string())
+                    }),
+            )
+            .arg(
+                Arg::new("Checked")
+                    .long("checked")
+                    .num_args(0)
+                    .global(true)
+                    .help(
+                        "Stop a day with an error if its arithmetic overflows, not a wrong answer",
+                    )
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("Param")
+                    .long("param")
+                    .num_args(1)
+                    .global(true)
+                    .value_name("[DAY.]NAME=VALUE")
+                    .help("Override a puzzle parameter, e.g. day_15.row=10, or row=10 for one day")
+                    .long_help(parameters_help())
+                    .action(ArgAction::Append),
+            )
+            .args_conflicts_with_subcommands(true)
+            .subcommand(
+                Command::new("generate")
+                    .about("Generate a random, valid puzzle input for a day")
+                    .arg(
+                        Arg::new("Day")
+                            .long("day")
+                            .short('d')
+                            .num_args(1)
+                            .required(true)
+                            .value_parser(Self::solved_days),
+                    )
+                    .arg(
+                        Arg::new("Seed")
+                            .long("seed")
+                            .short('s')
+                            .num_args(1)
+                            .default_value("0")
+                            .value_parser(value_parser!(u64)),
+                    )
+                    .arg(
+                        Arg::new("Size")
+                            .long("size")
+                            .short('k')
+                            .num_args(1)
+                            .default_value("10")
+                            .value_parser(value_parser!(usize)),
+                    ),
+            )
+            .subcommand(
+                Command::new("compare")
+                    .about(
+                        "Compare a day's answers, and how long they take, with another program's",
+                    )
+                    .arg(
+                        Arg::new("Day")
+                            .long("day")
+                            .short('d')
+                            .num_args(1)
+                            .required(true)
+                            .value_parser(Self::solved_days),
+                    )
+                    .arg(
+                        Arg::new("Cmd")
+                            .long("cmd")
+                            .num_args(1)
+                            .required(true)
+                            .value_name("PROGRAM")
+                            .help(
+                                "A shell command that reads the puzzle input on stdin, and prints \
+                                 part 1's answer on one line and part 2's after it",
+                            ),
+                    ),
+            )
+            .subcommand(
+                Command::new("batch")
+                    .about(
+                        "Run each day on every input in <DIR>/day_<N>/*.txt, checking each \
+                         against the answers in <NAME>.answers beside it, if there are any",
+                    )
+                    .arg(
+                        Arg::new("Day")
+                            .long("day")
+                            .short('d')
+                            .num_args(1)
+                            .help("Only this day's inputs, rather than every day's")
+                            .value_parser(Self::solved_days),
+                    )
+                    .arg(
+                        Arg::new("Inputs")
+                            .long("inputs")
+                            .num_args(1)
+                            .value_name("DIR")
+                            .default_value("inputs")
+                            .value_parser(value_parser!(PathBuf)),
+                    ),
+            )
+            .subcommand(Encryption::command("encrypt").about(format!(
+                "Encrypt the puzzle inputs, day_<N>.txt, to day_<N>.txt.enc, with the passphrase \
+                 in {}",
+                input::KEY_VARIABLE
+            )))
+            .subcommand(Encryption::command("decrypt").about(format!(
+                "Decrypt the puzzle inputs, day_<N>.txt.enc, to day_<N>.txt, with the passphrase \
+                 in {}",
+                input::KEY_VARIABLE
+            )));
+        #[cfg(feature = "simulation")]
+        let command = command.subcommand(Simulate::command());
+        #[cfg(feature = "dashboard")]
+        let command = command.subcommand(
+            Command::new("dashboard")
+                .about("Browse the days, their inputs and their answers, full-screen"),
+        );
+        let matches = command.get_matches();
 
-        Ok(Config {
+        trace::install(trace::Filter::new(
+            matches.get_count("Verbose"),
+            matches
+                .get_one::<String>("Trace")
+                .map_or("", String::as_str),
+        )?);
+
+        // Trace events and the progress bar would get in each other's way on stderr, and the
+        // progress bar would get in the dashboard's way.
+        if matches.get_count("Verbose") == 0
+            && io::stderr().is_terminal()
+            && matches.subcommand_name() != Some("dashboard")
+        {
+            progress::enable();
+        }
+        let mut config = Config {
+            generate: matches.subcommand_matches("generate").map(Generate::new),
+            compare: matches.subcommand_matches("compare").map(Compare::new),
+            batch: matches.subcommand_matches("batch").map(Batch::new),
+            #[cfg(feature = "dashboard")]
+            dashboard: matches.subcommand_matches("dashboard").is_some(),
+            encryption: match matches.subcommand() {
+                Some(("encrypt", matches)) => Some(Encryption::new(true, matches)),
+                Some(("decrypt", matches)) => Some(Encryption::new(false, matches)),
+                _ => None,
+            },
+            #[cfg(feature = "simulation")]
+            simulate: matches.subcommand_matches("simulate").map(Simulate::new),
             all_days: matches.get_flag("AllDays"),
+            explain: matches.get_flag("Explain"),
+            checked: matches.get_flag("Checked"),
+            overrides: Overrides::default(),
             specific_day: if matches.contains_id("SpecificDay") {
                 *matches.get_one::<usize>("SpecificDay").unwrap()
             } else {
-                *Self::DAY_RANGE.end()
+                Self::SOLVED_DAYS
+                    .rev()
+                    .find(|&day| is_compiled(day))
+                    .unwrap_or(*Self::SOLVED_DAYS.end())
             },
-        })
+        };
+        config.overrides = config.overrides(
+            matches
+                .get_many::<String>("Param")
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+        )?;
+        Ok(config)
     }
 
-    const DAY_RANGE: RangeInclusive<usize> = 1..=15;
+    // Parameters in `aoc.toml`, in the current directory, are overridden in turn by `--param`.
+    // Parameters can be given without their day when only one day runs.
+    fn overrides<'a>(&self, params: impl Iterator<Item = &'a str>) -> Result<Overrides, String> {
+        let mut overrides = match fs::read_to_string("aoc.toml") {
+            Ok(toml) => Overrides::from_toml(&toml)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Overrides::default(),
+            Err(err) => return Err(format!("can't read aoc.toml: {err}")),
+        };
+
+        #[cfg(feature = "simulation")]
+        let simulated = self.simulate.as_ref().map(|simulate| simulate.day);
+        #[cfg(not(feature = "simulation"))]
+        let simulated = None;
+        let batched = self.batch.as_ref().and_then(|batch| batch.day);
+        let day = match (&self.generate, &self.compare, simulated.or(batched)) {
+            (Some(generate), _, _) => Some(generate.day),
+            (_, Some(compare), _) => Some(compare.day),
+            (_, _, Some(day)) => Some(day),
+            _ if self.batch.is_some() => None,
+            _ if self.all_days => None,
+            _ => Some(self.specific_day),
+        };
+        let mut given = Overrides::default();
+        for param in params {
+            given.add(param, day)?;
+        }
+        overrides.extend(given);
+
+        overrides.check(parameters)?;
+        Ok(overrides)
+    }
+
+    const DAY_RANGE: RangeInclusive<usize> = 1..=25;
+
+    // The days that have been solved, with a puzzle input, an input generator, and so on. The rest
+    // are stubs.
+    const SOLVED_DAYS: RangeInclusive<usize> = 1..=15;
 
     fn days_in_range(s: &str) -> Result<usize, String> {
         let days: usize = s
             .parse()
             .map_err(|_| format!("'{}' isn't a valid Advent of Code day for 2022!", s))?;
-        if Self::DAY_RANGE.contains(&days) {
+        if Self::DAY_RANGE.contains(&days) && !is_compiled(days) {
+            Err(format!(
+                "Day {days} isn't compiled in; build with the day-{days} feature"
+            ))
+        } else if Self::DAY_RANGE.contains(&days) {
             Ok(days)
         } else {
             Err(format!(
@@ -95,12 +522,845 @@ impl Config {
         }
     }
 
+    fn solved_days(s: &str) -> Result<usize, String> {
+        let day = Self::days_in_range(s)?;
+        if Self::SOLVED_DAYS.contains(&day) {
+            Ok(day)
+        } else {
+            Err(format!("Day {day} hasn't been solved yet"))
+        }
+    }
+
     pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
-        if self.all_days {
-            Self::DAY_RANGE.for_each(|day| run!(day));
+        let _checked = self.checked.then(checked::enable);
+        if let Some(generate) = &self.generate {
+            print!("{}", generate.run());
+            return Ok(());
+        }
+        if let Some(compare) = &self.compare {
+            return compare.run(&self.overrides);
+        }
+        if let Some(batch) = &self.batch {
+            return batch.run(&self.overrides);
+        }
+        if let Some(encryption) = &self.encryption {
+            return encryption.run();
+        }
+        #[cfg(feature = "dashboard")]
+        if self.dashboard {
+            return dashboard::run(&self.overrides);
+        }
+        #[cfg(feature = "simulation")]
+        if let Some(simulate) = &self.simulate {
+            return simulate.run(&self.overrides);
+        }
+
+        if !self.all_days && !is_compiled(self.specific_day) {
+            return Err("No days are compiled in".into());
+        } else if self.explain {
+            self.explain()?;
+        } else if self.all_days {
+            self.run_all_days()?;
         } else {
-            run!(self.specific_day);
+            let input = puzzle_input(self.specific_day)?;
+            match isolation::run(|| run_day(self.specific_day, &input, &self.overrides)) {
+                Outcome::Succeeded => {}
+                Outcome::Failed(failure) => return Err(failure.into()),
+                Outcome::Unimplemented => {
+                    return Err(format!("Day {} hasn't been solved yet", self.specific_day).into())
+                }
+            }
         }
         Ok(())
     }
+
+    // Every day is run, even once one has failed; failures are reported as they happen, and
+    // summed up at the end.
+    fn run_all_days(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut summary = Summary::default();
+        for day in Self::DAY_RANGE.filter(|&day| is_compiled(day)) {
+            let outcome = match puzzle_input(day) {
+                Ok(input) => isolation::run(|| run_day(day, &input, &self.overrides)),
+                Err(err) => Outcome::Failed(err.to_string()),
+            };
+            if let Outcome::Failed(failure) = &outcome {
+                eprintln!("Day {day} failed: {failure}");
+            }
+            summary.record(day, &outcome);
+        }
+        eprintln!("{summary}");
+
+        if summary.has_failures() {
+            Err("some days failed".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn explain(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let days = if self.all_days {
+            Self::DAY_RANGE
+        } else {
+            self.specific_day..=self.specific_day
+        };
+
+        let mut rejected = 0;
+        for day in days {
+            let input = puzzle_input(day)?;
+            let params = self.overrides.params(day, parameters(day));
+            let explanations = match explanations(day, &input, &params) {
+                Some(explanations) => explanations?,
+                None if self.all_days => continue,
+                None => return Err(format!("Day {day} can't explain its answers yet").into()),
+            };
+            println!("Day {day}");
+            for explanation in explanations {
+                println!("{explanation}");
+                rejected += usize::from(!explanation.is_verified());
+            }
+        }
+
+        match rejected {
+            0 => Ok(()),
+            1 => Err("1 witness was rejected".into()),
+            rejected => Err(format!("{rejected} witnesses were rejected").into()),
+        }
+    }
+}
+
+// Whether each day's solver is compiled in, with its `day-<N>` feature.
+const COMPILED: [bool; 25] = [
+    cfg!(feature = "day-1"),
+    cfg!(feature = "day-2"),
+    cfg!(feature = "day-3"),
+    cfg!(feature = "day-4"),
+    cfg!(feature = "day-5"),
+    cfg!(feature = "day-6"),
+    cfg!(feature = "day-7"),
+    cfg!(feature = "day-8"),
+    cfg!(feature = "day-9"),
+    cfg!(feature = "day-10"),
+    cfg!(feature = "day-11"),
+    cfg!(feature = "day-12"),
+    cfg!(feature = "day-13"),
+    cfg!(feature = "day-14"),
+    cfg!(feature = "day-15"),
+    cfg!(feature = "day-16"),
+    cfg!(feature = "day-17"),
+    cfg!(feature = "day-18"),
+    cfg!(feature = "day-19"),
+    cfg!(feature = "day-20"),
+    cfg!(feature = "day-21"),
+    cfg!(feature = "day-22"),
+    cfg!(feature = "day-23"),
+    cfg!(feature = "day-24"),
+    cfg!(feature = "day-25"),
+];
+
+fn is_compiled(day: usize) -> bool {
+    day.checked_sub(1).and_then(|idx| COMPILED.get(idx)) == Some(&true)
+}
+
+// The days whose puzzles have parameters that can be overridden.
+fn parameters(day: usize) -> &'static [Parameter] {
+    match day {
+        #[cfg(feature = "day-6")]
+        6 => day_6::PARAMETERS,
+        #[cfg(feature = "day-7")]
+        7 => day_7::PARAMETERS,
+        #[cfg(feature = "day-10")]
+        10 => day_10::PARAMETERS,
+        #[cfg(feature = "day-11")]
+        11 => day_11::PARAMETERS,
+        #[cfg(feature = "day-15")]
+        15 => day_15::PARAMETERS,
+        _ => &[],
+    }
+}
+
+// Every day's parameters and their defaults, for `--help`.
+fn parameters_help() -> String {
+    let mut help = "Override a puzzle parameter, given as day_<N>.<name>=<value>, or just \
+        <name>=<value> when one day runs. Overrides can also be given in aoc.toml, as [day_<N>] \
+        tables of <name> = <value>.\n\nThe parameters, and their defaults, are:"
+        .to_string();
+    for day in Config::DAY_RANGE {
+        for parameter in parameters(day) {
+            help.push_str(&format!("\n  day_{day}.{parameter}"));
+        }
+    }
+    help
+}
+
+// Only solved days have a puzzle input to load; the rest are stubs.
+fn puzzle_input(day: usize) -> Result<Cow<'static, str>, InputError> {
+    if Config::SOLVED_DAYS.contains(&day) && is_compiled(day) {
+        input::load(day)
+    } else {
+        Ok(Cow::Borrowed(""))
+    }
+}
+
+#[cfg_attr(not(feature = "all-days"), allow(unused_variables))]
+fn run_day(day: usize, input: &str, overrides: &Overrides) -> Result<(), ParseError> {
+    let params = overrides.params(day, parameters(day));
+    match day {
+        #[cfg(feature = "day-1")]
+        1 => day_1::run(input),
+        #[cfg(feature = "day-2")]
+        2 => day_2::run(input),
+        #[cfg(feature = "day-3")]
+        3 => day_3::run(input),
+        #[cfg(feature = "day-4")]
+        4 => day_4::run(input),
+        #[cfg(feature = "day-5")]
+        5 => day_5::run(input),
+        #[cfg(feature = "day-6")]
+        6 => day_6::run(input, &params),
+        #[cfg(feature = "day-7")]
+        7 => day_7::run(input, &params),
+        #[cfg(feature = "day-8")]
+        8 => day_8::run(input),
+        #[cfg(feature = "day-9")]
+        9 => day_9::run(input),
+        #[cfg(feature = "day-10")]
+        10 => day_10::run(input, &params),
+        #[cfg(feature = "day-11")]
+        11 => day_11::run(input, &params),
+        #[cfg(feature = "day-12")]
+        12 => day_12::run(input),
+        #[cfg(feature = "day-13")]
+        13 => day_13::run(input),
+        #[cfg(feature = "day-14")]
+        14 => day_14::run(input),
+        #[cfg(feature = "day-15")]
+        15 => day_15::run(input, &params),
+        #[cfg(feature = "day-16")]
+        16 => day_16::run(),
+        #[cfg(feature = "day-17")]
+        17 => day_17::run(),
+        #[cfg(feature = "day-18")]
+        18 => day_18::run(),
+        #[cfg(feature = "day-19")]
+        19 => day_19::run(),
+        #[cfg(feature = "day-20")]
+        20 => day_20::run(),
+        #[cfg(feature = "day-21")]
+        21 => day_21::run(),
+        #[cfg(feature = "day-22")]
+        22 => day_22::run(),
+        #[cfg(feature = "day-23")]
+        23 => day_23::run(),
+        #[cfg(feature = "day-24")]
+        24 => day_24::run(),
+        #[cfg(feature = "day-25")]
+        25 => day_25::run(),
+        _ => unreachable!(),
+    }
+}
+
+// The days whose solvers can back their answers up with witnesses.
+#[cfg_attr(not(feature = "all-days"), allow(unused_variables))]
+fn explanations(
+    day: usize,
+    input: &str,
+    params: &Params,
+) -> Option<Result<Vec<Explanation>, ParseError>> {
+    match day {
+        #[cfg(feature = "day-1")]
+        1 => Some(day_1::explain(input)),
+        #[cfg(feature = "day-6")]
+        6 => Some(day_6::explain(input, params)),
+        #[cfg(feature = "day-7")]
+        7 => Some(day_7::explain(input, params)),
+        #[cfg(feature = "day-13")]
+        13 => Some(day_13::explain(input)),
+        #[cfg(feature = "day-15")]
+        15 => Some(day_15::explain(input, params)),
+        _ => None,
+    }
+}
+
+fn solvable(day: usize) -> Result<(), ParseError> {
+    if !Config::SOLVED_DAYS.contains(&day) {
+        return Err(ParseError::new(format!(
+            "day {day} has not been solved yet"
+        )));
+    }
+    if !is_compiled(day) {
+        return Err(ParseError::new(format!("day {day} isn't compiled in")));
+    }
+    Ok(())
+}
+
+// Check that `input` is a well-formed puzzle input for `day`. Some days check that what it
+// describes is possible too -- that every crate moved is there to be moved, say.
+// This is the entry point for the fuzz targets, so it must never panic, whatever it is given.
+#[cfg_attr(not(feature = "all-days"), allow(unused_variables))]
+pub fn check_input(day: usize, input: &str) -> Result<(), ParseError> {
+    solvable(day)?;
+    day!(day => check_input(input))
+}
+
+// Solve one part of `day` for `input`, with the puzzle's own parameters, and return the answer as
+// it would be printed. This is the entry point for the FFI layer.
+pub fn solve(day: usize, part: usize, input: &str) -> Result<String, ParseError> {
+    solve_with(day, part, input, &Params::defaults(parameters(day)))
+}
+
+#[cfg_attr(not(feature = "all-days"), allow(unused_variables))]
+fn solve_with(day: usize, part: usize, input: &str, params: &Params) -> Result<String, ParseError> {
+    solvable(day)?;
+    if !(1..=2).contains(&part) {
+        return Err(ParseError::new(format!("day {day} has no part {part}")));
+    }
+    match day {
+        #[cfg(feature = "day-1")]
+        1 => day_1::solve(input, part),
+        #[cfg(feature = "day-2")]
+        2 => day_2::solve(input, part),
+        #[cfg(feature = "day-3")]
+        3 => day_3::solve(input, part),
+        #[cfg(feature = "day-4")]
+        4 => day_4::solve(input, part),
+        #[cfg(feature = "day-5")]
+        5 => day_5::solve(input, part),
+        #[cfg(feature = "day-6")]
+        6 => day_6::solve(input, part, params),
+        #[cfg(feature = "day-7")]
+        7 => day_7::solve(input, part, params),
+        #[cfg(feature = "day-8")]
+        8 => day_8::solve(input, part),
+        #[cfg(feature = "day-9")]
+        9 => day_9::solve(input, part),
+        #[cfg(feature = "day-10")]
+        10 => day_10::solve(input, part, params),
+        #[cfg(feature = "day-11")]
+        11 => day_11::solve(input, part, params),
+        #[cfg(feature = "day-12")]
+        12 => day_12::solve(input, part),
+        #[cfg(feature = "day-13")]
+        13 => day_13::solve(input, part),
+        #[cfg(feature = "day-14")]
+        14 => day_14::solve(input, part),
+        #[cfg(feature = "day-15")]
+        15 => day_15::solve(input, part, params),
+        _ => unreachable!(),
+    }
+}
+
+// Solve one part of `day` in isolation, so that a solver that panics is just another failure.
+fn solve_isolated(day: usize, part: usize, input: &str, params: &Params) -> Result<String, String> {
+    let mut answer = None;
+    let outcome = isolation::run(AssertUnwindSafe(|| {
+        answer = Some(solve_with(day, part, input, params));
+        Ok(())
+    }));
+    match (outcome, answer) {
+        (Outcome::Succeeded, Some(answer)) => answer.map_err(|err| err.to_string()),
+        (Outcome::Failed(failure), _) => Err(failure),
+        _ => Err("not implemented".to_string()),
+    }
+}
+
+impl Generate {
+    fn new(matches: &ArgMatches) -> Self {
+        Generate {
+            day: *matches.get_one::<usize>("Day").unwrap(),
+            seed: *matches.get_one::<u64>("Seed").unwrap(),
+            size: *matches.get_one::<usize>("Size").unwrap(),
+        }
+    }
+
+    #[cfg_attr(not(feature = "all-days"), allow(unused_variables))]
+    fn run(&self) -> String {
+        let rng = &mut Rng::new(self.seed);
+        day!(self.day => generate(rng, self.size))
+    }
+}
+
+impl Compare {
+    fn new(matches: &ArgMatches) -> Self {
+        Compare {
+            day: *matches.get_one::<usize>("Day").unwrap(),
+            program: matches.get_one::<String>("Cmd").unwrap().clone(),
+        }
+    }
+
+    fn run(&self, overrides: &Overrides) -> Result<(), Box<dyn std::error::Error>> {
+        let input = puzzle_input(self.day)?;
+        let params = overrides.params(self.day, parameters(self.day));
+        let comparison = compare::compare(self.day, &self.program, &input, &params)?;
+        println!("{comparison}");
+        if comparison.agrees() {
+            Ok(())
+        } else {
+            Err("The answers differ".into())
+        }
+    }
+}
+
+impl Batch {
+    fn new(matches: &ArgMatches) -> Self {
+        Batch {
+            day: matches.get_one::<usize>("Day").copied(),
+            dir: matches.get_one::<PathBuf>("Inputs").unwrap().clone(),
+        }
+    }
+
+    fn run(&self, overrides: &Overrides) -> Result<(), Box<dyn std::error::Error>> {
+        let days = self.day.map_or(Config::SOLVED_DAYS, |day| day..=day);
+        let mut results = batch::Results::default();
+        for day in days.filter(|&day| is_compiled(day)) {
+            let params = overrides.params(day, parameters(day));
+            for path in batch::inputs(&self.dir, day)? {
+                results.push(batch::run(day, &path, &params)?);
+            }
+        }
+        if results.is_empty() {
+            return Err(format!("There are no inputs in {}", self.dir.display()).into());
+        }
+
+        print!("{results}");
+        match results.went_wrong() {
+            0 => Ok(()),
+            1 => Err("1 input went wrong".into()),
+            wrong => Err(format!("{wrong} of {} inputs went wrong", results.len()).into()),
+        }
+    }
+}
+
+impl Encryption {
+    fn command(name: &'static str) -> Command {
+        Command::new(name)
+            .arg(
+                Arg::new("Day")
+                    .long("day")
+                    .short('d')
+                    .num_args(1)
+                    .help("Only this day's input, rather than every input there is")
+                    .value_parser(value_parser!(u8).range(1..=25)),
+            )
+            .arg(
+                Arg::new("Inputs")
+                    .long("inputs")
+                    .num_args(1)
+                    .value_name("DIR")
+                    .default_value("src/input")
+                    .value_parser(value_parser!(PathBuf)),
+            )
+    }
+
+    fn new(encrypt: bool, matches: &ArgMatches) -> Self {
+        Encryption {
+            encrypt,
+            day: matches.get_one::<u8>("Day").copied().map(usize::from),
+            dir: matches.get_one::<PathBuf>("Inputs").unwrap().clone(),
+        }
+    }
+
+    fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let passphrase = input::passphrase().ok_or_else(|| {
+            format!(
+                "Set {} to the passphrase to {} the inputs with",
+                input::KEY_VARIABLE,
+                if self.encrypt { "encrypt" } else { "decrypt" }
+            )
+        })?;
+        let days = self.day.map_or(Config::DAY_RANGE, |day| day..=day);
+        for day in days {
+            let from = self.dir.join(if self.encrypt {
+                format!("day_{day}.txt")
+            } else {
+                format!("day_{day}.txt.enc")
+            });
+            if !from.exists() {
+                match self.day {
+                    Some(_) => return Err(format!("There's no {}", from.display()).into()),
+                    None => continue,
+                }
+            }
+            if !self.encrypt {
+                input::decrypt_file(&self.dir, day, &passphrase)?;
+                println!("Decrypted {}", from.display());
+            } else if input::encrypt_file(&self.dir, day, &passphrase)? {
+                println!("Encrypted {}", from.display());
+            } else {
+                println!("{} is already encrypted", from.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "simulation")]
+impl Simulate {
+    const DAYS: [usize; 5] = [9, 10, 11, 12, 14];
+
+    // Long simulations are sampled down to this many frames when they're recorded.
+    #[cfg_attr(not(feature = "all-days"), allow(dead_code))]
+    const MAX_FRAMES: usize = 250;
+
+    fn simulated_days(s: &str) -> Result<usize, String> {
+        let day = Config::days_in_range(s)?;
+        if Self::DAYS.contains(&day) {
+            Ok(day)
+        } else {
+            Err(format!("Only days {:?} are simulations", Self::DAYS))
+        }
+    }
+
+    fn command() -> Command {
+        let command = Command::new("simulate")
+            .about("Run a day's simulation, showing its state at the end, or at a given step")
+            .arg(
+                Arg::new("Day")
+                    .long("day")
+                    .short('d')
+                    .num_args(1)
+                    .required(true)
+                    .value_parser(Simulate::simulated_days),
+            )
+            .arg(
+                Arg::new("Part")
+                    .long("part")
+                    .short('p')
+                    .num_args(1)
+                    .default_value("1")
+                    .value_parser(value_parser!(u8).range(1..=2)),
+            )
+            .arg(
+                Arg::new("StopAt")
+                    .long("stop_at")
+                    .short('n')
+                    .num_args(1)
+                    .conflicts_with("Step")
+                    .value_parser(value_parser!(usize)),
+            )
+            .arg(
+                Arg::new("Step")
+                    .long("step")
+                    .short('s')
+                    .num_args(0)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("Animate")
+                    .long("animate")
+                    .num_args(0)
+                    .help("Play the simulation in the terminal")
+                    .conflicts_with_all(["StopAt", "Step"])
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("Fps")
+                    .long("fps")
+                    .num_args(1)
+                    .default_value("20")
+                    .requires("Animate")
+                    .help("Frames per second to animate at")
+                    .value_parser(value_parser!(u32).range(1..)),
+            );
+        #[cfg(feature = "visualization")]
+        let command = command.arg(
+            Arg::new("Visualize")
+                .long("visualize")
+                .num_args(1)
+                .value_name("FILE")
+                .help("Record the simulation as a .gif, an animated .png, or .ppm frames")
+                .conflicts_with_all(["StopAt", "Step", "Animate"])
+                .value_parser(value_parser!(PathBuf)),
+        );
+        command
+    }
+
+    fn new(matches: &ArgMatches) -> Self {
+        Simulate {
+            day: *matches.get_one::<usize>("Day").unwrap(),
+            part: usize::from(*matches.get_one::<u8>("Part").unwrap()),
+            playback: if matches.get_flag("Step") {
+                Playback::Step
+            } else if let Some(&stop) = matches.get_one::<usize>("StopAt") {
+                Playback::StopAt(stop)
+            } else {
+                Playback::Run
+            },
+            #[cfg(feature = "visualization")]
+            visualize: matches.get_one::<PathBuf>("Visualize").cloned(),
+            animate: matches
+                .get_flag("Animate")
+                .then(|| *matches.get_one::<u32>("Fps").unwrap()),
+        }
+    }
+
+    #[cfg_attr(not(feature = "all-days"), allow(unreachable_code, unused_variables))]
+    fn run(&self, overrides: &Overrides) -> Result<(), Box<dyn std::error::Error>> {
+        let params = overrides.params(self.day, parameters(self.day));
+        let input = input::load(self.day)?;
+        #[cfg(feature = "visualization")]
+        if let Some(path) = &self.visualize {
+            return self.record(path, &input, &params);
+        }
+
+        match self.day {
+            #[cfg(feature = "day-9")]
+            9 => self.play(&mut day_9::simulation(&input, self.part)?)?,
+            #[cfg(feature = "day-10")]
+            10 => self.play(&mut day_10::simulation(&input, self.part, &params)?)?,
+            #[cfg(feature = "day-11")]
+            11 => self.play(&mut day_11::simulation(&input, self.part, &params)?)?,
+            #[cfg(feature = "day-12")]
+            12 => self.play(&mut day_12::simulation(&input, self.part)?)?,
+            #[cfg(feature = "day-14")]
+            14 => self.play(&mut day_14::simulation(&input, self.part)?)?,
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "all-days"), allow(dead_code))]
+    fn play<S: simulation::Simulation>(&self, simulation: &mut S) -> io::Result<()> {
+        match self.animate {
+            Some(fps) => animate::play(simulation, fps),
+            None => simulation::play(
+                simulation,
+                self.playback,
+                &mut io::stdin().lock(),
+                &mut io::stdout().lock(),
+            ),
+        }
+    }
+
+    #[cfg(feature = "visualization")]
+    #[cfg_attr(not(feature = "all-days"), allow(unreachable_code, unused_variables))]
+    fn record(
+        &self,
+        path: &Path,
+        input: &str,
+        params: &Params,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let animation: visualize::Animation = match self.day {
+            #[cfg(feature = "day-9")]
+            9 => visualize::record(day_9::simulation(input, self.part)?, Self::MAX_FRAMES),
+            #[cfg(feature = "day-10")]
+            10 => visualize::record(
+                day_10::simulation(input, self.part, params)?,
+                Self::MAX_FRAMES,
+            ),
+            #[cfg(feature = "day-12")]
+            12 => visualize::record(day_12::simulation(input, self.part)?, Self::MAX_FRAMES),
+            #[cfg(feature = "day-14")]
+            14 => visualize::record(day_14::simulation(input, self.part)?, Self::MAX_FRAMES),
+            day => return Err(format!("Day {day}'s simulation can't be visualized").into()),
+        };
+        animation.save(path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    // Inputs that once made a parser panic, kept so that they never do again.
+    const REGRESSIONS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/regressions");
+
+    // What each of them is rejected with, so that a parser that comes to accept one, or to reject it
+    // for some other reason, is caught too.
+    const REGRESSION_ERRORS: &[(usize, &str, &str)] = &[
+        (1, "empty", "there are no elves in the input"),
+        (1, "not_a_number", "'2x00' is not a valid number"),
+        (2, "missing_space", "expected ' ' in 'A'"),
+        (2, "unknown_shape", "'A W' is not a valid round"),
+        (3, "no_badge", "'aa''s group has no badge"),
+        (
+            3,
+            "non_letter",
+            "'ab1c' contains something other than a letter",
+        ),
+        (
+            3,
+            "odd_length",
+            "'abc' can't be split into two equal compartments",
+        ),
+        (
+            4,
+            "backwards_area",
+            "line 1, column 5: the area 6-5 ends before it starts",
+        ),
+        (
+            4,
+            "missing_comma",
+            "line 1, column 4: expected ',', found the end of the line",
+        ),
+        (
+            4,
+            "missing_dash",
+            "line 1, column 2: expected '-', found ',6-8'",
+        ),
+        (
+            5,
+            "no_stack_numbers",
+            "the drawing has no row of stack numbers",
+        ),
+        (
+            5,
+            "stack_zero",
+            "line 4, column 1: Rearrangement { number: 1, origin: 0, destination: 1 } refers to a \
+             stack that isn't in the drawing",
+        ),
+        (
+            5,
+            "take_too_many",
+            "Rearrangement { number: 2, origin: 1, destination: 2 } moves more crates than there \
+             are on the stack",
+        ),
+        (6, "empty", "there are no 4 unique characters in a row"),
+        (6, "no_marker", "there are no 14 unique characters in a row"),
+        (
+            7,
+            "bad_size",
+            "line 3, column 1: expected '$ cd ' or '$ ls' or 'dir ' or a number, found 'big a'",
+        ),
+        (
+            7,
+            "no_root",
+            "line 1, column 1: expected '$ cd /', found '$ ls'",
+        ),
+        (8, "empty", "the grid should not be empty"),
+        (8, "non_digit", "'x' is not a tree height"),
+        (8, "ragged", "the grid should be rectangular"),
+        (9, "huge_distance", "'99999999999' is not a valid number"),
+        (9, "no_distance", "expected ' ' in 'R'"),
+        (
+            10,
+            "addx_without_value",
+            "line 1, column 1: expected 'noop' or 'addx ', found 'addx'",
+        ),
+        (10, "x_overflow", "line 1, column 1: this overflows x"),
+        (
+            11,
+            "divide_by_zero",
+            "line 4, column 1: a monkey can't test divisibility by 0",
+        ),
+        (
+            11,
+            "truncated",
+            "line 3, column 1: expected '  Operation: new = old ', found the end of the input",
+        ),
+        (12, "no_start", "the heightmap should have exactly one 'S'"),
+        (12, "ragged", "the grid should be rectangular"),
+        (
+            13,
+            "not_a_number",
+            "line 2, column 2: expected ']', found 'a]'",
+        ),
+        (
+            13,
+            "unpaired",
+            "line 2, column 1: expected '[', found the end of the input",
+        ),
+        (14, "diagonal", "rock paths should only be straight lines"),
+        (14, "empty", "the scan should have at least one rock"),
+        (14, "out_of_cave", "0,4 is out of reach of the sand"),
+        (
+            15,
+            "overflow",
+            "line 1, column 13: '9223372036854775807' is not a valid number",
+        ),
+        (
+            15,
+            "truncated",
+            "line 1, column 20: expected ': closest beacon is at ', found the end of the line",
+        ),
+    ];
+
+    #[test]
+    fn check_input_rejects_unsolved_days() {
+        assert!(check_input(0, "").is_err());
+        assert!(check_input(16, "").is_err());
+    }
+
+    #[cfg(any_solved_day)]
+    #[test]
+    fn check_input_accepts_generated_inputs() {
+        for day in Config::SOLVED_DAYS.filter(|&day| is_compiled(day)) {
+            let input: String = day!(day => generate(&mut Rng::new(day as u64), 10));
+            assert_eq!(check_input(day, &input), Ok(()), "day {day}");
+        }
+    }
+
+    // A cheap, always-on stand-in for the fuzz targets: mangle generated inputs, and throw random
+    // bytes at every parser.
+    #[cfg(any_solved_day)]
+    #[test]
+    fn check_input_never_panics() {
+        let rng = &mut Rng::new(28);
+        for day in Config::SOLVED_DAYS.filter(|&day| is_compiled(day)) {
+            for _ in 0..200 {
+                let size = rng.range(1..=10);
+                let mut input = String::into_bytes(day!(day => generate(rng, size)));
+                for _ in 0..rng.range(1..=4) {
+                    let idx = rng.range(0..=input.len() - 1);
+                    match rng.range(0..=2) {
+                        0 => input[idx] = *rng.choose(b"0123456789 -,=:[]\nabcSExyz"),
+                        1 => {
+                            input.remove(idx);
+                        }
+                        _ => input.truncate(idx),
+                    }
+                    if input.is_empty() {
+                        break;
+                    }
+                }
+                let _ = check_input(day, &String::from_utf8_lossy(&input));
+            }
+
+            for _ in 0..200 {
+                let input = (0..rng.range(0..=64))
+                    .map(|_| rng.letter(b' '..=b'~'))
+                    .collect::<String>();
+                let _ = check_input(day, &input);
+            }
+        }
+    }
+
+    #[test]
+    fn check_input_regressions() {
+        for day in Config::SOLVED_DAYS.filter(|&day| is_compiled(day)) {
+            let directory = Path::new(REGRESSIONS).join(format!("day_{day}"));
+            let mut names = fs::read_dir(&directory)
+                .map(|entries| {
+                    entries
+                        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            names.sort();
+            let expected = REGRESSION_ERRORS
+                .iter()
+                .filter(|&&(regression_day, ..)| regression_day == day)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                names,
+                expected
+                    .iter()
+                    .map(|&&(_, name, _)| name)
+                    .collect::<Vec<_>>(),
+                "day {day}'s regressions should each have an error to expect"
+            );
+
+            for &&(_, name, error) in &expected {
+                let input = fs::read(directory.join(name)).unwrap();
+                assert_eq!(
+                    check_input(day, &String::from_utf8_lossy(&input))
+                        .map_err(|err| err.to_string()),
+                    Err(format!("Invalid puzzle input: {error}")),
+                    "day_{day}/{name}"
+                );
+            }
+        }
+    }
 }
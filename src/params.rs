@@ -0,0 +1,279 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+// Some days have constants that are part of the puzzle rather than the solution: the row day 15
+// counts, how many rounds the monkeys play in day 11, the size of day 10's screen. Each such day
+// declares them as named parameters, with defaults matching the puzzle, which can be overridden in
+// `aoc.toml`:
+//
+//     [day_15]
+//     row = 10
+//     region = 20
+//
+// or with `--param day_15.row=10` (or just `--param row=10`, when running a single day), which
+// takes precedence. That way the puzzle's examples, and any what-ifs, run without changing code.
+
+// Every parameter is an integer, no smaller than its minimum and no larger than its maximum, if it
+// has one: the largest the day's arithmetic can cope with. Most of the puzzles' examples are
+// solved with the same parameters as the puzzles themselves, but not all of them.
+#[derive(Debug)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub default: i64,
+    pub min: i64,
+    pub max: i64,
+    pub description: &'static str,
+    pub example: i64,
+}
+
+impl Parameter {
+    pub const fn new(
+        name: &'static str,
+        default: i64,
+        min: i64,
+        description: &'static str,
+    ) -> Self {
+        Parameter {
+            name,
+            default,
+            min,
+            max: i64::MAX,
+            description,
+            example: default,
+        }
+    }
+
+    pub const fn at_most(mut self, max: i64) -> Self {
+        self.max = max;
+        self
+    }
+
+    // The value to solve the puzzle's example with, when it isn't the default.
+    pub const fn example(mut self, example: i64) -> Self {
+        self.example = example;
+        self
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} = {}", self.name, self.default)?;
+        match (self.min > i64::MIN, self.max < i64::MAX) {
+            (true, true) => write!(f, " (from {} to {})", self.min, self.max)?,
+            (true, false) => write!(f, " (at least {})", self.min)?,
+            (false, true) => write!(f, " (at most {})", self.max)?,
+            (false, false) => {}
+        }
+        write!(f, ": {}", self.description)
+    }
+}
+
+// The values of a day's parameters, once any overrides have been applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Params(Vec<(&'static str, i64)>);
+
+impl Params {
+    pub fn defaults(declared: &[Parameter]) -> Self {
+        Params(
+            declared
+                .iter()
+                .map(|parameter| (parameter.name, parameter.default))
+                .collect(),
+        )
+    }
+
+    #[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+    pub fn examples(declared: &[Parameter]) -> Self {
+        Params(
+            declared
+                .iter()
+                .map(|parameter| (parameter.name, parameter.example))
+                .collect(),
+        )
+    }
+
+    // Asking for a parameter the day never declared, or in a type its bounds don't guarantee it fits
+    // in, is a bug in the day, not in the overrides.
+    pub fn get<T: TryFrom<i64>>(&self, name: &str) -> T {
+        let &(_, value) = self
+            .0
+            .iter()
+            .find(|&&(declared, _)| declared == name)
+            .unwrap_or_else(|| panic!("there is no parameter '{name}'"));
+        T::try_from(value)
+            .unwrap_or_else(|_| panic!("'{name}' = {value} doesn't fit in the type it's used as"))
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Overrides(BTreeMap<(usize, String), i64>);
+
+impl Overrides {
+    // A table for each day with parameters to override, named `day_<N>`.
+    pub fn from_toml(toml: &str) -> Result<Self, String> {
+        let table = toml
+            .parse::<toml::Table>()
+            .map_err(|err| format!("aoc.toml isn't valid TOML: {}", err.message()))?;
+
+        let mut overrides = Overrides::default();
+        for (section, parameters) in table {
+            let day = day(&section)?;
+            let parameters = parameters
+                .as_table()
+                .ok_or_else(|| format!("'{section}' should be a table of parameters"))?;
+            for (name, value) in parameters {
+                let value = value
+                    .as_integer()
+                    .ok_or_else(|| format!("{section}.{name} should be an integer"))?;
+                overrides.0.insert((day, name.clone()), value);
+            }
+        }
+        Ok(overrides)
+    }
+
+    // `day_<N>.<name>=<value>`, or just `<name>=<value>` for the given day, if there is one.
+    pub fn add(&mut self, assignment: &str, day: Option<usize>) -> Result<(), String> {
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("'{assignment}' should be <name>=<value>"))?;
+        let (day, name) = match key.split_once('.') {
+            Some((section, name)) => (self::day(section)?, name),
+            None => (
+                day.ok_or_else(|| {
+                    format!("'{key}' needs a day when more than one day runs, e.g. day_15.{key}")
+                })?,
+                key,
+            ),
+        };
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{value}' isn't an integer, for day_{day}.{name}"))?;
+        self.0.insert((day, name.trim().to_string()), value);
+        Ok(())
+    }
+
+    // Later overrides take precedence over earlier ones.
+    pub fn extend(&mut self, overrides: Overrides) {
+        self.0.extend(overrides.0);
+    }
+
+    // Check every override against what its day declares, before any day runs.
+    pub fn check(&self, declared: impl Fn(usize) -> &'static [Parameter]) -> Result<(), String> {
+        for ((day, name), value) in &self.0 {
+            let parameter = declared(*day)
+                .iter()
+                .find(|parameter| parameter.name == name)
+                .ok_or_else(|| format!("day {day} has no parameter '{name}'"))?;
+            if *value < parameter.min {
+                return Err(format!(
+                    "day_{day}.{name} should be at least {}, not {value}",
+                    parameter.min
+                ));
+            }
+            if *value > parameter.max {
+                return Err(format!(
+                    "day_{day}.{name} should be at most {}, not {value}",
+                    parameter.max
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn params(&self, day: usize, declared: &[Parameter]) -> Params {
+        let mut params = Params::defaults(declared);
+        for (name, value) in &mut params.0 {
+            if let Some(&overridden) = self.0.get(&(day, name.to_string())) {
+                *value = overridden;
+            }
+        }
+        params
+    }
+}
+
+fn day(section: &str) -> Result<usize, String> {
+    section
+        .strip_prefix("day_")
+        .and_then(|day| day.parse().ok())
+        .ok_or_else(|| format!("'{section}' should name a day, e.g. day_15"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECLARED: &[Parameter] = &[
+        Parameter::new("row", 2_000_000, i64::MIN, "the row to count").example(10),
+        Parameter::new("size", 4_000_000, 0, "how far to search")
+            .at_most(i32::MAX as i64)
+            .example(20),
+        Parameter::new("rounds", 20, 0, "how many rounds to play"),
+    ];
+
+    fn declared(day: usize) -> &'static [Parameter] {
+        if day == 15 {
+            DECLARED
+        } else {
+            &[]
+        }
+    }
+
+    #[test]
+    fn params_default_and_override() {
+        let mut overrides = Overrides::from_toml("[day_15]\nrow = 10\nrounds = 3\n").unwrap();
+        overrides.add("day_15.rounds=4", None).unwrap();
+        overrides.add("row = -1", Some(15)).unwrap();
+        assert_eq!(overrides.check(declared), Ok(()));
+
+        let params = overrides.params(15, DECLARED);
+        assert_eq!(params.get::<i64>("row"), -1);
+        assert_eq!(params.get::<usize>("rounds"), 4);
+        assert_eq!(Params::defaults(DECLARED).get::<i64>("row"), 2_000_000);
+        assert_eq!(Params::examples(DECLARED).get::<i64>("row"), 10);
+        assert_eq!(Params::examples(DECLARED).get::<usize>("rounds"), 20);
+        assert_eq!(DECLARED[0].to_string(), "row = 2000000: the row to count");
+        assert_eq!(
+            DECLARED[1].to_string(),
+            "size = 4000000 (from 0 to 2147483647): how far to search"
+        );
+        assert_eq!(
+            DECLARED[2].to_string(),
+            "rounds = 20 (at least 0): how many rounds to play"
+        );
+        assert_eq!(
+            Overrides::default().params(15, DECLARED),
+            Params::defaults(DECLARED)
+        );
+    }
+
+    #[test]
+    fn params_reject_bad_overrides() {
+        let check = |toml: &str, assignment: &str, day| {
+            let mut overrides = Overrides::from_toml(toml)?;
+            overrides.add(assignment, day)?;
+            overrides.check(declared)
+        };
+        assert_eq!(
+            check("", "day_15.rounds=-1", None),
+            Err("day_15.rounds should be at least 0, not -1".to_string())
+        );
+        assert_eq!(
+            check("", "day_15.size=2147483648", None),
+            Err("day_15.size should be at most 2147483647, not 2147483648".to_string())
+        );
+        assert_eq!(
+            check("[day_3]\nrow = 1", "row=1", Some(15)),
+            Err("day 3 has no parameter 'row'".to_string())
+        );
+        assert!(check("", "rows=1", Some(15)).is_err());
+        assert!(check("", "row=1", None).is_err());
+        assert!(check("", "day_15.row=ten", None).is_err());
+        assert!(check("", "day_15.row", None).is_err());
+        assert!(check("[day_15]\nrow = 'ten'", "row=1", Some(15)).is_err());
+        assert!(check("[fifteen]\nrow = 1", "row=1", Some(15)).is_err());
+        assert!(check("row = 1", "row=1", Some(15)).is_err());
+    }
+}
@@ -0,0 +1,439 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::fmt;
+use std::str::FromStr;
+
+// Puzzle inputs are hand-copied text, so the parsers report malformed input as an error, rather
+// than panicking part-way through.
+//
+// Simple inputs are picked apart with the helpers below; anything with more structure is built out
+// of the parsers further down, which say where in the input they went wrong.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+    location: Option<Location>,
+    // When something else should have come next, what could have: any of them would have done.
+    expected: Vec<String>,
+}
+
+// Lines and columns are counted from 1, and columns in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Location {
+    line: usize,
+    column: usize,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ParseError {
+            message: message.into(),
+            location: None,
+            expected: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid puzzle input: ")?;
+        if let Some(Location { line, column }) = self.location {
+            write!(f, "line {line}, column {column}: ")?;
+        }
+        if !self.expected.is_empty() {
+            write!(f, "expected {}, ", self.expected.join(" or "))?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn number<T: FromStr>(input: &str) -> Result<T, ParseError> {
+    input
+        .parse::<T>()
+        .map_err(|_| ParseError::new(format!("'{input}' is not a valid number")))
+}
+
+pub fn split_once<'a>(input: &'a str, delimiter: &str) -> Result<(&'a str, &'a str), ParseError> {
+    input
+        .split_once(delimiter)
+        .ok_or_else(|| ParseError::new(format!("expected '{delimiter}' in '{input}'")))
+}
+
+// Where a parser has got to in the input, and so where an error it finds is.
+#[derive(Clone, Copy, Debug)]
+pub struct Input<'a> {
+    source: &'a str,
+    offset: usize,
+    location: Location,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Input {
+            source,
+            offset: 0,
+            location: Location { line: 1, column: 1 },
+        }
+    }
+
+    // What's still to be parsed.
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    fn advance(&mut self, length: usize) {
+        for c in self.rest()[..length].chars() {
+            if c == '\n' {
+                self.location.line += 1;
+                self.location.column = 1;
+            } else {
+                self.location.column += 1;
+            }
+        }
+        self.offset += length;
+    }
+
+    // Move past `text`, if that's what comes next.
+    fn skip(&mut self, text: &str) -> bool {
+        let next = self.rest().starts_with(text);
+        if next {
+            self.advance(text.len());
+        }
+        next
+    }
+
+    // Move past a line break, if that's what comes next.
+    fn skip_line_break(&mut self) -> bool {
+        self.skip("\n") || self.skip("\r\n")
+    }
+
+    // An error found here.
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            location: Some(self.location),
+            ..ParseError::new(message)
+        }
+    }
+
+    // An error for when `what` should have come next, but didn't.
+    fn expected(&self, what: impl Into<String>) -> ParseError {
+        let rest = self.rest();
+        let line = rest.lines().next().unwrap_or_default();
+        let found = if rest.is_empty() {
+            "the end of the input".to_string()
+        } else if line.is_empty() {
+            "the end of the line".to_string()
+        } else {
+            format!("'{line}'")
+        };
+        ParseError {
+            expected: vec![what.into()],
+            ..self.error(format!("found {found}"))
+        }
+    }
+}
+
+// A parser reads something from the front of the input, and moves past it. One that fails leaves
+// the input wherever it got to, so the parsers that try several things go back to where they
+// started first.
+pub trait Parser<'a, T>: FnMut(&mut Input<'a>) -> Result<T, ParseError> {}
+
+impl<'a, T, F: FnMut(&mut Input<'a>) -> Result<T, ParseError>> Parser<'a, T> for F {}
+
+// Parse the whole of `source`: only whitespace can be left over.
+pub fn all<'a, T>(source: &'a str, mut parser: impl Parser<'a, T>) -> Result<T, ParseError> {
+    let mut input = Input::new(source);
+    let parsed = parser(&mut input)?;
+    let leftover = input.rest().trim_start();
+    input.advance(input.rest().len() - leftover.len());
+    if leftover.is_empty() {
+        Ok(parsed)
+    } else {
+        Err(input.expected("the end of the input"))
+    }
+}
+
+pub fn literal<'a>(text: &'static str) -> impl Parser<'a, ()> {
+    move |input: &mut Input<'a>| {
+        if input.skip(text) {
+            Ok(())
+        } else {
+            Err(input.expected(format!("'{text}'")))
+        }
+    }
+}
+
+// A whole number, which can be negative if `T` can be.
+pub fn integer<'a, T: FromStr>() -> impl Parser<'a, T> {
+    move |input: &mut Input<'a>| {
+        let rest = input.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let digits = rest[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len() - sign);
+        if digits == 0 {
+            return Err(input.expected("a number"));
+        }
+        let number = &rest[..sign + digits];
+        let parsed = number
+            .parse()
+            .map_err(|_| input.error(format!("'{number}' is not a valid number")))?;
+        input.advance(number.len());
+        Ok(parsed)
+    }
+}
+
+// Everything up to the end of the line, which could be nothing.
+pub fn rest_of_line<'a>() -> impl Parser<'a, &'a str> {
+    move |input: &mut Input<'a>| {
+        let rest = input.rest();
+        let line = rest.lines().next().unwrap_or_default();
+        input.advance(line.len());
+        Ok(line)
+    }
+}
+
+pub fn map<'a, T, U>(
+    mut parser: impl Parser<'a, T>,
+    mut f: impl FnMut(T) -> U,
+) -> impl Parser<'a, U> {
+    move |input: &mut Input<'a>| parser(input).map(&mut f)
+}
+
+// `parser`, after `prefix`.
+pub fn preceded<'a, T>(prefix: &'static str, mut parser: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    let mut prefix = literal(prefix);
+    move |input: &mut Input<'a>| {
+        prefix(input)?;
+        parser(input)
+    }
+}
+
+// `parser`, followed by `suffix`.
+pub fn terminated<'a, T>(
+    mut parser: impl Parser<'a, T>,
+    suffix: &'static str,
+) -> impl Parser<'a, T> {
+    let mut suffix = literal(suffix);
+    move |input: &mut Input<'a>| {
+        let parsed = parser(input)?;
+        suffix(input)?;
+        Ok(parsed)
+    }
+}
+
+pub fn pair<'a, A, B>(
+    mut first: impl Parser<'a, A>,
+    mut second: impl Parser<'a, B>,
+) -> impl Parser<'a, (A, B)> {
+    move |input: &mut Input<'a>| Ok((first(input)?, second(input)?))
+}
+
+// Whichever of the two parsers works, trying `first` first. If neither does, the error is the one
+// from the parser that got further -- or, if both failed straight away, one listing what either
+// was expecting.
+pub fn either<'a, T>(
+    mut first: impl Parser<'a, T>,
+    mut second: impl Parser<'a, T>,
+) -> impl Parser<'a, T> {
+    move |input: &mut Input<'a>| {
+        let start = *input;
+        let mut first_error = match first(input) {
+            Ok(parsed) => return Ok(parsed),
+            Err(err) => err,
+        };
+        *input = start;
+        let second_error = match second(input) {
+            Ok(parsed) => return Ok(parsed),
+            Err(err) => err,
+        };
+        Err(match first_error.location.cmp(&second_error.location) {
+            std::cmp::Ordering::Greater => first_error,
+            std::cmp::Ordering::Equal
+                if !first_error.expected.is_empty() && !second_error.expected.is_empty() =>
+            {
+                first_error.expected.extend(second_error.expected);
+                first_error
+            }
+            _ => second_error,
+        })
+    }
+}
+
+// `parser`, if it's there: if it fails before reading anything, it isn't, and nothing is read. If
+// it fails part-way through, that's still an error.
+pub fn optional<'a, T>(mut parser: impl Parser<'a, T>) -> impl Parser<'a, Option<T>> {
+    move |input: &mut Input<'a>| {
+        let start = *input;
+        match parser(input) {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(err) if err.location == Some(start.location) => {
+                *input = start;
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+// One or more of `item`, with `separator` between each of them.
+pub fn separated<'a, T>(
+    mut item: impl Parser<'a, T>,
+    separator: &'static str,
+) -> impl Parser<'a, Vec<T>> {
+    move |input: &mut Input<'a>| {
+        let mut items = vec![item(input)?];
+        while input.skip(separator) {
+            items.push(item(input)?);
+        }
+        Ok(items)
+    }
+}
+
+// `parser`, taking up the rest of the line, and the line break at the end of it.
+pub fn line<'a, T>(mut parser: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    move |input: &mut Input<'a>| {
+        let parsed = parser(input)?;
+        if input.skip_line_break() || input.rest().is_empty() {
+            Ok(parsed)
+        } else {
+            Err(input.expected("the end of the line"))
+        }
+    }
+}
+
+// A block of lines, each read by `parser`, up to the end of the input or the next blank line.
+pub fn lines<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    let mut line = line(parser);
+    move |input: &mut Input<'a>| {
+        let mut lines = Vec::new();
+        while !input.rest().is_empty() && !at_blank_line(input) {
+            lines.push(line(input)?);
+        }
+        Ok(lines)
+    }
+}
+
+// Blocks of lines read by `parser`, separated by blank lines.
+pub fn blocks<'a, T>(mut parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |input: &mut Input<'a>| {
+        let mut blocks = vec![parser(input)?];
+        while at_blank_line(input) {
+            while input.skip_line_break() {}
+            if input.rest().is_empty() {
+                break;
+            }
+            blocks.push(parser(input)?);
+        }
+        Ok(blocks)
+    }
+}
+
+// One or more blank lines, between blocks.
+pub fn blank_lines<'a>() -> impl Parser<'a, ()> {
+    move |input: &mut Input<'a>| {
+        if !at_blank_line(input) {
+            return Err(input.expected("a blank line"));
+        }
+        while input.skip_line_break() {}
+        Ok(())
+    }
+}
+
+fn at_blank_line(input: &Input) -> bool {
+    let rest = input.rest();
+    rest.starts_with('\n') || rest.starts_with("\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_helpers() {
+        assert_eq!(number::<u8>("42"), Ok(42));
+        assert!(number::<u8>("256").is_err());
+        assert_eq!(split_once("1-2", "-"), Ok(("1", "2")));
+        assert!(split_once("12", "-").is_err());
+    }
+
+    fn point<'a>() -> impl Parser<'a, (i32, i32)> {
+        pair(preceded("x=", integer()), preceded(", y=", integer()))
+    }
+
+    #[test]
+    fn parse_combinators() {
+        assert_eq!(all("x=3, y=-4", point()), Ok((3, -4)));
+        assert_eq!(
+            all(
+                "[1,2,3]",
+                preceded("[", terminated(separated(integer::<u8>(), ","), "]"))
+            ),
+            Ok(vec![1, 2, 3])
+        );
+        assert_eq!(
+            all(
+                "1 2\n3\n\n\n4\n",
+                blocks(lines(separated(integer::<u8>(), " ")))
+            ),
+            Ok(vec![vec![vec![1, 2], vec![3]], vec![vec![4]]])
+        );
+        let mut instruction = either(
+            map(literal("noop"), |_| None),
+            map(preceded("addx ", integer::<i32>()), Some),
+        );
+        assert_eq!(
+            all("noop\naddx -2", lines(&mut instruction)),
+            Ok(vec![None, Some(-2)])
+        );
+        assert_eq!(all("", optional(literal("$"))), Ok(None));
+        assert_eq!(
+            all("$ cd a\n$ ls", lines(preceded("$ ", rest_of_line()))),
+            Ok(vec!["cd a", "ls"])
+        );
+    }
+
+    fn error<'a, T: fmt::Debug>(source: &'a str, parser: impl Parser<'a, T>) -> String {
+        all(source, parser).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parse_errors_say_where_they_are() {
+        assert_eq!(
+            error("x=1, y=2\nx=3, z=4", lines(point())),
+            "Invalid puzzle input: line 2, column 4: expected ', y=', found ', z=4'"
+        );
+        assert_eq!(
+            error("x=1, y=99999999999", lines(point())),
+            "Invalid puzzle input: line 1, column 8: '99999999999' is not a valid number"
+        );
+        assert_eq!(
+            error("x=1, y=2 and more", lines(point())),
+            "Invalid puzzle input: line 1, column 9: expected the end of the line, found ' and more'"
+        );
+        assert_eq!(
+            error("x=1, y=2\n\nx=3, y=4", lines(point())),
+            "Invalid puzzle input: line 3, column 1: expected the end of the input, found 'x=3, y=4'"
+        );
+        assert_eq!(
+            error(
+                "nop",
+                either(map(literal("noop"), |_| 0), preceded("addx ", integer())),
+            ),
+            "Invalid puzzle input: line 1, column 1: expected 'noop' or 'addx ', found 'nop'"
+        );
+        assert_eq!(
+            error(
+                "[1,]",
+                preceded("[", optional(separated(integer::<u8>(), ",")))
+            ),
+            "Invalid puzzle input: line 1, column 4: expected a number, found ']'"
+        );
+        assert_eq!(
+            error("", integer::<u8>()),
+            "Invalid puzzle input: line 1, column 1: expected a number, found the end of the input"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use crate::trace;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// The slowest solvers -- 10,000 rounds of keep away, searching 16 trillion positions for a distress
+// beacon -- report how far along they are, which is drawn as a progress bar on stderr:
+//
+//     [day 11] round [#############-----------------]  46% (4600/10000)
+//
+// Solvers report their progress with the `progress!` macro, which reports nothing unless the
+// progress bar has been enabled: only when stderr is a terminal, which nothing else is writing
+// to. The bar only appears once a solver has been running for a little while, so quick ones don't
+// flicker, and it's cleared when the solver is done.
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+const DELAY: Duration = Duration::from_millis(250);
+const INTERVAL: Duration = Duration::from_millis(100);
+const WIDTH: usize = 30;
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+// Without a bar, when progress isn't being shown, reporting it costs next to nothing.
+pub struct Progress(Option<Bar>);
+
+struct Bar {
+    day: usize,
+    kind: &'static str,
+    total: u64,
+    started: Instant,
+    drawn: Option<Instant>,
+}
+
+impl Progress {
+    pub fn new(module: &str, kind: &'static str, total: u64) -> Self {
+        let bar = ENABLED
+            .load(Ordering::Relaxed)
+            .then(|| trace::day(module))
+            .flatten()
+            .map(|day| Bar {
+                day,
+                kind,
+                total,
+                started: Instant::now(),
+                drawn: None,
+            });
+        Progress(bar)
+    }
+
+    pub fn update(&mut self, done: u64) {
+        let Some(bar) = &mut self.0 else {
+            return;
+        };
+        let now = Instant::now();
+        let due = match bar.drawn {
+            Some(drawn) => now - drawn >= INTERVAL,
+            None => now - bar.started >= DELAY,
+        };
+        if due {
+            bar.drawn = Some(now);
+            let _ = write!(
+                io::stderr().lock(),
+                "\r{}\x1b[K",
+                render(bar.day, bar.kind, done, bar.total)
+            );
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.0.as_ref().is_some_and(|bar| bar.drawn.is_some()) {
+            let _ = write!(io::stderr().lock(), "\r\x1b[K");
+        }
+    }
+}
+
+fn render(day: usize, kind: &str, done: u64, total: u64) -> String {
+    let fraction = if total == 0 {
+        1.0
+    } else {
+        done.min(total) as f64 / total as f64
+    };
+    let filled = (fraction * WIDTH as f64) as usize;
+    format!(
+        "[day {day}] {kind} [{}{}] {:>3}% ({done}/{total})",
+        "#".repeat(filled),
+        "-".repeat(WIDTH - filled),
+        (fraction * 100.0) as u32
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_renders_a_bar() {
+        assert_eq!(
+            render(11, "round", 4600, 10_000),
+            "[day 11] round [#############-----------------]  46% (4600/10000)"
+        );
+        assert_eq!(
+            render(15, "searched", 0, 0),
+            "[day 15] searched [##############################] 100% (0/0)"
+        );
+        assert!(Progress::new("aoc_2022::day_11", "round", 10).0.is_none());
+    }
+}
@@ -0,0 +1,61 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use crate::generate::{first_failure, Rng};
+
+// Properties are invariants that should hold for every valid puzzle input, not just the examples.
+// Each one is checked against inputs generated from many seeds; when it fails, the smallest
+// failing size for that seed is reported, so the input can be reproduced with
+// `generate --day <day> --seed <seed> --size <size>`.
+//
+// The property is also handed the generator's random number generator, for properties that need
+// to perturb the input they are given.
+
+pub fn check(
+    name: &str,
+    generate: fn(&mut Rng, usize) -> String,
+    property: impl Fn(&mut Rng, &str) -> bool,
+) -> Result<(), String> {
+    let holds = |seed: u64, size: usize| {
+        let rng = &mut Rng::new(seed);
+        let input = generate(rng, size);
+        property(rng, &input)
+    };
+
+    match first_failure(|seed, size| !holds(seed, size)) {
+        Some((seed, size)) => Err(format!(
+            "{name} does not hold for seed {seed}, size {size}:\n{}",
+            generate(&mut Rng::new(seed), size)
+        )),
+        None => Ok(()),
+    }
+}
+
+pub fn assert_holds(
+    name: &str,
+    generate: fn(&mut Rng, usize) -> String,
+    property: impl Fn(&mut Rng, &str) -> bool,
+) {
+    if let Err(failure) = check(name, generate, property) {
+        panic!("{failure}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::digits;
+
+    #[test]
+    fn property_holds() {
+        assert!(check("digits", digits, |_, input| input
+            .lines()
+            .all(|line| line.len() == 1))
+        .is_ok());
+    }
+
+    #[test]
+    fn property_reports_smallest_failure() {
+        let failure = check("short", digits, |_, input| input.lines().count() < 3).unwrap_err();
+        assert!(failure.starts_with("short does not hold for seed 2, size 3:\n"));
+    }
+}
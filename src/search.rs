@@ -0,0 +1,423 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+// Searches over states -- squares of a heightmap, valves and the time left, the blizzards at a
+// given minute -- where a state's neighbours are whatever the closure passed in says they are.
+//
+// Every state a search reaches is remembered, with the state it was reached from, so that the way
+// to any of them can be followed back. A state is recognised as already reached by its key, which
+// is the state itself unless a search is given a `key` function: a state that carries the time,
+// say, can be keyed by the time modulo the period it repeats with, so the search doesn't wander
+// round in circles.
+//
+// No day takes a weighted step yet, or explores everything there is to reach: until one does, only
+// the tests use those searches.
+
+// The states along a route, from where it starts to where it ends, and what it cost.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<S, C = usize> {
+    pub states: Vec<S>,
+    pub cost: C,
+}
+
+impl<S, C> Path<S, C> {
+    // How many steps the route takes.
+    pub fn steps(&self) -> usize {
+        self.states.len() - 1
+    }
+}
+
+// The states a search has reached, each with the one it was reached from, if any.
+#[derive(Clone)]
+struct Tree<S> {
+    nodes: Vec<(S, Option<usize>)>,
+}
+
+impl<S: Clone> Tree<S> {
+    fn push(&mut self, state: S, parent: Option<usize>) -> usize {
+        self.nodes.push((state, parent));
+        self.nodes.len() - 1
+    }
+
+    fn path_to(&self, node: usize) -> Vec<S> {
+        let mut path = Vec::new();
+        let mut node = Some(node);
+        while let Some(idx) = node {
+            path.push(self.nodes[idx].0.clone());
+            node = self.nodes[idx].1;
+        }
+        path.reverse();
+        path
+    }
+}
+
+// A breadth-first search, a level at a time: after n steps, the frontier is every state that is n
+// steps from the nearest start. It can start from several states at once.
+#[derive(Clone)]
+pub struct Bfs<S, K = S> {
+    key: fn(&S) -> K,
+    tree: Tree<S>,
+    reached: HashMap<K, usize>,
+    frontier: Vec<usize>,
+    depth: usize,
+}
+
+impl<S: Clone + Eq + Hash> Bfs<S> {
+    pub fn new(starts: impl IntoIterator<Item = S>) -> Self {
+        Bfs::by_key(starts, S::clone)
+    }
+}
+
+impl<S: Clone, K: Eq + Hash> Bfs<S, K> {
+    pub fn by_key(starts: impl IntoIterator<Item = S>, key: fn(&S) -> K) -> Self {
+        let mut search = Bfs {
+            key,
+            tree: Tree { nodes: Vec::new() },
+            reached: HashMap::new(),
+            frontier: Vec::new(),
+            depth: 0,
+        };
+        for start in starts {
+            if let Some(node) = search.reach(start, None) {
+                search.frontier.push(node);
+            }
+        }
+        search
+    }
+
+    // Remember `state` as reached, unless it already was.
+    fn reach(&mut self, state: S, parent: Option<usize>) -> Option<usize> {
+        match self.reached.entry((self.key)(&state)) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => Some(*entry.insert(self.tree.push(state, parent))),
+        }
+    }
+
+    // The states that are `depth` steps from the nearest start.
+    pub fn frontier(&self) -> impl Iterator<Item = &S> {
+        self.frontier.iter().map(|&node| &self.tree.nodes[node].0)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.frontier.is_empty()
+    }
+
+    pub fn has_reached(&self, state: &S) -> bool {
+        self.reached.contains_key(&(self.key)(state))
+    }
+
+    // Every state reached so far, in the order they were reached.
+    pub fn reached(&self) -> impl Iterator<Item = &S> {
+        self.tree.nodes.iter().map(|(state, _)| state)
+    }
+
+    // A shortest route from a start to `state`, if it has been reached.
+    pub fn path_to(&self, state: &S) -> Option<Path<S>> {
+        let node = *self.reached.get(&(self.key)(state))?;
+        let states = self.tree.path_to(node);
+        Some(Path {
+            cost: states.len() - 1,
+            states,
+        })
+    }
+
+    // Move the frontier on a step, to the neighbours of the states on it that haven't been reached
+    // yet. `expanded` is told, for each state that was on the frontier, how many new states it
+    // led to.
+    pub fn step<I: IntoIterator<Item = S>>(
+        &mut self,
+        mut neighbours: impl FnMut(&S) -> I,
+        mut expanded: impl FnMut(&S, usize),
+    ) {
+        let mut frontier = Vec::new();
+        for node in std::mem::take(&mut self.frontier) {
+            let reached = frontier.len();
+            for next in neighbours(&self.tree.nodes[node].0) {
+                frontier.extend(self.reach(next, Some(node)));
+            }
+            expanded(&self.tree.nodes[node].0, frontier.len() - reached);
+        }
+        self.frontier = frontier;
+        self.depth += 1;
+    }
+
+    // Search until a state on the frontier is a goal, and return the route to it -- the shortest
+    // there is to any goal.
+    pub fn find<I: IntoIterator<Item = S>>(
+        &mut self,
+        mut neighbours: impl FnMut(&S) -> I,
+        mut is_goal: impl FnMut(&S) -> bool,
+    ) -> Option<Path<S>> {
+        loop {
+            if let Some(goal) = self.frontier().find(|state| is_goal(state)) {
+                return self.path_to(&goal.clone());
+            }
+            if self.is_done() {
+                return None;
+            }
+            self.step(&mut neighbours, |_, _| {});
+        }
+    }
+
+    // Reach everything there is to reach -- a flood fill.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn explore<I: IntoIterator<Item = S>>(&mut self, mut neighbours: impl FnMut(&S) -> I) {
+        while !self.is_done() {
+            self.step(&mut neighbours, |_, _| {});
+        }
+    }
+}
+
+// The shortest route from any of `starts` to a goal, where every step costs the same.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbours: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    Bfs::new(starts).find(neighbours, is_goal)
+}
+
+// The cheapest route from any of `starts` to a goal, where each neighbour comes with the cost of
+// the step to it.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn dijkstra<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbours: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    a_star_by_key(starts, neighbours, |_| C::default(), is_goal, S::clone)
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn dijkstra_by_key<S, K, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbours: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+    key: impl Fn(&S) -> K,
+) -> Option<Path<S, C>>
+where
+    S: Clone,
+    K: Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    a_star_by_key(starts, neighbours, |_| C::default(), is_goal, key)
+}
+
+// Like `dijkstra`, but trying the states that look closest to a goal first. For the route to be
+// the cheapest, `heuristic` mustn't ever guess more than it would really cost to get to a goal.
+pub fn a_star<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbours: impl FnMut(&S) -> I,
+    heuristic: impl FnMut(&S) -> C,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    a_star_by_key(starts, neighbours, heuristic, is_goal, S::clone)
+}
+
+pub fn a_star_by_key<S, K, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+    key: impl Fn(&S) -> K,
+) -> Option<Path<S, C>>
+where
+    S: Clone,
+    K: Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut tree = Tree { nodes: Vec::new() };
+    let mut cheapest = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if cheapest.insert(key(&start), C::default()).is_none() {
+            let estimate = heuristic(&start);
+            queue.push(Reverse((estimate, C::default(), tree.push(start, None))));
+        }
+    }
+
+    while let Some(Reverse((_, cost, node))) = queue.pop() {
+        let state = tree.nodes[node].0.clone();
+        // A state can be queued again once a cheaper way to it turns up; the dearer one is stale.
+        if cheapest
+            .get(&key(&state))
+            .is_some_and(|&cheapest| cheapest < cost)
+        {
+            continue;
+        }
+        if is_goal(&state) {
+            return Some(Path {
+                states: tree.path_to(node),
+                cost,
+            });
+        }
+        for (next, step) in neighbours(&state) {
+            let next_cost = cost + step;
+            let next_key = key(&next);
+            if cheapest
+                .get(&next_key)
+                .is_none_or(|&cheapest| next_cost < cheapest)
+            {
+                cheapest.insert(next_key, next_cost);
+                let estimate = next_cost + heuristic(&next);
+                queue.push(Reverse((estimate, next_cost, tree.push(next, Some(node)))));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+    use crate::grid::{Grid, Position};
+
+    // '#' is a wall; digits are how much it costs to step onto a square.
+    const MAZE: &str = "S911E\n1#9#1\n11111";
+
+    fn maze() -> Grid<char> {
+        Grid::parse(MAZE, Ok).unwrap()
+    }
+
+    fn open(grid: &Grid<char>, position: &Position) -> Vec<Position> {
+        grid.neighbours(*position)
+            .filter(|&next| grid[next] != '#')
+            .collect()
+    }
+
+    fn cost(grid: &Grid<char>, position: Position) -> usize {
+        grid[position].to_digit(10).map_or(1, |cost| cost as usize)
+    }
+
+    #[test]
+    fn search_bfs_finds_the_shortest_route() {
+        let grid = maze();
+        let (start, end) = (Point::new(0, 0), Point::new(4, 0));
+        let path = bfs(
+            [start],
+            |position| open(&grid, position),
+            |&position| position == end,
+        )
+        .unwrap();
+        assert_eq!(path.steps(), 4);
+        assert_eq!(path.states.first(), Some(&start));
+        assert_eq!(path.states.last(), Some(&end));
+        assert!(path
+            .states
+            .windows(2)
+            .all(|step| step[0].manhattan_distance(step[1]) == 1));
+
+        assert_eq!(
+            bfs([start], |position| open(&grid, position), |_| false),
+            None
+        );
+    }
+
+    #[test]
+    fn search_bfs_steps_a_level_at_a_time() {
+        let grid = maze();
+        let mut search = Bfs::new([Point::new(0, 0), Point::new(4, 0)]);
+        let mut expanded = Vec::new();
+        search.step(
+            |position| open(&grid, position),
+            |&position, reached| expanded.push((position, reached)),
+        );
+        assert_eq!(expanded, [(Point::new(0, 0), 2), (Point::new(4, 0), 2)]);
+        assert_eq!(search.depth(), 1);
+        assert_eq!(search.frontier().count(), 4);
+        assert!(search.has_reached(&Point::new(4, 1)));
+        assert!(!search.has_reached(&Point::new(4, 2)));
+        assert_eq!(search.path_to(&Point::new(4, 1)).unwrap().steps(), 1);
+
+        search.explore(|position| open(&grid, position));
+        assert_eq!(search.reached().count(), 13);
+        assert!(search.is_done());
+    }
+
+    #[test]
+    fn search_bfs_by_key_merges_states() {
+        // Counting up from 0 by 1 or 3, where only the count modulo 5 matters: every remainder is
+        // reached within two steps, and the third finds nothing new.
+        let mut search = Bfs::by_key([0_u32], |count| count % 5);
+        search.explore(|&count| [count + 1, count + 3]);
+        assert_eq!(search.reached().count(), 5);
+        assert_eq!(search.depth(), 3);
+        assert_eq!(search.path_to(&9).unwrap().states, [0, 1, 4]);
+    }
+
+    #[test]
+    fn search_dijkstra_and_a_star_find_the_cheapest_route() {
+        let grid = maze();
+        let (start, end) = (Point::new(0, 0), Point::new(4, 0));
+        let neighbours = |position: &Position| {
+            open(&grid, position)
+                .into_iter()
+                .map(|next| (next, cost(&grid, next)))
+                .collect::<Vec<_>>()
+        };
+
+        // Nothing is cheaper than 0, and there's no route to a square behind the walls.
+        assert_eq!(
+            dijkstra([start], neighbours, |&position| position == start).map(|path| path.cost),
+            Some(0)
+        );
+        assert_eq!(
+            dijkstra([start], neighbours, |&position| position
+                == Point::new(1, 1)),
+            None
+        );
+
+        let cheapest = dijkstra([start], neighbours, |&position| position == end).unwrap();
+        // The long way round the bottom is cheaper than the short way through the 9.
+        assert_eq!(cheapest.cost, 8);
+        assert_eq!(cheapest.steps(), 8);
+        assert_eq!(
+            cheapest.cost,
+            cheapest.states[1..]
+                .iter()
+                .map(|&position| cost(&grid, position))
+                .sum::<usize>()
+        );
+
+        let guided = a_star(
+            [start],
+            neighbours,
+            |position| position.manhattan_distance(end),
+            |&position| position == end,
+        )
+        .unwrap();
+        assert_eq!(guided.cost, cheapest.cost);
+
+        let by_row = dijkstra_by_key(
+            [start],
+            neighbours,
+            |position| position.y == 2,
+            |position| position.y,
+        );
+        assert_eq!(by_row.unwrap().cost, 2);
+    }
+}
@@ -0,0 +1,143 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::io::{self, BufRead, Write};
+
+// Some days are simulations that advance one step at a time: the rope (day 9), the CPU (day 10),
+// the monkeys' rounds (day 11), the search across the heightmap (day 12), and the falling sand
+// (day 14).
+//
+// A step is whatever unit the puzzle counts in -- a single move of the head of the rope, a clock
+// cycle, a round, one more square of distance from the start, or a unit of sand coming to rest.
+
+pub trait Simulation {
+    // The state the puzzle's answer is read from.
+    type Snapshot;
+
+    fn step(&mut self);
+
+    fn is_done(&self) -> bool;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    // A human-readable picture of the current state.
+    fn render(&self) -> String;
+
+    // Step until the simulation is done, returning how many steps that took.
+    fn run(&mut self) -> usize {
+        let mut steps = 0;
+        while !self.is_done() {
+            self.step();
+            steps += 1;
+        }
+        steps
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Playback {
+    // Run to completion, and show the final state.
+    Run,
+    // Run until the given step (or completion, if that comes first), and show that state.
+    StopAt(usize),
+    // Show every state, waiting for a new line on `input` between steps; 'q' stops early.
+    Step,
+}
+
+pub fn play<S: Simulation>(
+    simulation: &mut S,
+    playback: Playback,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let mut steps = 0;
+    let show = |simulation: &S, steps: usize, output: &mut dyn Write| {
+        write!(output, "{}", simulation.render())?;
+        writeln!(output, "step {steps}")
+    };
+
+    match playback {
+        Playback::Run => steps = simulation.run(),
+        Playback::StopAt(stop) => {
+            while steps < stop && !simulation.is_done() {
+                simulation.step();
+                steps += 1;
+            }
+        }
+        Playback::Step => {
+            let mut line = String::new();
+            while !simulation.is_done() {
+                show(simulation, steps, output)?;
+                output.flush()?;
+                line.clear();
+                if input.read_line(&mut line)? == 0 || line.trim() == "q" {
+                    return Ok(());
+                }
+                simulation.step();
+                steps += 1;
+            }
+        }
+    }
+    show(simulation, steps, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Countdown(usize);
+
+    impl Simulation for Countdown {
+        type Snapshot = usize;
+
+        fn step(&mut self) {
+            self.0 -= 1;
+        }
+
+        fn is_done(&self) -> bool {
+            self.0 == 0
+        }
+
+        fn snapshot(&self) -> usize {
+            self.0
+        }
+
+        fn render(&self) -> String {
+            format!("{}\n", self.0)
+        }
+    }
+
+    fn played(start: usize, playback: Playback, input: &str) -> String {
+        let mut output = Vec::new();
+        play(
+            &mut Countdown(start),
+            playback,
+            &mut input.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn simulation_runs_to_completion() {
+        let countdown = &mut Countdown(3);
+        assert_eq!(countdown.run(), 3);
+        assert_eq!(countdown.snapshot(), 0);
+        assert_eq!(played(3, Playback::Run, ""), "0\nstep 3\n");
+    }
+
+    #[test]
+    fn simulation_stops_at_step() {
+        assert_eq!(played(3, Playback::StopAt(1), ""), "2\nstep 1\n");
+        assert_eq!(played(3, Playback::StopAt(5), ""), "0\nstep 3\n");
+    }
+
+    #[test]
+    fn simulation_steps() {
+        assert_eq!(
+            played(2, Playback::Step, "\n\n"),
+            "2\nstep 0\n1\nstep 1\n0\nstep 2\n"
+        );
+        assert_eq!(played(2, Playback::Step, "q\n"), "2\nstep 0\n");
+    }
+}
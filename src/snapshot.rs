@@ -0,0 +1,140 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// Rendered output -- a grid, a CRT's picture, a solver's trace -- is checked against a snapshot of
+// what it should be, kept in `src/snapshots/<name>.txt`, so that a change to a renderer shows up
+// as a change to a file, to be reviewed like any other.
+//
+// A snapshot that doesn't match fails the test, with the lines that differ. Once the new output
+// has been checked, it's accepted by running the tests again with the variable below set, which
+// rewrites the snapshots that changed, and writes any that are new:
+//
+//     AOC_UPDATE_SNAPSHOTS=1 cargo test
+
+const UPDATE_VARIABLE: &str = "AOC_UPDATE_SNAPSHOTS";
+
+// Only so many differing lines are shown.
+const MAX_DIFF_LINES: usize = 40;
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/snapshots")
+        .join(format!("{name}.txt"))
+}
+
+fn updating() -> bool {
+    env::var_os(UPDATE_VARIABLE).is_some_and(|update| !update.is_empty())
+}
+
+pub fn check(name: &str, actual: &str) -> Result<(), String> {
+    let path = path(name);
+    let expected = fs::read_to_string(&path)
+        .ok()
+        .map(|snapshot| snapshot.replace("\r\n", "\n"));
+    if expected.as_deref() == Some(actual) {
+        return Ok(());
+    }
+    if updating() {
+        fs::create_dir_all(path.parent().unwrap()).map_err(|err| err.to_string())?;
+        return fs::write(&path, actual).map_err(|err| format!("can't write {name}: {err}"));
+    }
+
+    match expected {
+        None => Err(format!(
+            "there's no snapshot {name} yet; run the tests with {UPDATE_VARIABLE}=1 to write it:\n\
+             {actual}"
+        )),
+        Some(expected) => Err(format!(
+            "snapshot {name} doesn't match; run the tests with {UPDATE_VARIABLE}=1 to accept the \
+             change:\n{}",
+            diff(&expected, actual)
+        )),
+    }
+}
+
+pub fn assert_matches(name: &str, actual: &str) {
+    if let Err(mismatch) = check(name, actual) {
+        panic!("{mismatch}");
+    }
+}
+
+// The lines that were taken away, marked with '-', and added, marked with '+', numbered as they
+// are in the snapshot and in the new output respectively. Lines the two have in common -- as many
+// as can be matched up, in order -- are left out.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+
+    // common[i][j] is how many lines expected[i..] and actual[j..] have in common.
+    let mut common = vec![vec![0_usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            (i, j) = (i + 1, j + 1);
+        } else if j == actual.len() || (i < expected.len() && common[i + 1][j] >= common[i][j + 1])
+        {
+            lines.push(format!("{:>4} - {}", i + 1, expected[i]));
+            i += 1;
+        } else {
+            lines.push(format!("{:>4} + {}", j + 1, actual[j]));
+            j += 1;
+        }
+    }
+
+    if lines.len() > MAX_DIFF_LINES {
+        let more = lines.len() - MAX_DIFF_LINES;
+        lines.truncate(MAX_DIFF_LINES);
+        lines.push(format!("     ... and {more} more"));
+    }
+    if lines.is_empty() {
+        // Only the line endings at the very end differ.
+        lines.push("     (a newline at the end)".to_string());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_diffs_show_what_changed() {
+        assert_eq!(
+            diff("..#\n.##\n###\n", "..#\n###\n###\n#..\n"),
+            "   2 - .##\n   3 + ###\n   4 + #..".to_string()
+        );
+        assert_eq!(diff("a\nb\nc\n", "a\nc\n"), "   2 - b");
+        assert_eq!(diff("a\n", "a"), "     (a newline at the end)");
+
+        let long = (0..50).map(|line| format!("{line}\n")).collect::<String>();
+        let diff = diff("", &long);
+        assert_eq!(diff.lines().count(), MAX_DIFF_LINES + 1);
+        assert!(diff.ends_with("... and 10 more"));
+    }
+
+    #[test]
+    fn snapshot_mismatches_are_reported() {
+        assert_eq!(check("snapshot", "a snapshot\nof itself\n"), Ok(()));
+        if !updating() {
+            let mismatch = check("snapshot", "a snapshot\nof something else\n").unwrap_err();
+            assert!(mismatch.ends_with("   2 - of itself\n   2 + of something else"));
+            assert!(check("no_such_snapshot", "")
+                .unwrap_err()
+                .starts_with("there's no snapshot no_such_snapshot yet"));
+        }
+    }
+}
@@ -0,0 +1,214 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+// Trace events show what the solvers are doing along the way: each CPU cycle, each monkey's throw,
+// each crate moved, each square the search expands. They're written to stderr, one per line, as the
+// day, the kind of event, and its fields:
+//
+//     [day 10] cycle: cycle=20 x=21 signal=420
+//
+// Nothing is traced unless asked for: `-v` shows `Info` events, roughly one per step of a solver,
+// and `-vv` adds the `Debug` events within each step. `--trace` narrows them down further to some
+// days, some kinds of event, or both, e.g. `--trace 11:round,cycle,12`.
+//
+// Events are emitted with the `trace!` macro.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Info = 1,
+    Debug = 2,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Filter {
+    verbosity: u8,
+    rules: Vec<Rule>,
+}
+
+// A rule lets an event through if it matches both its day and its kind, when it has them.
+#[derive(Debug, PartialEq)]
+struct Rule {
+    day: Option<usize>,
+    kind: Option<String>,
+}
+
+impl Filter {
+    pub fn new(verbosity: u8, rules: &str) -> Result<Self, String> {
+        let rules = rules
+            .split(',')
+            .filter(|rule| !rule.is_empty())
+            .map(|rule| {
+                let (day, kind) = match rule.split_once(':') {
+                    Some((day, kind)) => (Some(day), Some(kind)),
+                    None if rule.bytes().all(|byte| byte.is_ascii_digit()) => (Some(rule), None),
+                    None => (None, Some(rule)),
+                };
+                Ok(Rule {
+                    day: day
+                        .map(|day| day.parse())
+                        .transpose()
+                        .map_err(|_| format!("'{rule}' should be <day>, <kind> or <day>:<kind>"))?,
+                    kind: kind.map(str::to_string),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Filter { verbosity, rules })
+    }
+
+    fn enables(&self, level: Level, day: usize, kind: &str) -> bool {
+        level as u8 <= self.verbosity
+            && (self.rules.is_empty()
+                || self.rules.iter().any(|rule| {
+                    rule.day.is_none_or(|rule| rule == day)
+                        && rule.kind.as_ref().is_none_or(|rule| rule == kind)
+                }))
+    }
+}
+
+static FILTER: OnceLock<Filter> = OnceLock::new();
+
+// Only the first filter installed takes effect.
+pub fn install(filter: Filter) {
+    let _ = FILTER.set(filter);
+}
+
+// A quick check on the level alone, so that a disabled event costs next to nothing.
+pub fn enabled(level: Level) -> bool {
+    FILTER
+        .get()
+        .is_some_and(|filter| level as u8 <= filter.verbosity)
+        || capturing(level)
+}
+
+// Events are attributed to the day whose module they come from.
+pub fn day(module: &str) -> Option<usize> {
+    module
+        .rsplit("::")
+        .next()?
+        .strip_prefix("day_")?
+        .parse()
+        .ok()
+}
+
+fn format_event(day: usize, kind: &str, fields: &[(&str, &dyn Display)]) -> String {
+    let mut event = format!("[day {day}] {kind}:");
+    for (name, value) in fields {
+        event.push_str(&format!(" {name}={value}"));
+    }
+    event
+}
+
+pub fn emit(level: Level, module: &str, kind: &str, fields: &[(&str, &dyn Display)]) {
+    #[cfg(test)]
+    if capturing(level) {
+        return CAPTURED.with_borrow_mut(|captured| {
+            if let (Some((filter, events)), Some(day)) = (captured, day(module)) {
+                if filter.enables(level, day, kind) {
+                    events.push_str(&format_event(day, kind, fields));
+                    events.push('\n');
+                }
+            }
+        });
+    }
+    let (Some(filter), Some(day)) = (FILTER.get(), day(module)) else {
+        return;
+    };
+    if filter.enables(level, day, kind) {
+        let _ = writeln!(io::stderr().lock(), "{}", format_event(day, kind, fields));
+    }
+}
+
+// Tests capture the events on their own thread, rather than have them written to stderr, to check
+// what a solver traces.
+#[cfg(test)]
+thread_local! {
+    static CAPTURED: std::cell::RefCell<Option<(Filter, String)>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(test)]
+fn capturing(level: Level) -> bool {
+    CAPTURED.with_borrow(|captured| {
+        captured
+            .as_ref()
+            .is_some_and(|(filter, _)| level as u8 <= filter.verbosity)
+    })
+}
+
+#[cfg(not(test))]
+fn capturing(_: Level) -> bool {
+    false
+}
+
+// The events traced while running `f`, one per line, as they'd be written to stderr.
+#[cfg(test)]
+pub fn capture(filter: Filter, f: impl FnOnce()) -> String {
+    CAPTURED.set(Some((filter, String::new())));
+    f();
+    CAPTURED
+        .take()
+        .map(|(_, events)| events)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_filters_by_level_day_and_kind() {
+        let filter = Filter::new(1, "").unwrap();
+        assert!(filter.enables(Level::Info, 10, "cycle"));
+        assert!(!filter.enables(Level::Debug, 10, "cycle"));
+        assert!(!Filter::default().enables(Level::Info, 10, "cycle"));
+
+        let filter = Filter::new(2, "11:round,cycle,12").unwrap();
+        assert!(filter.enables(Level::Info, 11, "round"));
+        assert!(!filter.enables(Level::Debug, 11, "throw"));
+        assert!(filter.enables(Level::Info, 10, "cycle"));
+        assert!(filter.enables(Level::Debug, 12, "expand"));
+        assert!(!filter.enables(Level::Info, 5, "move"));
+    }
+
+    #[test]
+    fn trace_rejects_malformed_filters() {
+        assert!(Filter::new(1, "x:cycle").is_err());
+        assert_eq!(
+            Filter::new(1, "10,,cycle").unwrap().rules,
+            vec![
+                Rule {
+                    day: Some(10),
+                    kind: None
+                },
+                Rule {
+                    day: None,
+                    kind: Some("cycle".to_string())
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_captures_events_on_this_thread() {
+        let events = capture(Filter::new(1, "10").unwrap(), || {
+            emit(Level::Info, "aoc_2022::day_10", "cycle", &[("cycle", &1)]);
+            emit(Level::Debug, "aoc_2022::day_10", "detail", &[]);
+            emit(Level::Info, "aoc_2022::day_11", "round", &[("round", &1)]);
+        });
+        assert_eq!(events, "[day 10] cycle: cycle=1\n");
+        assert!(!enabled(Level::Info));
+    }
+
+    #[test]
+    fn trace_formats_events() {
+        assert_eq!(day("aoc_2022::day_10"), Some(10));
+        assert_eq!(day("aoc_2022::trace"), None);
+        assert_eq!(
+            format_event(10, "cycle", &[("cycle", &20), ("x", &21)]),
+            "[day 10] cycle: cycle=20 x=21"
+        );
+    }
+}
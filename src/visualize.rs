@@ -0,0 +1,296 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use crate::simulation::Simulation;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// Simulations whose state is a grid can be drawn as image frames, and recorded as an animation.
+//
+// Each frame is a grid of indices into the day's palette, one cell per square of the puzzle. Every
+// day's palette has far fewer than 256 colours, which is all a GIF (or an indexed PNG) can hold, so
+// frames can be written out as they are.
+
+pub type Rgb = [u8; 3];
+
+pub trait Visualize: Simulation {
+    const PALETTE: &'static [Rgb];
+
+    fn frame(&self) -> Frame;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    // A frame filled with the first colour of the palette.
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: u8) {
+        self.pixels[y * self.width + x] = colour;
+    }
+
+    // Each cell becomes a `scale` x `scale` square of pixels.
+    fn scaled(&self, scale: usize) -> Frame {
+        let mut scaled = Frame::new(self.width * scale, self.height * scale);
+        for y in 0..scaled.height {
+            for x in 0..scaled.width {
+                scaled.set(x, y, self.get(x / scale, y / scale));
+            }
+        }
+        scaled
+    }
+
+    fn write_ppm(&self, palette: &[Rgb], writer: &mut impl Write) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for &colour in &self.pixels {
+            writer.write_all(&palette[colour as usize])?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Animation {
+    palette: &'static [Rgb],
+    frames: Vec<Frame>,
+}
+
+// Hundredths of a second between frames, and how long the final state is held for.
+const FRAME_DELAY: u16 = 4;
+const FINAL_DELAY: u16 = 300;
+
+// Images are scaled up until the longest side is roughly this many pixels.
+const TARGET_SIZE: usize = 600;
+
+impl Animation {
+    fn scale(&self) -> usize {
+        let longest = self.frames[0].width().max(self.frames[0].height());
+        (TARGET_SIZE / longest).max(1)
+    }
+
+    fn flat_palette(&self) -> Vec<u8> {
+        self.palette.concat()
+    }
+
+    pub fn write_gif(&self, writer: impl Write) -> io::Result<()> {
+        let scale = self.scale();
+        let (width, height) = (
+            self.frames[0].width() * scale,
+            self.frames[0].height() * scale,
+        );
+        let too_large = || io::Error::other("the frames are too large for a GIF");
+
+        let mut encoder = gif::Encoder::new(
+            writer,
+            u16::try_from(width).map_err(|_| too_large())?,
+            u16::try_from(height).map_err(|_| too_large())?,
+            &self.flat_palette(),
+        )
+        .map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+
+        for (idx, frame) in self.frames.iter().enumerate() {
+            let scaled = frame.scaled(scale);
+            let mut gif_frame =
+                gif::Frame::from_indexed_pixels(width as u16, height as u16, scaled.pixels, None);
+            gif_frame.delay = if idx == self.frames.len() - 1 {
+                FINAL_DELAY
+            } else {
+                FRAME_DELAY
+            };
+            encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    // An animated PNG (APNG); viewers without APNG support show the first frame.
+    pub fn write_png(&self, writer: impl Write) -> io::Result<()> {
+        let scale = self.scale();
+        let (width, height) = (
+            self.frames[0].width() * scale,
+            self.frames[0].height() * scale,
+        );
+
+        let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(self.flat_palette());
+        encoder
+            .set_animated(self.frames.len() as u32, 0)
+            .map_err(io::Error::other)?;
+        encoder
+            .set_frame_delay(FRAME_DELAY, 100)
+            .map_err(io::Error::other)?;
+
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        for (idx, frame) in self.frames.iter().enumerate() {
+            if idx == self.frames.len() - 1 {
+                writer
+                    .set_frame_delay(FINAL_DELAY, 100)
+                    .map_err(io::Error::other)?;
+            }
+            writer
+                .write_image_data(&frame.scaled(scale).pixels)
+                .map_err(io::Error::other)?;
+        }
+        writer.finish().map_err(io::Error::other)
+    }
+
+    // One PPM per frame, numbered: `out.ppm` becomes `out_0000.ppm`, `out_0001.ppm`, ...
+    pub fn write_ppm_frames(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let scale = self.scale();
+        let stem = path.with_extension("");
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(idx, frame)| {
+                let path = PathBuf::from(format!("{}_{idx:04}.ppm", stem.display()));
+                let mut writer = BufWriter::new(File::create(&path)?);
+                frame.scaled(scale).write_ppm(self.palette, &mut writer)?;
+                writer.flush()?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    // The format is chosen by the file's extension: .gif, .png (animated), or .ppm (a sequence of
+    // frames).
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gif") => self.write_gif(BufWriter::new(File::create(path)?)),
+            Some("png") => self.write_png(BufWriter::new(File::create(path)?)),
+            Some("ppm") => self.write_ppm_frames(path).map(drop),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "can't tell what format to save {} in: use .gif, .png or .ppm",
+                    path.display()
+                ),
+            )),
+        }
+    }
+}
+
+// Record a simulation from its current state to completion.
+// Long simulations are sampled, so that there are at most `max_frames` frames; the first and
+// final states are always included.
+pub fn record<S: Visualize + Clone>(mut simulation: S, max_frames: usize) -> Animation {
+    let steps = simulation.clone().run();
+    let every = steps.div_ceil(max_frames.max(2) - 1).max(1);
+
+    let mut frames = vec![simulation.frame()];
+    let mut step = 0;
+    while !simulation.is_done() {
+        simulation.step();
+        step += 1;
+        if step % every == 0 || simulation.is_done() {
+            frames.push(simulation.frame());
+        }
+    }
+    Animation {
+        palette: S::PALETTE,
+        frames,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Fill(usize);
+
+    impl Simulation for Fill {
+        type Snapshot = usize;
+
+        fn step(&mut self) {
+            self.0 += 1;
+        }
+
+        fn is_done(&self) -> bool {
+            self.0 == 9
+        }
+
+        fn snapshot(&self) -> usize {
+            self.0
+        }
+
+        fn render(&self) -> String {
+            "#".repeat(self.0)
+        }
+    }
+
+    impl Visualize for Fill {
+        const PALETTE: &'static [Rgb] = &[[0, 0, 0], [255, 255, 255]];
+
+        fn frame(&self) -> Frame {
+            let mut frame = Frame::new(3, 3);
+            (0..self.0).for_each(|idx| frame.set(idx % 3, idx / 3, 1));
+            frame
+        }
+    }
+
+    #[test]
+    fn visualize_records_samples() {
+        let animation = record(Fill(0), 4);
+        assert_eq!(animation.frames.len(), 4);
+        assert_eq!(animation.frames[0], Frame::new(3, 3));
+        assert_eq!(animation.frames[1].get(2, 0), 1);
+        assert_eq!(animation.frames[1].get(0, 1), 0);
+        assert_eq!(animation.frames[3].pixels, vec![1; 9]);
+        assert_eq!(record(Fill(0), 100).frames.len(), 10);
+    }
+
+    #[test]
+    fn visualize_writes_images() {
+        let animation = record(Fill(0), 3);
+        let scale = TARGET_SIZE / 3;
+
+        let mut ppm = Vec::new();
+        animation.frames[2]
+            .scaled(2)
+            .write_ppm(Fill::PALETTE, &mut ppm)
+            .unwrap();
+        assert!(ppm.starts_with(b"P6\n6 6\n255\n"));
+        assert_eq!(ppm.len(), 11 + 6 * 6 * 3);
+
+        let mut gif = Vec::new();
+        animation.write_gif(&mut gif).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(gif.as_slice()).unwrap();
+        assert_eq!(decoder.width() as usize, 3 * scale);
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 3);
+
+        let mut png = Vec::new();
+        animation.write_png(&mut png).unwrap();
+        let reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        assert_eq!(reader.info().animation_control.unwrap().num_frames, 3);
+    }
+}
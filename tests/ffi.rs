@@ -0,0 +1,66 @@
+// Build the static library and the C test program against it and its header, and run it.
+#![cfg(all(feature = "ffi", unix))]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// The header in `include/` is the one C programs are given, so it has to be the one the build
+// script generates. Like the snapshots, it's rewritten when it differs, with AOC_UPDATE_SNAPSHOTS.
+#[test]
+fn ffi_header_is_up_to_date() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let generated = fs::read_to_string(Path::new(env!("OUT_DIR")).join("aoc2022.h")).unwrap();
+    let committed = root.join("include/aoc2022.h");
+    if fs::read_to_string(&committed).ok().as_deref() == Some(&*generated) {
+        return;
+    }
+    if env::var_os("AOC_UPDATE_SNAPSHOTS").is_some_and(|update| !update.is_empty()) {
+        fs::write(&committed, generated).unwrap();
+    } else {
+        panic!(
+            "include/aoc2022.h is out of date with src/ffi.rs; \
+             rerun with AOC_UPDATE_SNAPSHOTS=1 to rewrite it"
+        );
+    }
+}
+
+#[test]
+fn ffi_from_c() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let target = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ffi");
+    let program = target.join("ffi_solve");
+
+    // The static library is built again, in a target directory of its own, since the one these
+    // tests were built in is still locked by the `cargo test` running them.
+    let built = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+        .current_dir(&root)
+        .args(["build", "--lib"])
+        .args(["--no-default-features", "--features", "all-days,ffi"])
+        .arg("--target-dir")
+        .arg(&target)
+        .status()
+        .unwrap();
+    assert!(built.success(), "the static library didn't build");
+
+    let compiled = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(root.join("tests/ffi/solve.c"))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg(target.join("debug/libaoc_2022.a"))
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .expect("a C compiler should be installed, as cc or $CC");
+    assert!(compiled.success(), "the C test program didn't compile");
+
+    let output = Command::new(&program).output().unwrap();
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{report}");
+    assert_eq!(
+        report.lines().filter(|line| line.ends_with(": ok")).count(),
+        8,
+        "{report}"
+    );
+}
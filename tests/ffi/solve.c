@@ -0,0 +1,66 @@
+/* Calls the solvers through the FFI layer, the way a C program would, and checks what comes back:
+ * the answer, or the start of the message saying why there isn't one. Built and run by
+ * tests/ffi.rs, which links it against the static library. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "aoc2022.h"
+
+static int failures = 0;
+
+static void expect(const char *name, uint32_t day, uint32_t part, const char *input,
+                   size_t capacity, Aoc2022Status expected_status, const char *expected_output) {
+    char out_buf[64];
+    size_t out_len = capacity;
+    Aoc2022Status status =
+        aoc2022_solve(day, part, (const uint8_t *)input, strlen(input), out_buf, &out_len);
+
+    if (status != expected_status) {
+        printf("%s: status %d, expected %d\n", name, status, expected_status);
+        failures++;
+    } else if (expected_output != NULL && strncmp(out_buf, expected_output, strlen(expected_output)) != 0) {
+        printf("%s: output '%.*s', expected '%s'\n", name, (int)out_len, out_buf,
+               expected_output);
+        failures++;
+    } else {
+        printf("%s: ok\n", name);
+    }
+}
+
+int main(void) {
+    const char *calories = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+    const char *overflows = "Monkey 0:\n"
+                            "  Starting items: 4294967296\n"
+                            "  Operation: new = old * old\n"
+                            "  Test: divisible by 2\n"
+                            "    If true: throw to monkey 1\n"
+                            "    If false: throw to monkey 1\n"
+                            "\n"
+                            "Monkey 1:\n"
+                            "  Starting items: 1\n"
+                            "  Operation: new = old + 1\n"
+                            "  Test: divisible by 2\n"
+                            "    If true: throw to monkey 0\n"
+                            "    If false: throw to monkey 0\n";
+
+    expect("part 1", 1, 1, calories, 64, AOC2022_STATUS_OK, "24000");
+    expect("part 2", 1, 2, calories, 64, AOC2022_STATUS_OK, "45000");
+    expect("no such day", 26, 1, calories, 64, AOC2022_STATUS_NO_SUCH_DAY, NULL);
+    expect("no such part", 1, 3, calories, 64, AOC2022_STATUS_NO_SUCH_PART,
+           "day 1 has no part 3");
+    expect("invalid UTF-8", 1, 1, "\xff\n", 64, AOC2022_STATUS_INVALID_UTF8, NULL);
+    expect("parse error", 1, 1, "lots\n", 64, AOC2022_STATUS_PARSE_ERROR,
+           "Invalid puzzle input: 'lots' is not a valid number");
+    expect("panic", 11, 1, overflows, 64, AOC2022_STATUS_PANICKED, "panicked at src/day_11.rs:");
+    expect("buffer too small", 1, 2, calories, 5, AOC2022_STATUS_BUFFER_TOO_SMALL, NULL);
+
+    size_t out_len = 0;
+    aoc2022_solve(1, 2, (const uint8_t *)calories, strlen(calories), NULL, &out_len);
+    if (out_len != strlen("45000")) {
+        printf("length query: %zu, expected 5\n", out_len);
+        failures++;
+    }
+
+    return failures == 0 ? 0 : 1;
+}